@@ -2,6 +2,18 @@
 //! The LLVM context constants.
 //!
 
+/// The `log` target of the intermediate representation dumps.
+pub static LOG_TARGET_DUMP: &str = "compiler_llvm_context::dump";
+
+/// The `log` target of the static analysis warnings.
+pub static LOG_TARGET_ANALYSIS: &str = "compiler_llvm_context::analysis";
+
+/// The code coverage storage counters base key preimage.
+pub static COVERAGE_STORAGE_KEY_PREIMAGE: &str = "zksync.coverage";
+
+/// The code coverage counter event signature.
+pub static COVERAGE_EVENT_SIGNATURE: &str = "Coverage(uint256)";
+
 /// The calldata pointer global variable name.
 pub static GLOBAL_CALLDATA_POINTER: &str = "ptr_calldata";
 
@@ -23,11 +35,35 @@ pub static GLOBAL_EXTRA_ABI_DATA: &str = "extra_abi_data";
 /// The active pointer global variable name.
 pub static GLOBAL_ACTIVE_POINTER: &str = "ptr_active";
 
-/// The external call data offset in the auxiliary heap.
-pub const HEAP_AUX_OFFSET_EXTERNAL_CALL: u64 = 0;
+/// The bit mask of the immutables written by the constructor global variable name.
+pub static GLOBAL_IMMUTABLES_WRITTEN: &str = "immutables_written";
+
+/// The system request cached value global variable name prefix.
+pub static GLOBAL_SYSTEM_REQUEST_CACHE_PREFIX: &str = "system_request_cache_";
+
+/// The deferred linker symbol global prefix, followed by the library path hash.
+pub static GLOBAL_LINKER_SYMBOL_PREFIX: &str = "__linker_symbol_";
+
+/// The read-only revert string global prefix, followed by the message hash.
+pub static GLOBAL_REVERT_STRING_PREFIX: &str = "revert_string_";
+
+/// The read-only sorted selector table global prefix, followed by the unique identifier.
+pub static GLOBAL_SELECTOR_TABLE_PREFIX: &str = "selector_table_";
+
+/// The Solidity `Error(string)` selector, used to encode the assertion messages.
+pub static ERROR_STRING_SELECTOR: &str = "08c379a0";
+
+/// The Solidity `Panic(uint256)` selector, used to encode the runtime check failures.
+pub static PANIC_SELECTOR: &str = "4e487b71";
+
+/// The Solidity panic code of a too large memory allocation.
+pub const PANIC_CODE_MEMORY_OVERFLOW: u64 = 0x41;
+
+/// The revert reason of a token call which has not succeeded according to the `SafeERC20` rules.
+pub static SAFE_TRANSFER_ERROR_MESSAGE: &str = "SafeERC20: ERC20 operation did not succeed";
 
-/// The constructor return data offset in the auxiliary heap.
-pub const HEAP_AUX_OFFSET_CONSTRUCTOR_RETURN_DATA: u64 = 8 * (compiler_common::SIZE_FIELD as u64);
+/// The upper bound of the kernel space addresses, where the system contracts are deployed.
+pub const KERNEL_SPACE_ADDRESS_BOUND: u64 = 1 << 16;
 
 /// The number of the extra ABI data arguments.
 pub const EXTRA_ABI_DATA_SIZE: usize = 2;
@@ -15,6 +15,24 @@ pub enum AddressSpace {
     HeapAuxiliary,
     /// The generic memory page.
     Generic,
+    /// The read-only code page of the executed contract, including the constants.
+    /// Consists of 32-byte cells.
+    Code,
+}
+
+impl AddressSpace {
+    ///
+    /// Returns the alignment of the loads and stores in bytes.
+    ///
+    /// The stack and the code page are addressed by cells, whereas the heaps and the generic
+    /// page are byte-addressable.
+    ///
+    pub fn alignment(&self) -> usize {
+        match self {
+            Self::Stack | Self::Code => compiler_common::SIZE_FIELD,
+            Self::Heap | Self::HeapAuxiliary | Self::Generic => 1,
+        }
+    }
 }
 
 impl From<AddressSpace> for inkwell::AddressSpace {
@@ -24,6 +42,22 @@ impl From<AddressSpace> for inkwell::AddressSpace {
             AddressSpace::Heap => Self::One,
             AddressSpace::HeapAuxiliary => Self::Two,
             AddressSpace::Generic => Self::Three,
+            AddressSpace::Code => Self::Four,
+        }
+    }
+}
+
+impl TryFrom<inkwell::AddressSpace> for AddressSpace {
+    type Error = inkwell::AddressSpace;
+
+    fn try_from(value: inkwell::AddressSpace) -> Result<Self, Self::Error> {
+        match value {
+            inkwell::AddressSpace::Zero => Ok(Self::Stack),
+            inkwell::AddressSpace::One => Ok(Self::Heap),
+            inkwell::AddressSpace::Two => Ok(Self::HeapAuxiliary),
+            inkwell::AddressSpace::Three => Ok(Self::Generic),
+            inkwell::AddressSpace::Four => Ok(Self::Code),
+            value => Err(value),
         }
     }
 }
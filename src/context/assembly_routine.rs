@@ -0,0 +1,49 @@
+//!
+//! The hand-written zkEVM assembly routine.
+//!
+
+use std::collections::BTreeSet;
+
+///
+/// The hand-written zkEVM assembly routine.
+///
+/// The routine is appended to the generated assembly text as is, so it must contain its own
+/// section directives and define a label equal to its name, which is called by the generated code.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyRoutine {
+    /// The routine name, which is also its entry label.
+    pub name: String,
+    /// The routine assembly text.
+    pub text: String,
+}
+
+impl AssemblyRoutine {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(name: String, text: String) -> Self {
+        Self { name, text }
+    }
+
+    ///
+    /// Returns the labels defined in the routine.
+    ///
+    pub fn labels(&self) -> BTreeSet<String> {
+        Self::parse_labels(self.text.as_str())
+    }
+
+    ///
+    /// Parses the labels defined in the zkEVM `assembly_text`.
+    ///
+    pub fn parse_labels(assembly_text: &str) -> BTreeSet<String> {
+        assembly_text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with(';'))
+            .filter_map(|line| line.strip_suffix(':'))
+            .filter(|label| !label.is_empty() && !label.contains(char::is_whitespace))
+            .map(str::to_owned)
+            .collect()
+    }
+}
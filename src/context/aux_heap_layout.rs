@@ -0,0 +1,73 @@
+//!
+//! The auxiliary heap layout.
+//!
+
+///
+/// The auxiliary heap layout.
+///
+/// The auxiliary heap is used by the compiler for the system contract requests and the
+/// constructor return data, so the offsets are bound to the VM protocol version.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AuxHeapLayout {
+    /// The external call data offset.
+    external_call_offset: u64,
+    /// The constructor return data offset.
+    constructor_return_data_offset: u64,
+}
+
+impl Default for AuxHeapLayout {
+    fn default() -> Self {
+        Self::v1()
+    }
+}
+
+impl AuxHeapLayout {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub const fn new(external_call_offset: u64, constructor_return_data_offset: u64) -> Self {
+        Self {
+            external_call_offset,
+            constructor_return_data_offset,
+        }
+    }
+
+    ///
+    /// The layout of the initial VM version.
+    ///
+    /// The external call data takes the first eight fields, followed by the constructor
+    /// return data.
+    ///
+    pub const fn v1() -> Self {
+        Self::new(0, 8 * (compiler_common::SIZE_FIELD as u64))
+    }
+
+    ///
+    /// Returns the external call data offset.
+    ///
+    pub fn external_call_offset(&self) -> u64 {
+        self.external_call_offset
+    }
+
+    ///
+    /// Returns the constructor return data offset, where the immutables array offset is written.
+    ///
+    pub fn constructor_return_data_offset(&self) -> u64 {
+        self.constructor_return_data_offset
+    }
+
+    ///
+    /// Returns the offset of the number of immutables in the constructor return data.
+    ///
+    pub fn immutables_number_offset(&self) -> u64 {
+        self.constructor_return_data_offset + compiler_common::SIZE_FIELD as u64
+    }
+
+    ///
+    /// Returns the offset of the immutables array in the constructor return data.
+    ///
+    pub fn immutables_offset(&self) -> u64 {
+        self.constructor_return_data_offset + (2 * compiler_common::SIZE_FIELD) as u64
+    }
+}
@@ -4,6 +4,11 @@
 
 use std::collections::BTreeMap;
 
+use crate::context::build_statistics::BuildStatistics;
+use crate::context::build_timings::BuildTimings;
+use crate::context::code_hash::CodeHash;
+use crate::context::coverage::CoverageCounter;
+
 ///
 /// The LLVM module build.
 ///
@@ -15,28 +20,173 @@ pub struct Build {
     pub assembly: zkevm_assembly::Assembly,
     /// The zkEVM binary bytecode.
     pub bytecode: Vec<u8>,
+    /// The zkEVM binary bytecode split into 32-byte words, as expected by the `ContractDeployer`.
+    pub bytecode_words: Vec<[u8; compiler_common::SIZE_FIELD]>,
     /// The zkEVM bytecode hash.
     pub hash: String,
+    /// The zkEVM bytecode hash parts.
+    pub code_hash: CodeHash,
     /// The hash-to-path mapping of the contract factory dependencies.
     pub factory_dependencies: BTreeMap<String, String>,
+    /// The function-to-frame-size mapping, where the sizes are measured in stack cells.
+    pub stack_sizes: BTreeMap<String, usize>,
+    /// The per-function instruction counts and the constants size.
+    pub statistics: BuildStatistics,
+    /// The applied, missed, and analysis LLVM optimization remarks in the emission order.
+    pub remarks: Vec<String>,
+    /// The code coverage counter manifest, if the instrumentation is enabled.
+    pub coverage: Vec<CoverageCounter>,
+    /// The deferred linker symbol-to-library-path mapping.
+    /// The symbols are defined with zero addresses, which must be replaced when linking.
+    pub linker_symbols: BTreeMap<String, String>,
+    /// The EVM bytecode stub embedding the zkEVM bytecode hash, if enabled.
+    /// Only intended for the tooling requiring the EVM bytecode fields to be populated.
+    pub evm_stub: Option<Vec<u8>>,
+    /// The wall-clock durations of the build pipeline stages.
+    pub timings: BuildTimings,
+    /// The textual LLVM IR before the optimization, if retained.
+    pub llvm_ir_unoptimized: Option<String>,
+    /// The textual LLVM IR after the optimization, if retained.
+    pub llvm_ir_optimized: Option<String>,
+    /// The optimized LLVM bitcode, if requested with `OutputFormat::Bitcode`.
+    pub bitcode: Option<Vec<u8>>,
+    /// The object file, if requested with `OutputFormat::Object`.
+    pub object: Option<Vec<u8>>,
 }
 
 impl Build {
+    /// The stack allocation operand prefix in the zkEVM text assembly.
+    const STACK_ALLOCATION_PREFIX: &'static str = "stack+=[";
+
+    /// The EVM stub code, that is, `PUSH1 0x00 DUP1 REVERT INVALID`.
+    pub const EVM_STUB_CODE: [u8; 5] = [0x60, 0x00, 0x80, 0xfd, 0xfe];
+
+    /// The EVM stub metadata length suffix size.
+    pub const EVM_STUB_SUFFIX_SIZE: usize = 2;
+
     ///
     /// A shortcut constructor.
     ///
     pub fn new(
         assembly_text: String,
         assembly: zkevm_assembly::Assembly,
-        bytecode: Vec<u8>,
-        hash: String,
+        bytecode_words: Vec<[u8; compiler_common::SIZE_FIELD]>,
+        hash: [u8; compiler_common::SIZE_FIELD],
     ) -> Self {
         Self {
             assembly_text,
             assembly,
-            bytecode,
-            hash,
+            bytecode: bytecode_words.iter().flatten().copied().collect(),
+            bytecode_words,
+            hash: hex::encode(hash),
+            code_hash: CodeHash::new(hash),
             factory_dependencies: BTreeMap::new(),
+            stack_sizes: BTreeMap::new(),
+            statistics: BuildStatistics::default(),
+            remarks: Vec::new(),
+            coverage: Vec::new(),
+            linker_symbols: BTreeMap::new(),
+            evm_stub: None,
+            timings: BuildTimings::default(),
+            llvm_ir_unoptimized: None,
+            llvm_ir_optimized: None,
+            bitcode: None,
+            object: None,
+        }
+    }
+
+    ///
+    /// Generates the EVM bytecode stub embedding the zkEVM bytecode `hash`.
+    ///
+    /// The stub reverts immediately if executed. The hash follows the code, and the stub is
+    /// padded with zeros to the whole number of words. The last two bytes contain the big-endian
+    /// length of the metadata section, that is, the hash and padding, like the Solidity metadata
+    /// length suffix, so the tooling can locate the hash from the end.
+    ///
+    pub fn evm_stub(hash: [u8; compiler_common::SIZE_FIELD]) -> Vec<u8> {
+        let unpadded_size = Self::EVM_STUB_CODE.len() + hash.len() + Self::EVM_STUB_SUFFIX_SIZE;
+        let size =
+            unpadded_size.div_ceil(compiler_common::SIZE_FIELD) * compiler_common::SIZE_FIELD;
+        let metadata_size = size - Self::EVM_STUB_CODE.len() - Self::EVM_STUB_SUFFIX_SIZE;
+
+        let mut stub = Vec::with_capacity(size);
+        stub.extend_from_slice(Self::EVM_STUB_CODE.as_slice());
+        stub.extend_from_slice(hash.as_slice());
+        stub.resize(size - Self::EVM_STUB_SUFFIX_SIZE, 0);
+        stub.extend_from_slice((metadata_size as u16).to_be_bytes().as_slice());
+        stub
+    }
+
+    ///
+    /// Parses the frame sizes allocated by the back-end from the zkEVM text assembly.
+    ///
+    /// The frame is allocated in the function prologue with a `stack+=[N]` operand, so the
+    /// largest allocation found in the function body is taken as its frame size.
+    /// The functions which do not allocate any stack are reported with zero size.
+    ///
+    pub fn parse_stack_sizes(assembly_text: &str) -> BTreeMap<String, usize> {
+        let mut stack_sizes = BTreeMap::new();
+        let mut function = None;
+
+        for line in assembly_text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(label) = line.strip_suffix(':') {
+                if !label.starts_with('.') && !label.contains(char::is_whitespace) {
+                    stack_sizes.insert(label.to_owned(), 0);
+                    function = Some(label.to_owned());
+                }
+                continue;
+            }
+
+            let (current, operand) =
+                match (function.as_ref(), line.find(Self::STACK_ALLOCATION_PREFIX)) {
+                    (Some(current), Some(index)) => (
+                        current,
+                        &line[index + Self::STACK_ALLOCATION_PREFIX.len()..],
+                    ),
+                    _ => continue,
+                };
+            let digits: String = operand
+                .chars()
+                .skip_while(|character| character.is_whitespace())
+                .take_while(char::is_ascii_digit)
+                .collect();
+            if let Ok(size) = digits.parse::<usize>() {
+                let entry = stack_sizes.entry(current.to_owned()).or_insert(0);
+                *entry = (*entry).max(size);
+            }
         }
+
+        stack_sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Build;
+
+    #[test]
+    fn evm_stub() {
+        let hash = [0xab; compiler_common::SIZE_FIELD];
+        let stub = Build::evm_stub(hash);
+
+        assert_eq!(stub.len() % compiler_common::SIZE_FIELD, 0);
+        assert!(stub.starts_with(Build::EVM_STUB_CODE.as_slice()));
+
+        let metadata_size = u16::from_be_bytes([stub[stub.len() - 2], stub[stub.len() - 1]]);
+        let metadata_start = stub.len() - Build::EVM_STUB_SUFFIX_SIZE - metadata_size as usize;
+        assert_eq!(metadata_start, Build::EVM_STUB_CODE.len());
+        assert_eq!(
+            &stub[metadata_start..metadata_start + compiler_common::SIZE_FIELD],
+            hash.as_slice()
+        );
+        assert!(stub[metadata_start + compiler_common::SIZE_FIELD
+            ..stub.len() - Build::EVM_STUB_SUFFIX_SIZE]
+            .iter()
+            .all(|byte| *byte == 0));
     }
 }
@@ -0,0 +1,202 @@
+//!
+//! The zkEVM assembly statistics.
+//!
+
+use std::collections::BTreeMap;
+
+///
+/// The zkEVM assembly statistics.
+///
+/// Shows the contribution of each function to the bytecode size, so the largest functions
+/// can be found without disassembling the bytecode.
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BuildStatistics {
+    /// The function-to-instruction-count mapping.
+    pub functions: BTreeMap<String, usize>,
+    /// The number of the constant cells in the data sections.
+    pub constant_cells: usize,
+}
+
+impl BuildStatistics {
+    /// The zkEVM instruction size in bytes.
+    pub const INSTRUCTION_SIZE: usize = 8;
+
+    /// The constant cell directive in the data sections.
+    const CELL_DIRECTIVE: &'static str = ".cell";
+
+    ///
+    /// Parses the statistics from the zkEVM text assembly.
+    ///
+    /// The instructions are attributed to the last global label in the code section, whereas
+    /// the local labels starting with `.` belong to the enclosing function.
+    ///
+    pub fn parse(assembly_text: &str) -> Self {
+        let mut statistics = Self::default();
+        let mut is_code_section = true;
+        let mut function: Option<String> = None;
+
+        for line in assembly_text.lines() {
+            let line = match line.find(';') {
+                Some(index) => &line[..index],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with(".text") {
+                is_code_section = true;
+                continue;
+            }
+            if line.starts_with(".rodata") || line.starts_with(".data") {
+                is_code_section = false;
+                function = None;
+                continue;
+            }
+
+            if let Some(label) = line.strip_suffix(':') {
+                if is_code_section && !label.starts_with('.') {
+                    statistics.functions.entry(label.to_owned()).or_insert(0);
+                    function = Some(label.to_owned());
+                }
+                continue;
+            }
+
+            if line.starts_with(Self::CELL_DIRECTIVE) {
+                if !is_code_section {
+                    statistics.constant_cells += 1;
+                }
+                continue;
+            }
+            if line.starts_with('.') || !is_code_section {
+                continue;
+            }
+
+            if let Some(function) = function.as_ref() {
+                *statistics
+                    .functions
+                    .get_mut(function.as_str())
+                    .expect("Always exists") += 1;
+            }
+        }
+
+        statistics
+    }
+
+    ///
+    /// Returns the total number of instructions.
+    ///
+    pub fn instructions(&self) -> usize {
+        self.functions.values().sum()
+    }
+
+    ///
+    /// Returns the code size in bytes, excluding the constants.
+    ///
+    pub fn code_size(&self) -> usize {
+        self.instructions() * Self::INSTRUCTION_SIZE
+    }
+
+    ///
+    /// Returns the constants size in bytes.
+    ///
+    pub fn constants_size(&self) -> usize {
+        self.constant_cells * compiler_common::SIZE_FIELD
+    }
+
+    ///
+    /// Returns at most `count` functions with the most instructions, the largest first.
+    ///
+    pub fn largest_functions(&self, count: usize) -> Vec<(&str, usize)> {
+        let mut functions: Vec<(&str, usize)> = self
+            .functions
+            .iter()
+            .map(|(name, instructions)| (name.as_str(), *instructions))
+            .collect();
+        functions.sort_by(|(name_1, size_1), (name_2, size_2)| {
+            size_2.cmp(size_1).then_with(|| name_1.cmp(name_2))
+        });
+        functions.truncate(count);
+        functions
+    }
+}
+
+impl std::fmt::Display for BuildStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instructions = self.instructions();
+        writeln!(
+            f,
+            "{:>12} {:>12} {:>8}  function",
+            "instructions", "bytes", "share"
+        )?;
+        for (name, count) in self.largest_functions(self.functions.len()) {
+            writeln!(
+                f,
+                "{:>12} {:>12} {:>7.2}%  {}",
+                count,
+                count * Self::INSTRUCTION_SIZE,
+                (count as f64) * 100.0 / (instructions.max(1) as f64),
+                name
+            )?;
+        }
+        write!(
+            f,
+            "{:>12} {:>12} {:>8}  total code, and {} bytes of constants",
+            instructions,
+            self.code_size(),
+            "",
+            self.constants_size(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildStatistics;
+
+    const ASSEMBLY: &str = r#"
+	.text
+	.file	"Test.sol:Test"
+	.globl	__entry
+__entry:
+.func_begin0:
+	add	r1, r0, r2 ; a comment
+	near_call	r0, @main, @DEFAULT_UNWIND
+.BB0_1:
+	ret
+.func_end0:
+
+main:
+	add	1, r0, r1
+	ret
+
+	.rodata.cst32
+	.p2align	5
+CPI0_0:
+	.cell 1
+	.cell 2
+"#;
+
+    #[test]
+    fn parse() {
+        let statistics = BuildStatistics::parse(ASSEMBLY);
+
+        assert_eq!(statistics.functions.len(), 2);
+        assert_eq!(statistics.functions.get("__entry"), Some(&3));
+        assert_eq!(statistics.functions.get("main"), Some(&2));
+        assert_eq!(
+            statistics.code_size(),
+            5 * BuildStatistics::INSTRUCTION_SIZE
+        );
+        assert_eq!(statistics.constants_size(), 2 * compiler_common::SIZE_FIELD);
+    }
+
+    #[test]
+    fn largest_functions() {
+        let statistics = BuildStatistics::parse(ASSEMBLY);
+
+        assert_eq!(statistics.largest_functions(1), vec![("__entry", 3)]);
+    }
+}
@@ -0,0 +1,62 @@
+//!
+//! The LLVM module build pipeline timings.
+//!
+
+use std::time::Duration;
+
+///
+/// The LLVM module build pipeline timings.
+///
+/// The wall-clock durations of the pipeline stages, allowing the front-ends to produce
+/// the per-contract compile-time reports.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BuildTimings {
+    /// The front-end translation, from the context creation to the build start, excluding
+    /// the dependency compilation.
+    pub translation: Duration,
+    /// The dependency compilation callbacks.
+    pub dependencies: Duration,
+    /// The unoptimized and optimized LLVM IR verification.
+    pub verification: Duration,
+    /// The LLVM IR optimization, including the additional IR rewrites.
+    pub optimization: Duration,
+    /// The assembly code generation.
+    pub codegen: Duration,
+    /// The assembly parsing and the bytecode assembling.
+    pub assembling: Duration,
+    /// The bytecode hashing.
+    pub hashing: Duration,
+}
+
+impl BuildTimings {
+    ///
+    /// Returns the total duration of the stages.
+    ///
+    pub fn total(&self) -> Duration {
+        self.translation
+            + self.dependencies
+            + self.verification
+            + self.optimization
+            + self.codegen
+            + self.assembling
+            + self.hashing
+    }
+}
+
+impl std::fmt::Display for BuildTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "translation {:?}, dependencies {:?}, verification {:?}, optimization {:?}, codegen {:?}, assembling {:?}, hashing {:?}, total {:?}",
+            self.translation,
+            self.dependencies,
+            self.verification,
+            self.optimization,
+            self.codegen,
+            self.assembling,
+            self.hashing,
+            self.total(),
+        )
+    }
+}
@@ -0,0 +1,79 @@
+//!
+//! The bounded calldata slice.
+//!
+
+use crate::context::address_space::AddressSpace;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The bounded calldata slice.
+///
+/// Is produced by the checked ABI decoding of the dynamic types, so the `length` elements
+/// starting at `offset` are guaranteed to be within the calldata.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct CalldataSlice<'ctx> {
+    /// The calldata offset of the first element.
+    pub offset: inkwell::values::IntValue<'ctx>,
+    /// The number of elements.
+    pub length: inkwell::values::IntValue<'ctx>,
+    /// The element size in bytes.
+    pub element_size: u64,
+}
+
+impl<'ctx> CalldataSlice<'ctx> {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(
+        offset: inkwell::values::IntValue<'ctx>,
+        length: inkwell::values::IntValue<'ctx>,
+        element_size: u64,
+    ) -> Self {
+        Self {
+            offset,
+            length,
+            element_size,
+        }
+    }
+
+    ///
+    /// Returns the generic pointer to the first element.
+    ///
+    pub fn pointer<D>(
+        &self,
+        context: &Context<'ctx, D>,
+    ) -> anyhow::Result<inkwell::values::PointerValue<'ctx>>
+    where
+        D: Dependency,
+    {
+        let calldata_pointer = context
+            .get_global(crate::r#const::GLOBAL_CALLDATA_POINTER)?
+            .into_pointer_value();
+        let pointer = unsafe {
+            context
+                .builder()
+                .build_gep(calldata_pointer, &[self.offset], "calldata_slice_pointer")
+        };
+        Ok(context.builder().build_pointer_cast(
+            pointer,
+            context.field_type().ptr_type(AddressSpace::Generic.into()),
+            "calldata_slice_pointer_casted",
+        ))
+    }
+
+    ///
+    /// Returns the slice size in bytes.
+    ///
+    pub fn size<D>(&self, context: &Context<'ctx, D>) -> inkwell::values::IntValue<'ctx>
+    where
+        D: Dependency,
+    {
+        context.builder().build_int_mul(
+            self.length,
+            context.field_const(self.element_size),
+            "calldata_slice_size",
+        )
+    }
+}
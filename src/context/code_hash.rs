@@ -0,0 +1,38 @@
+//!
+//! The zkEVM bytecode hash.
+//!
+
+///
+/// The zkEVM bytecode hash.
+///
+/// Unlike in EVM, the hash is not a plain digest, but is prefixed with the versioned bytecode
+/// length, which is checked by the `ContractDeployer` system contract.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeHash {
+    /// The bytecode hashing algorithm version.
+    pub version: u8,
+    /// The bytecode length in 32-byte words.
+    pub length_words: u16,
+    /// The truncated `sha256` digest of the bytecode words.
+    pub digest: [u8; Self::DIGEST_SIZE],
+}
+
+impl CodeHash {
+    /// The truncated digest size in bytes.
+    pub const DIGEST_SIZE: usize = compiler_common::SIZE_FIELD - 4;
+
+    ///
+    /// Splits the 32-byte `hash` into its parts.
+    ///
+    pub fn new(hash: [u8; compiler_common::SIZE_FIELD]) -> Self {
+        let mut digest = [0u8; Self::DIGEST_SIZE];
+        digest.copy_from_slice(&hash[4..]);
+
+        Self {
+            version: hash[0],
+            length_words: u16::from_be_bytes([hash[2], hash[3]]),
+            digest,
+        }
+    }
+}
@@ -0,0 +1,500 @@
+//!
+//! The experimental cold code splitting.
+//!
+
+use inkwell::types::BasicType;
+use inkwell::values::AsValueRef;
+
+use crate::context::address_space::AddressSpace;
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::function::runtime::Runtime;
+use crate::context::function::Function;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// Selects the functions to be moved to the companion library contract.
+///
+/// Only the functions which never return and do not depend on the caller state other than
+/// their arguments and storage are selected, such as the error builders, since a delegate call
+/// shares the storage, whereas the heap, calldata, and global variables of the caller are not
+/// accessible to the library. In particular, the whole revert data must be written by the
+/// function itself. The revert data of the library is then forwarded by the thunk.
+///
+/// The functions with fewer than `threshold` instructions are not worth the call overhead.
+///
+pub fn select<'ctx, D>(
+    context: &Context<'ctx, D>,
+    threshold: usize,
+) -> Vec<inkwell::values::FunctionValue<'ctx>>
+where
+    D: Dependency,
+{
+    let mut functions = Vec::new();
+    let mut function = context.module().get_first_function();
+    while let Some(current_function) = function {
+        function = current_function.get_next_function();
+
+        if is_candidate::<D>(context, current_function)
+            && is_revert_only(context, current_function).map_or(false, |size| size >= threshold)
+        {
+            functions.push(current_function);
+        }
+    }
+    functions
+}
+
+///
+/// Replaces the body of `function` with the thunk delegating to the companion library
+/// `library_path`, where the function is dispatched by `index`.
+///
+/// The index and arguments are passed as the 32-byte calldata words, and the library address
+/// is left to the deferred linking. The returned data is reverted with unconditionally, since
+/// the original function never returns.
+///
+pub fn thunk<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    index: usize,
+    library_path: &str,
+) -> anyhow::Result<()>
+where
+    D: Dependency,
+{
+    clear_body(function);
+
+    let entry_block = context
+        .llvm
+        .append_basic_block(function, "cold_thunk_entry");
+    let name = function.get_name().to_string_lossy().into_owned();
+    let current_function = context.function.take();
+    let current_block = context.builder().get_insert_block();
+    context.set_function(Function::new(
+        name,
+        function,
+        entry_block,
+        entry_block,
+        None,
+    ));
+    context.set_basic_block(entry_block);
+
+    let words = std::iter::once(context.field_const(index as u64))
+        .chain(
+            function
+                .get_param_iter()
+                .map(|parameter| parameter.into_int_value()),
+        )
+        .enumerate();
+    let mut input_length = 0;
+    for (position, word) in words {
+        let offset = (position * compiler_common::SIZE_FIELD) as u64;
+        let pointer = context.access_memory(
+            context.field_const(offset),
+            AddressSpace::Heap,
+            "cold_thunk_input_pointer",
+        );
+        context.build_store(pointer, word);
+        input_length = offset + compiler_common::SIZE_FIELD as u64;
+    }
+
+    let address = context.resolve_library_deferred(library_path);
+    let gas = crate::evm::ether_gas::gas(context)?
+        .expect("Always exists")
+        .into_int_value();
+    crate::evm::contract::delegatecall(
+        context,
+        gas,
+        address,
+        None,
+        context.field_const(0),
+        context.field_const(input_length),
+        context.field_const(0),
+        context.field_const(0),
+        false,
+    )?;
+    let return_data_size = crate::evm::return_data::size(context)?
+        .expect("Always exists")
+        .into_int_value();
+    crate::evm::return_data::copy(
+        context,
+        context.field_const(0),
+        context.field_const(0),
+        return_data_size,
+    )?;
+    context.build_exit(
+        IntrinsicFunction::Revert,
+        context.field_const(0),
+        return_data_size,
+    );
+
+    for declared in context.functions.values_mut() {
+        if declared.value == function {
+            declared.entry_block = entry_block;
+            declared.return_block = entry_block;
+        }
+    }
+    context.function = current_function;
+    if let Some(current_block) = current_block {
+        context.set_basic_block(current_block);
+    }
+    Ok(())
+}
+
+///
+/// Emits the companion library dispatcher calling the function at the index in the first
+/// calldata word with the arguments in the subsequent words, and terminates the current block.
+///
+/// The unknown indexes and the functions unexpectedly returning are reverted with empty data.
+///
+pub fn dispatcher<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    functions: &[inkwell::values::FunctionValue<'ctx>],
+) -> anyhow::Result<()>
+where
+    D: Dependency,
+{
+    let index = crate::evm::calldata::load(context, context.field_const(0))?
+        .expect("Always exists")
+        .into_int_value();
+    let default_block = context.append_basic_block("cold_dispatch_default_block");
+    let cases: Vec<(
+        inkwell::values::IntValue<'ctx>,
+        inkwell::basic_block::BasicBlock<'ctx>,
+    )> = (0..functions.len())
+        .map(|index| {
+            (
+                context.field_const(index as u64),
+                context.append_basic_block(
+                    context
+                        .name_indexed("cold_dispatch_case_block", index)
+                        .as_ref(),
+                ),
+            )
+        })
+        .collect();
+    context.build_switch(index, default_block, cases.as_slice())?;
+
+    for (function, (_index, block)) in functions.iter().zip(cases.into_iter()) {
+        context.set_basic_block(block);
+        let mut arguments = Vec::with_capacity(function.count_params() as usize);
+        for position in 0..function.count_params() as usize {
+            let offset = ((position + 1) * compiler_common::SIZE_FIELD) as u64;
+            let argument = crate::evm::calldata::load(context, context.field_const(offset))?
+                .expect("Always exists");
+            arguments.push(argument);
+        }
+        context.build_call(*function, arguments.as_slice(), "cold_dispatch_call");
+        context.build_exit(
+            IntrinsicFunction::Revert,
+            context.field_const(0),
+            context.field_const(0),
+        );
+    }
+
+    context.set_basic_block(default_block);
+    context.build_exit(
+        IntrinsicFunction::Revert,
+        context.field_const(0),
+        context.field_const(0),
+    );
+    Ok(())
+}
+
+///
+/// Whether `function` is a defined front-end function with the field-typed parameters.
+///
+fn is_candidate<D>(context: &Context<'_, D>, function: inkwell::values::FunctionValue) -> bool
+where
+    D: Dependency,
+{
+    if function.count_basic_blocks() == 0
+        || function.get_linkage() == inkwell::module::Linkage::WeakAny
+        || function
+            .get_param_iter()
+            .any(|parameter| parameter.get_type() != context.field_type().as_basic_type_enum())
+    {
+        return false;
+    }
+
+    let name = function.get_name().to_string_lossy();
+    let name = name
+        .rsplit(Context::<D>::CONTRACT_NAMESPACE_SEPARATOR)
+        .next()
+        .unwrap_or_default();
+    !(name.starts_with("llvm.")
+        || name.starts_with(Runtime::FUNCTION_PREFIX)
+        || name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
+        || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER)
+}
+
+///
+/// Returns the instruction count of `function`, if it never returns, only reads its stack
+/// and arguments, and reverts with the heap data written by itself.
+///
+/// The revert data is only recognized if its bounds are constant and it is covered by
+/// the constant offset heap stores preceding the revert in the same block.
+///
+fn is_revert_only<'ctx, D>(
+    context: &Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+) -> Option<usize>
+where
+    D: Dependency,
+{
+    let mut size = 0;
+    for block in function.get_basic_blocks().into_iter() {
+        let mut written = Vec::new();
+        let mut instruction = block.get_first_instruction();
+        while let Some(current) = instruction {
+            instruction = current.get_next_instruction();
+            size += 1;
+
+            match current.get_opcode() {
+                inkwell::values::InstructionOpcode::Return
+                | inkwell::values::InstructionOpcode::Invoke => return None,
+                inkwell::values::InstructionOpcode::Load => {
+                    let pointer = current.get_operand(0)?.left()?.into_pointer_value();
+                    let is_global = unsafe {
+                        !llvm_sys::core::LLVMIsAGlobalValue(pointer.as_value_ref()).is_null()
+                    };
+                    if is_global
+                        || pointer.get_type().get_address_space() != AddressSpace::Stack.into()
+                    {
+                        return None;
+                    }
+                }
+                inkwell::values::InstructionOpcode::Store => {
+                    let value = current.get_operand(0)?.left()?;
+                    let pointer = current.get_operand(1)?.left()?.into_pointer_value();
+                    if let (Some(offset), true) =
+                        (heap_constant_offset(context, pointer), value.is_int_value())
+                    {
+                        let width = value.into_int_value().get_type().get_bit_width() as u64;
+                        written.push((
+                            offset,
+                            offset.saturating_add(width / compiler_common::BITLENGTH_BYTE as u64),
+                        ));
+                    }
+                }
+                inkwell::values::InstructionOpcode::Call => {
+                    let callee = current
+                        .get_operand(current.get_num_operands() - 1)?
+                        .left()?
+                        .into_pointer_value();
+                    let name = callee.get_name().to_string_lossy();
+                    if !name.starts_with("llvm.")
+                        || name.starts_with("llvm.memcpy")
+                        || name.starts_with("llvm.memmove")
+                        || name == IntrinsicFunction::Return.name()
+                    {
+                        return None;
+                    }
+                    if name == IntrinsicFunction::Revert.name() {
+                        let abi_data = current.get_operand(0)?.left()?.into_int_value();
+                        let (offset, length) = revert_bounds(context, abi_data)?;
+                        if !is_covered(written.as_mut_slice(), offset, offset + length) {
+                            return None;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(size)
+}
+
+///
+/// Returns the heap offset `pointer` has been converted from, if it is a constant.
+///
+fn heap_constant_offset<'ctx, D>(
+    context: &Context<'ctx, D>,
+    pointer: inkwell::values::PointerValue<'ctx>,
+) -> Option<u64>
+where
+    D: Dependency,
+{
+    if pointer.get_type().get_address_space() != AddressSpace::Heap.into() {
+        return None;
+    }
+    if pointer.is_null() {
+        return Some(0);
+    }
+
+    if let Some(instruction) = pointer.as_instruction_value() {
+        if instruction.get_opcode() != inkwell::values::InstructionOpcode::IntToPtr {
+            return None;
+        }
+        return context.const_u64(instruction.get_operand(0)?.left()?.into_int_value());
+    }
+
+    unsafe {
+        let pointer = pointer.as_value_ref();
+        if llvm_sys::core::LLVMIsAConstantExpr(pointer).is_null()
+            || llvm_sys::core::LLVMGetConstOpcode(pointer) != llvm_sys::LLVMOpcode::LLVMIntToPtr
+        {
+            return None;
+        }
+        let offset = llvm_sys::core::LLVMGetOperand(pointer, 0);
+        if llvm_sys::core::LLVMIsAConstantInt(offset).is_null() {
+            return None;
+        }
+        let is_x64 = llvm_sys::core::LLVMConstIntGetZExtValue(llvm_sys::core::LLVMConstICmp(
+            llvm_sys::LLVMIntPredicate::LLVMIntULE,
+            offset,
+            llvm_sys::core::LLVMConstInt(llvm_sys::core::LLVMTypeOf(offset), u64::MAX, 0),
+        )) == 1;
+        is_x64.then(|| llvm_sys::core::LLVMConstIntGetZExtValue(offset))
+    }
+}
+
+///
+/// Returns the heap offset and length of the revert data packed by `Context::build_exit` into
+/// `abi_data`, if it is a constant.
+///
+fn revert_bounds<'ctx, D>(
+    context: &Context<'ctx, D>,
+    abi_data: inkwell::values::IntValue<'ctx>,
+) -> Option<(u64, u64)>
+where
+    D: Dependency,
+{
+    if !abi_data.is_constant_int() {
+        return None;
+    }
+
+    let field = |shift: usize| {
+        context.const_u64(
+            abi_data
+                .const_rshr(context.field_const(shift as u64))
+                .const_and(context.field_const(u32::MAX as u64)),
+        )
+    };
+    Some((
+        field(compiler_common::BITLENGTH_X32 * 2)?,
+        field(compiler_common::BITLENGTH_X32 * 3)?,
+    ))
+}
+
+///
+/// Checks whether the heap range `[start, end)` is covered by the `written` ranges.
+///
+fn is_covered(written: &mut [(u64, u64)], start: u64, end: u64) -> bool {
+    written.sort_unstable();
+    let mut covered = start;
+    for (range_start, range_end) in written.iter() {
+        if covered >= end || *range_start > covered {
+            break;
+        }
+        covered = covered.max(*range_end);
+    }
+    covered >= end
+}
+
+///
+/// Removes all the basic blocks of `function`.
+///
+fn clear_body(function: inkwell::values::FunctionValue) {
+    let blocks = function.get_basic_blocks();
+    let mut instructions = Vec::new();
+    for block in blocks.iter() {
+        let mut instruction = block.get_first_instruction();
+        while let Some(current) = instruction {
+            instruction = current.get_next_instruction();
+            instructions.push(current);
+        }
+    }
+
+    unsafe {
+        for instruction in instructions.iter() {
+            let value = instruction.as_value_ref();
+            if !llvm_sys::core::LLVMGetFirstUse(value).is_null() {
+                llvm_sys::core::LLVMReplaceAllUsesWith(
+                    value,
+                    llvm_sys::core::LLVMGetUndef(llvm_sys::core::LLVMTypeOf(value)),
+                );
+            }
+        }
+    }
+    for instruction in instructions.into_iter().rev() {
+        instruction.erase_from_basic_block();
+    }
+    for block in blocks.into_iter() {
+        unsafe { llvm_sys::core::LLVMDeleteBasicBlock(block.as_mut_ptr()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::address_space::AddressSpace;
+    use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn build_revert(
+        context: &mut Context<DummyDependency>,
+        name: &str,
+        written: &[u64],
+        length: u64,
+    ) {
+        context.add_function(
+            name,
+            context.function_type(0, vec![context.field_type().into()]),
+            None,
+        );
+        let function = context.functions[name].clone();
+        let argument = function
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_int_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        for offset in written.iter() {
+            let pointer = context.access_memory(
+                context.field_const(*offset),
+                AddressSpace::Heap,
+                "revert_data_pointer",
+            );
+            context.build_store(pointer, argument);
+        }
+        context.build_exit(
+            IntrinsicFunction::Revert,
+            context.field_const(0),
+            context.field_const(length),
+        );
+        context.set_basic_block(context.function().return_block);
+        context.build_unreachable();
+    }
+
+    #[test]
+    fn select_written_revert_data() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "cold_split", optimizer, None, vec![]);
+
+        let field_size = compiler_common::SIZE_FIELD as u64;
+        build_revert(&mut context, "written", &[0, field_size], field_size * 2);
+        build_revert(&mut context, "empty", &[], 0);
+        build_revert(&mut context, "partially_written", &[0], field_size * 2);
+        build_revert(&mut context, "caller_written", &[], field_size);
+
+        let selected: Vec<String> = super::select(&context, 0)
+            .into_iter()
+            .map(|function| function.get_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(selected, vec!["written".to_owned(), "empty".to_owned()]);
+    }
+
+    #[test]
+    fn coverage() {
+        assert!(super::is_covered(&mut [(32, 64), (0, 32)], 0, 64));
+        assert!(super::is_covered(&mut [], 16, 16));
+        assert!(!super::is_covered(&mut [(0, 32), (33, 64)], 0, 64));
+        assert!(!super::is_covered(&mut [(32, 64)], 0, 64));
+    }
+}
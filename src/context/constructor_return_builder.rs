@@ -0,0 +1,390 @@
+//!
+//! The deploy code return data builder.
+//!
+
+use crate::context::address_space::AddressSpace;
+use crate::context::aux_heap_layout::AuxHeapLayout;
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::immutables_layout::ImmutablesLayout;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The deploy code return data builder.
+///
+/// Unlike in EVM, zkSync constructors return the array of contract immutables in the auxiliary
+/// heap, prefixed with the array offset and the number of immutables. The entries are written
+/// by the front-end, either with the immutable stores, or as a single pre-sized block at
+/// `entries_offset`, and the builder emits the header and the return.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstructorReturnBuilder {
+    /// The auxiliary heap layout.
+    aux_heap_layout: AuxHeapLayout,
+    /// The immutables entries layout.
+    immutables_layout: ImmutablesLayout,
+    /// The number of immutables.
+    immutables_number: usize,
+    /// Whether the written immutables are checked before returning.
+    is_written_checked: bool,
+}
+
+impl ConstructorReturnBuilder {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(
+        aux_heap_layout: AuxHeapLayout,
+        immutables_layout: ImmutablesLayout,
+        immutables_number: usize,
+    ) -> Self {
+        Self {
+            aux_heap_layout,
+            immutables_layout,
+            immutables_number,
+            is_written_checked: true,
+        }
+    }
+
+    ///
+    /// Creates the builder with the layouts and the number of immutables of `context`.
+    ///
+    pub fn from_context<D>(context: &Context<D>) -> Self
+    where
+        D: Dependency,
+    {
+        Self::new(
+            context.aux_heap_layout(),
+            context.immutables_layout(),
+            context.immutable_size() / compiler_common::SIZE_FIELD,
+        )
+    }
+
+    ///
+    /// Disables the check of the written immutables, which is required if the entries are
+    /// written without the immutable stores, e.g. as a pre-sized block.
+    ///
+    pub fn without_written_check(mut self) -> Self {
+        self.is_written_checked = false;
+        self
+    }
+
+    ///
+    /// Returns the number of immutables.
+    ///
+    pub fn immutables_number(&self) -> usize {
+        self.immutables_number
+    }
+
+    ///
+    /// Returns the auxiliary heap offset the entries must be written at.
+    ///
+    pub fn entries_offset(&self) -> u64 {
+        self.aux_heap_layout.immutables_offset()
+    }
+
+    ///
+    /// Returns the size of the entries in bytes.
+    ///
+    pub fn entries_size(&self) -> u64 {
+        (self.immutables_number * self.immutables_layout.entry_size()) as u64
+    }
+
+    ///
+    /// Returns the return data length in bytes, including the header.
+    ///
+    /// Must only be called on a validated builder.
+    ///
+    pub fn return_data_length(&self) -> u64 {
+        self.return_data_end()
+            .expect("Always valid")
+            .checked_sub(self.aux_heap_layout.constructor_return_data_offset())
+            .expect("Always valid")
+    }
+
+    ///
+    /// Checks that the return data fits the 32-bit offset and length of the return ABI data,
+    /// and does not cover the external call data offset.
+    ///
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let offset = self.aux_heap_layout.constructor_return_data_offset();
+        let end = self
+            .return_data_end()
+            .filter(|end| *end >= offset && *end <= u32::MAX as u64)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "The constructor return data of {} immutables does not fit the auxiliary heap",
+                    self.immutables_number
+                )
+            })?;
+
+        let external_call_offset = self.aux_heap_layout.external_call_offset();
+        if (offset..end).contains(&external_call_offset) {
+            anyhow::bail!(
+                "The constructor return data at {}..{} overlaps the external call data at {}",
+                offset,
+                end,
+                external_call_offset
+            );
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Returns the end offset of the return data, that is, the end of the entries, if it does
+    /// not overflow.
+    ///
+    fn return_data_end(&self) -> Option<u64> {
+        self.immutables_number
+            .checked_mul(self.immutables_layout.entry_size())
+            .and_then(|size| self.entries_offset().checked_add(size as u64))
+    }
+
+    ///
+    /// Emits the check that every immutable has been written, reverting with empty data
+    /// otherwise.
+    ///
+    /// The immutable stores mark the written immutables in a bit mask, so the check is only
+    /// emitted for up to one immutable per mask bit. Nothing is emitted if there are no
+    /// immutables or the check is disabled.
+    ///
+    pub fn build_written_check<D>(&self, context: &mut Context<D>)
+    where
+        D: Dependency,
+    {
+        if self.immutables_number == 0
+            || self.immutables_number > compiler_common::BITLENGTH_FIELD
+            || !self.is_written_checked
+        {
+            return;
+        }
+
+        let immutables_valid_block = context.append_basic_block("immutables_valid_block");
+        let immutables_invalid_block = context.append_basic_block("immutables_invalid_block");
+
+        let immutables_written_pointer = context.declare_global(
+            crate::r#const::GLOBAL_IMMUTABLES_WRITTEN,
+            context.field_type(),
+        );
+        let immutables_written = context
+            .build_load(immutables_written_pointer, "immutables_written")
+            .into_int_value();
+        let mut immutables_expected = "f".repeat(self.immutables_number / 4);
+        if self.immutables_number % 4 != 0 {
+            immutables_expected.insert_str(
+                0,
+                format!("{:x}", (1u8 << (self.immutables_number % 4)) - 1).as_str(),
+            );
+        }
+        let are_immutables_written = context.builder().build_int_compare(
+            inkwell::IntPredicate::EQ,
+            immutables_written,
+            context.field_const_str_hex(immutables_expected.as_str()),
+            "are_immutables_written",
+        );
+        context.build_conditional_branch_weighted(
+            are_immutables_written,
+            immutables_valid_block,
+            immutables_invalid_block,
+            Context::<D>::BRANCH_WEIGHT_LIKELY,
+            Context::<D>::BRANCH_WEIGHT_UNLIKELY,
+        );
+
+        context.set_basic_block(immutables_invalid_block);
+        context.build_exit(
+            IntrinsicFunction::Revert,
+            context.field_const(0),
+            context.field_const(0),
+        );
+
+        context.set_basic_block(immutables_valid_block);
+    }
+
+    ///
+    /// Writes the immutables array offset and the number of immutables.
+    ///
+    pub fn build_header<D>(&self, context: &mut Context<D>)
+    where
+        D: Dependency,
+    {
+        let immutables_offset_pointer = context.access_memory(
+            context.field_const(self.aux_heap_layout.constructor_return_data_offset()),
+            AddressSpace::HeapAuxiliary,
+            "immutables_offset_pointer",
+        );
+        context.build_store(
+            immutables_offset_pointer,
+            context.field_const(compiler_common::SIZE_FIELD as u64),
+        );
+
+        let immutables_number_pointer = context.access_memory(
+            context.field_const(self.aux_heap_layout.immutables_number_offset()),
+            AddressSpace::HeapAuxiliary,
+            "immutables_number_pointer",
+        );
+        context.build_store(
+            immutables_number_pointer,
+            context.field_const(self.immutables_number as u64),
+        );
+    }
+
+    ///
+    /// Validates the layout, and emits the written check, the header, and the return.
+    ///
+    pub fn build<D>(&self, context: &mut Context<D>) -> anyhow::Result<()>
+    where
+        D: Dependency,
+    {
+        self.validate()?;
+
+        self.build_written_check(context);
+        self.build_header(context);
+        context.build_exit(
+            IntrinsicFunction::Return,
+            context.field_const(self.aux_heap_layout.constructor_return_data_offset()),
+            context.field_const(self.return_data_length()),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::aux_heap_layout::AuxHeapLayout;
+    use crate::context::code_type::CodeType;
+    use crate::context::immutables_layout::ImmutablesLayout;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    use super::ConstructorReturnBuilder;
+
+    #[test]
+    fn return_data_length_pairs() {
+        let builder =
+            ConstructorReturnBuilder::new(AuxHeapLayout::v1(), ImmutablesLayout::Pairs, 3);
+
+        assert_eq!(builder.entries_size(), 3 * 64);
+        assert_eq!(builder.return_data_length(), 2 * 32 + 3 * 64);
+        assert!(builder.validate().is_ok());
+    }
+
+    #[test]
+    fn return_data_length_compact() {
+        let builder =
+            ConstructorReturnBuilder::new(AuxHeapLayout::v1(), ImmutablesLayout::Compact, 3);
+
+        assert_eq!(builder.entries_offset(), 10 * 32);
+        assert_eq!(builder.return_data_length(), 2 * 32 + 3 * 32);
+        assert!(builder.validate().is_ok());
+    }
+
+    #[test]
+    fn empty() {
+        let builder =
+            ConstructorReturnBuilder::new(AuxHeapLayout::v1(), ImmutablesLayout::Pairs, 0);
+
+        assert_eq!(builder.return_data_length(), 2 * 32);
+        assert!(builder.validate().is_ok());
+    }
+
+    #[test]
+    fn return_data_length_custom_layout() {
+        let builder = ConstructorReturnBuilder::new(
+            AuxHeapLayout::new(0, 32 * 32),
+            ImmutablesLayout::Compact,
+            2,
+        );
+
+        assert_eq!(builder.entries_offset(), 34 * 32);
+        assert_eq!(builder.return_data_length(), 2 * 32 + 2 * 32);
+        assert!(builder.validate().is_ok());
+    }
+
+    #[test]
+    fn written_check() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "written_check", optimizer, None, vec![]);
+
+        context.add_function("constructor", context.function_type(0, vec![]), None);
+        let function = context.functions["constructor"].clone();
+        context.set_function(function);
+        context.set_code_type(CodeType::Deploy);
+        context.set_basic_block(context.function().entry_block);
+        for index in [0, 32, 32, 64].into_iter() {
+            crate::evm::immutable::store(
+                &mut context,
+                context.field_const(index),
+                context.field_const(42),
+            )
+            .expect("Always valid");
+        }
+        ConstructorReturnBuilder::new(AuxHeapLayout::v1(), ImmutablesLayout::Pairs, 3)
+            .build(&mut context)
+            .expect("Always valid");
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let printed = context
+            .print_function("constructor")
+            .expect("Always exists");
+        assert!(printed.contains("%immutables_written_updated = or i256"));
+        assert!(printed.contains("%are_immutables_written = icmp eq i256 %immutables_written, 7"));
+        assert!(!printed.contains("immutables_written_incremented"));
+    }
+
+    #[test]
+    fn written_check_disabled() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "written_check", optimizer, None, vec![]);
+
+        context.add_function("constructor", context.function_type(0, vec![]), None);
+        let function = context.functions["constructor"].clone();
+        context.set_function(function);
+        context.set_code_type(CodeType::Deploy);
+        context.set_basic_block(context.function().entry_block);
+        ConstructorReturnBuilder::new(AuxHeapLayout::v1(), ImmutablesLayout::Compact, 300)
+            .build(&mut context)
+            .expect("Always valid");
+        let block = context.append_basic_block("pre_sized_block");
+        context.set_basic_block(block);
+        ConstructorReturnBuilder::new(AuxHeapLayout::v1(), ImmutablesLayout::Compact, 3)
+            .without_written_check()
+            .build(&mut context)
+            .expect("Always valid");
+
+        let printed = context
+            .print_function("constructor")
+            .expect("Always exists");
+        assert!(!printed.contains("are_immutables_written"));
+    }
+
+    #[test]
+    fn error_too_many_immutables() {
+        let builder = ConstructorReturnBuilder::new(
+            AuxHeapLayout::v1(),
+            ImmutablesLayout::Pairs,
+            u32::MAX as usize,
+        );
+
+        assert!(builder.validate().is_err());
+    }
+
+    #[test]
+    fn error_overlapping_external_call() {
+        let builder =
+            ConstructorReturnBuilder::new(AuxHeapLayout::new(0, 0), ImmutablesLayout::Pairs, 1);
+
+        assert!(builder.validate().is_err());
+    }
+}
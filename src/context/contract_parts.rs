@@ -0,0 +1,50 @@
+//!
+//! The contract parts included in the artifact.
+//!
+
+///
+/// The contract parts included in the artifact.
+///
+/// The entry function reverts if the missing part is called.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContractParts {
+    /// Both the deploy and runtime code.
+    Both,
+    /// Only the deploy code, e.g. for factory helpers and constructor test fixtures.
+    DeployOnly,
+    /// Only the runtime code.
+    RuntimeOnly,
+}
+
+impl Default for ContractParts {
+    fn default() -> Self {
+        Self::Both
+    }
+}
+
+impl ContractParts {
+    ///
+    /// Whether the deploy code is included.
+    ///
+    pub fn has_deploy_code(&self) -> bool {
+        matches!(self, Self::Both | Self::DeployOnly)
+    }
+
+    ///
+    /// Whether the runtime code is included.
+    ///
+    pub fn has_runtime_code(&self) -> bool {
+        matches!(self, Self::Both | Self::RuntimeOnly)
+    }
+}
+
+impl std::fmt::Display for ContractParts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Both => write!(f, "both"),
+            Self::DeployOnly => write!(f, "deploy-only"),
+            Self::RuntimeOnly => write!(f, "runtime-only"),
+        }
+    }
+}
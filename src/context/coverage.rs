@@ -0,0 +1,149 @@
+//!
+//! The code coverage instrumentation.
+//!
+
+use inkwell::values::BasicValue;
+
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The code coverage counter increment mode.
+///
+/// Both modes modify the state, so the instrumented code fails in the static call context.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CoverageMode {
+    /// The counters are incremented in the contract storage, starting from the fixed key.
+    Storage,
+    /// The counter identifiers are emitted as the event topics.
+    Event,
+}
+
+impl std::fmt::Display for CoverageMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Storage => write!(f, "storage"),
+            Self::Event => write!(f, "event"),
+        }
+    }
+}
+
+///
+/// The code coverage counter manifest entry.
+///
+/// The counter identifier is its index in the manifest.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageCounter {
+    /// The LLVM function name.
+    pub function: String,
+    /// The LLVM basic block name.
+    pub block: String,
+}
+
+///
+/// Inserts a counter increment at the entry of each basic block of the functions defined in
+/// the current module, returning the counter manifest.
+///
+/// The runtime functions implemented in the LLVM back-end are not instrumented.
+///
+pub fn instrument<'ctx, D>(context: &Context<'ctx, D>, mode: CoverageMode) -> Vec<CoverageCounter>
+where
+    D: Dependency,
+{
+    let storage_key_base = context.field_const_str_hex(
+        crate::hashes::keccak256(crate::r#const::COVERAGE_STORAGE_KEY_PREIMAGE.as_bytes()).as_str(),
+    );
+    let event_topic = context.field_const_str_hex(
+        crate::hashes::keccak256(crate::r#const::COVERAGE_EVENT_SIGNATURE.as_bytes()).as_str(),
+    );
+
+    let mut counters = Vec::new();
+    let mut function = context.module().get_first_function();
+    while let Some(current_function) = function {
+        function = current_function.get_next_function();
+        if current_function.count_basic_blocks() == 0
+            || context.runtime.is_optimizer_skipped(current_function)
+        {
+            continue;
+        }
+
+        for block in current_function.get_basic_blocks().into_iter() {
+            let mut instruction = block.get_first_instruction();
+            while let Some(current) = instruction {
+                match current.get_opcode() {
+                    inkwell::values::InstructionOpcode::Phi
+                    | inkwell::values::InstructionOpcode::LandingPad => {
+                        instruction = current.get_next_instruction()
+                    }
+                    _ => break,
+                }
+            }
+            let instruction = match instruction {
+                Some(instruction) => instruction,
+                None => continue,
+            };
+            context.builder().position_before(&instruction);
+
+            let id = context.field_const(counters.len() as u64);
+            match mode {
+                CoverageMode::Storage => {
+                    let key = context.builder().build_int_add(
+                        storage_key_base,
+                        id,
+                        "coverage_counter_key",
+                    );
+                    let value = context
+                        .build_call(
+                            context.runtime.storage_load,
+                            &[key.as_basic_value_enum()],
+                            "coverage_counter_value",
+                        )
+                        .expect("Contract storage always returns a value")
+                        .into_int_value();
+                    let value_incremented = context.builder().build_int_add(
+                        value,
+                        context.field_const(1),
+                        "coverage_counter_value_incremented",
+                    );
+                    context.build_call(
+                        context.runtime.storage_store,
+                        &[
+                            value_incremented.as_basic_value_enum(),
+                            key.as_basic_value_enum(),
+                        ],
+                        "coverage_counter_store",
+                    );
+                }
+                CoverageMode::Event => {
+                    context.build_call(
+                        context.get_intrinsic_function(IntrinsicFunction::Event),
+                        &[
+                            context.field_const(2).as_basic_value_enum(),
+                            event_topic.as_basic_value_enum(),
+                            context.field_const(1).as_basic_value_enum(),
+                        ],
+                        "coverage_event_init_with_topic",
+                    );
+                    context.build_call(
+                        context.get_intrinsic_function(IntrinsicFunction::Event),
+                        &[
+                            id.as_basic_value_enum(),
+                            context.field_const(0).as_basic_value_enum(),
+                            context.field_const(0).as_basic_value_enum(),
+                        ],
+                        "coverage_event_counter",
+                    );
+                }
+            }
+
+            counters.push(CoverageCounter {
+                function: current_function.get_name().to_string_lossy().into_owned(),
+                block: block.get_name().to_string_lossy().into_owned(),
+            });
+        }
+    }
+    counters
+}
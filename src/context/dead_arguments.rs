@@ -0,0 +1,359 @@
+//!
+//! The dead argument elimination of the front-end functions.
+//!
+
+use std::collections::HashMap;
+
+use inkwell::values::AsValueRef;
+
+use crate::context::function::runtime::Runtime;
+use crate::context::function::Function;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// Removes the unused parameters of the front-end functions, updating all their call sites.
+///
+/// Each parameter occupies a register or a stack slot in the back-end, whereas the LLVM dead
+/// argument elimination is not a part of the configured pipelines.
+///
+/// Only the private and internal functions called directly and not used otherwise are rewritten,
+/// since the other ones may be called from outside the module. The runtime and near call ABI
+/// functions are left intact, since their signatures are fixed. The call site attributes are
+/// not preserved. The declared functions of the context are updated to the rewritten ones.
+///
+/// Returns the number of the removed parameters.
+///
+pub fn eliminate<'ctx, D>(context: &mut Context<'ctx, D>) -> usize
+where
+    D: Dependency,
+{
+    let mut candidates = Vec::new();
+    let mut function = context.module().get_first_function();
+    while let Some(current_function) = function {
+        function = current_function.get_next_function();
+
+        if !is_candidate::<D>(current_function) {
+            continue;
+        }
+        let unused: Vec<usize> = current_function
+            .get_param_iter()
+            .enumerate()
+            .filter(|(_index, parameter)| unsafe {
+                llvm_sys::core::LLVMGetFirstUse(parameter.as_value_ref()).is_null()
+            })
+            .map(|(index, _parameter)| index)
+            .collect();
+        if unused.is_empty() {
+            continue;
+        }
+        if let Some(calls) = call_sites(current_function) {
+            candidates.push((current_function, unused, calls));
+        }
+    }
+
+    let mut count = 0;
+    for (function, unused, calls) in candidates.into_iter() {
+        count += unused.len();
+        let name = function.get_name().to_string_lossy().into_owned();
+        unsafe { rewrite(context, function, unused.as_slice(), calls.as_slice()) };
+
+        let rewritten = context
+            .module()
+            .get_function(name.as_str())
+            .expect("Always exists");
+        for declared in context
+            .functions
+            .values_mut()
+            .chain(
+                context
+                    .namespaced_functions
+                    .values_mut()
+                    .flat_map(HashMap::values_mut),
+            )
+            .chain(context.function.as_mut())
+        {
+            if declared.value == function {
+                declared.value = rewritten;
+            }
+        }
+    }
+    count
+}
+
+///
+/// Whether the parameters of `function` may be removed.
+///
+fn is_candidate<D>(function: inkwell::values::FunctionValue) -> bool
+where
+    D: Dependency,
+{
+    if function.count_basic_blocks() == 0
+        || function.count_params() == 0
+        || !matches!(
+            function.get_linkage(),
+            inkwell::module::Linkage::Private | inkwell::module::Linkage::Internal
+        )
+    {
+        return false;
+    }
+
+    let name = function.get_name().to_string_lossy();
+    let name = name
+        .rsplit(Context::<D>::CONTRACT_NAMESPACE_SEPARATOR)
+        .next()
+        .unwrap_or_default();
+    !(name.starts_with("llvm.")
+        || name.starts_with(Runtime::FUNCTION_PREFIX)
+        || name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
+        || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER)
+}
+
+///
+/// Returns the calls and invokes of `function`, if it is not used otherwise, e.g. passed as
+/// an argument or aliased.
+///
+fn call_sites(
+    function: inkwell::values::FunctionValue,
+) -> Option<Vec<llvm_sys::prelude::LLVMValueRef>> {
+    let function = function.as_value_ref();
+    let mut calls = Vec::new();
+    unsafe {
+        let mut r#use = llvm_sys::core::LLVMGetFirstUse(function);
+        while !r#use.is_null() {
+            let user = llvm_sys::core::LLVMGetUser(r#use);
+            if (llvm_sys::core::LLVMIsACallInst(user).is_null()
+                && llvm_sys::core::LLVMIsAInvokeInst(user).is_null())
+                || llvm_sys::core::LLVMGetCalledValue(user) != function
+                || (0..llvm_sys::core::LLVMGetNumArgOperands(user))
+                    .any(|index| llvm_sys::core::LLVMGetOperand(user, index) == function)
+            {
+                return None;
+            }
+            calls.push(user);
+            r#use = llvm_sys::core::LLVMGetNextUse(r#use);
+        }
+    }
+    Some(calls)
+}
+
+///
+/// Replaces `function` with a copy without the `unused` parameters, moving the body and
+/// rewriting the `calls`.
+///
+/// # Safety
+/// The `unused` indexes must be sorted and refer to the parameters without uses, and `calls`
+/// must be all the uses of `function`.
+///
+unsafe fn rewrite<'ctx, D>(
+    context: &Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    unused: &[usize],
+    calls: &[llvm_sys::prelude::LLVMValueRef],
+) where
+    D: Dependency,
+{
+    let old = function.as_value_ref();
+    let kept: Vec<usize> = (0..function.count_params() as usize)
+        .filter(|index| unused.binary_search(index).is_err())
+        .collect();
+
+    let old_type = llvm_sys::core::LLVMGlobalGetValueType(old);
+    let mut old_parameter_types =
+        vec![std::ptr::null_mut(); llvm_sys::core::LLVMCountParamTypes(old_type) as usize];
+    llvm_sys::core::LLVMGetParamTypes(old_type, old_parameter_types.as_mut_ptr());
+    let mut parameter_types: Vec<llvm_sys::prelude::LLVMTypeRef> = kept
+        .iter()
+        .map(|index| old_parameter_types[*index])
+        .collect();
+    let r#type = llvm_sys::core::LLVMFunctionType(
+        llvm_sys::core::LLVMGetReturnType(old_type),
+        parameter_types.as_mut_ptr(),
+        parameter_types.len() as u32,
+        0,
+    );
+
+    let name = function.get_name().to_owned();
+    llvm_sys::core::LLVMSetValueName2(old, "".as_ptr() as *const std::os::raw::c_char, 0);
+    let new = llvm_sys::core::LLVMAddFunction(context.module().as_mut_ptr(), name.as_ptr(), r#type);
+    llvm_sys::core::LLVMSetLinkage(new, llvm_sys::core::LLVMGetLinkage(old));
+    llvm_sys::core::LLVMSetFunctionCallConv(new, llvm_sys::core::LLVMGetFunctionCallConv(old));
+    if llvm_sys::core::LLVMHasPersonalityFn(old) != 0 {
+        llvm_sys::core::LLVMSetPersonalityFn(new, llvm_sys::core::LLVMGetPersonalityFn(old));
+    }
+    copy_attributes(
+        old,
+        llvm_sys::LLVMAttributeFunctionIndex,
+        new,
+        llvm_sys::LLVMAttributeFunctionIndex,
+    );
+    copy_attributes(
+        old,
+        llvm_sys::LLVMAttributeReturnIndex,
+        new,
+        llvm_sys::LLVMAttributeReturnIndex,
+    );
+    for (new_index, old_index) in kept.iter().enumerate() {
+        copy_attributes(old, *old_index as u32 + 1, new, new_index as u32 + 1);
+
+        let old_parameter = llvm_sys::core::LLVMGetParam(old, *old_index as u32);
+        let new_parameter = llvm_sys::core::LLVMGetParam(new, new_index as u32);
+        let mut length = 0;
+        let parameter_name = llvm_sys::core::LLVMGetValueName2(old_parameter, &mut length);
+        llvm_sys::core::LLVMSetValueName2(new_parameter, parameter_name, length);
+        llvm_sys::core::LLVMReplaceAllUsesWith(old_parameter, new_parameter);
+    }
+
+    let mut block = llvm_sys::core::LLVMGetFirstBasicBlock(old);
+    while !block.is_null() {
+        let next = llvm_sys::core::LLVMGetNextBasicBlock(block);
+        llvm_sys::core::LLVMRemoveBasicBlockFromParent(block);
+        llvm_sys::core::LLVMAppendExistingBasicBlock(new, block);
+        block = next;
+    }
+
+    let builder = llvm_sys::core::LLVMCreateBuilderInContext(context.llvm.raw());
+    for call in calls.iter().copied() {
+        let mut arguments: Vec<llvm_sys::prelude::LLVMValueRef> = kept
+            .iter()
+            .map(|index| llvm_sys::core::LLVMGetOperand(call, *index as u32))
+            .collect();
+        llvm_sys::core::LLVMPositionBuilderBefore(builder, call);
+        let empty_name = "\0".as_ptr() as *const std::os::raw::c_char;
+        let new_call = if llvm_sys::core::LLVMIsAInvokeInst(call).is_null() {
+            let new_call = llvm_sys::core::LLVMBuildCall2(
+                builder,
+                r#type,
+                new,
+                arguments.as_mut_ptr(),
+                arguments.len() as u32,
+                empty_name,
+            );
+            llvm_sys::core::LLVMSetTailCall(new_call, llvm_sys::core::LLVMIsTailCall(call));
+            new_call
+        } else {
+            llvm_sys::core::LLVMBuildInvoke2(
+                builder,
+                r#type,
+                new,
+                arguments.as_mut_ptr(),
+                arguments.len() as u32,
+                llvm_sys::core::LLVMGetNormalDest(call),
+                llvm_sys::core::LLVMGetUnwindDest(call),
+                empty_name,
+            )
+        };
+        llvm_sys::core::LLVMSetInstructionCallConv(
+            new_call,
+            llvm_sys::core::LLVMGetInstructionCallConv(call),
+        );
+        llvm_sys::core::LLVMReplaceAllUsesWith(call, new_call);
+        llvm_sys::core::LLVMInstructionEraseFromParent(call);
+    }
+    llvm_sys::core::LLVMDisposeBuilder(builder);
+
+    llvm_sys::core::LLVMDeleteFunction(old);
+}
+
+///
+/// Copies the attributes of `source` at `source_index` to `destination` at `destination_index`.
+///
+/// # Safety
+/// Both values must be functions, and the indexes must be valid attribute indexes.
+///
+unsafe fn copy_attributes(
+    source: llvm_sys::prelude::LLVMValueRef,
+    source_index: llvm_sys::LLVMAttributeIndex,
+    destination: llvm_sys::prelude::LLVMValueRef,
+    destination_index: llvm_sys::LLVMAttributeIndex,
+) {
+    let count = llvm_sys::core::LLVMGetAttributeCountAtIndex(source, source_index);
+    let mut attributes = vec![std::ptr::null_mut(); count as usize];
+    llvm_sys::core::LLVMGetAttributesAtIndex(source, source_index, attributes.as_mut_ptr());
+    for attribute in attributes.into_iter() {
+        llvm_sys::core::LLVMAddAttributeAtIndex(destination, destination_index, attribute);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::address_space::AddressSpace;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn build_callee(
+        context: &mut Context<DummyDependency>,
+        name: &str,
+        linkage: Option<inkwell::module::Linkage>,
+    ) {
+        context.add_function(
+            name,
+            context.function_type(
+                0,
+                vec![context.field_type().into(), context.field_type().into()],
+            ),
+            linkage,
+        );
+        let function = context.functions[name].clone();
+        let used = function
+            .value
+            .get_nth_param(1)
+            .expect("Always exists")
+            .into_int_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let pointer =
+            context.access_memory(context.field_const(0), AddressSpace::Heap, "callee_pointer");
+        context.build_store(pointer, used);
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+    }
+
+    #[test]
+    fn private_only() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "dead_arguments", optimizer, None, vec![]);
+
+        build_callee(
+            &mut context,
+            "private_callee",
+            Some(inkwell::module::Linkage::Private),
+        );
+        build_callee(&mut context, "external_callee", None);
+
+        context.add_function("caller", context.function_type(0, vec![]), None);
+        let caller = context.functions["caller"].clone();
+        context.set_function(caller);
+        context.set_basic_block(context.function().entry_block);
+        let arguments: [inkwell::values::BasicValueEnum; 2] =
+            [context.field_const(1).into(), context.field_const(2).into()];
+        for name in ["private_callee", "external_callee"] {
+            let callee = context.functions[name].value;
+            context.build_call(callee, arguments.as_slice(), "callee_call");
+        }
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+
+        assert_eq!(super::eliminate(&mut context), 1);
+
+        let private_callee = context.functions["private_callee"].value;
+        assert_eq!(private_callee.count_params(), 1);
+        assert_eq!(
+            context.module().get_function("private_callee"),
+            Some(private_callee)
+        );
+        assert_eq!(context.functions["external_callee"].value.count_params(), 2);
+        assert!(context
+            .print_function("caller")
+            .expect("Always exists")
+            .contains("@private_callee(i256 2)"));
+        assert!(context.verify().is_ok());
+    }
+}
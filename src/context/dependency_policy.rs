@@ -0,0 +1,28 @@
+//!
+//! The missing dependency manager policy.
+//!
+
+///
+/// The missing dependency manager policy.
+///
+/// Defines how the context resolves the dependencies if the dependency manager is unset, e.g.
+/// when a contract is compiled in isolation to be linked later.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyPolicy {
+    /// Every dependency requires the manager, and its absence is an error.
+    Require,
+    /// The libraries are left to the deferred linking, and the paths are resolved as is.
+    /// The contract dependencies cannot be deferred and remain an error.
+    Defer,
+    /// The libraries and the contract dependency hashes are replaced with zeros, and the paths
+    /// are resolved as is. Is only suitable for the builds which are never deployed, e.g. for
+    /// the code size estimation.
+    ZeroFill,
+}
+
+impl Default for DependencyPolicy {
+    fn default() -> Self {
+        Self::Require
+    }
+}
@@ -0,0 +1,238 @@
+//!
+//! The LLVM far call result.
+//!
+
+use inkwell::types::BasicType;
+
+use crate::context::address_space::AddressSpace;
+use crate::context::fat_pointer_field::FatPointerField;
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The LLVM far call result.
+///
+/// Wraps the `{ i8 addrspace(3)*, i1 }` structure returned by the far call runtime functions.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct FarCallResult<'ctx> {
+    /// The returned ABI data pointer.
+    pub abi_data: inkwell::values::PointerValue<'ctx>,
+    /// The call success flag.
+    pub status: inkwell::values::IntValue<'ctx>,
+}
+
+impl<'ctx> FarCallResult<'ctx> {
+    /// The ABI data pointer field index.
+    pub const FIELD_INDEX_ABI_DATA: u32 = 0;
+
+    /// The status flag field index.
+    pub const FIELD_INDEX_STATUS: u32 = 1;
+
+    ///
+    /// Loads the result fields from the result structure `pointer`.
+    ///
+    pub fn load<D>(context: &Context<'ctx, D>, pointer: inkwell::values::PointerValue<'ctx>) -> Self
+    where
+        D: Dependency,
+    {
+        let abi_data_pointer = context
+            .builder()
+            .build_struct_gep(
+                pointer,
+                Self::FIELD_INDEX_ABI_DATA,
+                "far_call_result_abi_data_pointer",
+            )
+            .expect("Always valid");
+        let abi_data = context
+            .build_load(abi_data_pointer, "far_call_result_abi_data")
+            .into_pointer_value();
+
+        let status_pointer = context
+            .builder()
+            .build_struct_gep(
+                pointer,
+                Self::FIELD_INDEX_STATUS,
+                "far_call_result_status_pointer",
+            )
+            .expect("Always valid");
+        let status = context
+            .build_load(status_pointer, "far_call_result_status")
+            .into_int_value();
+
+        Self { abi_data, status }
+    }
+
+    ///
+    /// Stores the result fields to the result structure `pointer`.
+    ///
+    /// Allows joining the results of several calls made in different branches, which are then
+    /// loaded back with `load`.
+    ///
+    pub fn store<D>(&self, context: &Context<'ctx, D>, pointer: inkwell::values::PointerValue<'ctx>)
+    where
+        D: Dependency,
+    {
+        let abi_data_pointer = context
+            .builder()
+            .build_struct_gep(
+                pointer,
+                Self::FIELD_INDEX_ABI_DATA,
+                "far_call_result_abi_data_pointer",
+            )
+            .expect("Always valid");
+        context.build_store(abi_data_pointer, self.abi_data);
+
+        let status_pointer = context
+            .builder()
+            .build_struct_gep(
+                pointer,
+                Self::FIELD_INDEX_STATUS,
+                "far_call_result_status_pointer",
+            )
+            .expect("Always valid");
+        context.build_store(status_pointer, self.status);
+    }
+
+    ///
+    /// Returns the result structure type.
+    ///
+    pub fn r#type<D>(context: &Context<'ctx, D>) -> inkwell::types::StructType<'ctx>
+    where
+        D: Dependency,
+    {
+        context.structure_type(vec![
+            context
+                .integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Generic.into())
+                .as_basic_type_enum(),
+            context
+                .integer_type(compiler_common::BITLENGTH_BOOLEAN)
+                .as_basic_type_enum(),
+        ])
+    }
+
+    ///
+    /// Returns the status flag extended to the field type.
+    ///
+    pub fn status_code<D>(&self, context: &Context<'ctx, D>) -> inkwell::values::IntValue<'ctx>
+    where
+        D: Dependency,
+    {
+        context.builder().build_int_z_extend_or_bit_cast(
+            self.status,
+            context.field_type(),
+            "far_call_result_status_code",
+        )
+    }
+
+    ///
+    /// Returns the returned ABI data pointer casted to the field type.
+    ///
+    pub fn data_pointer<D>(&self, context: &Context<'ctx, D>) -> inkwell::values::PointerValue<'ctx>
+    where
+        D: Dependency,
+    {
+        context.builder().build_pointer_cast(
+            self.abi_data,
+            context.field_type().ptr_type(AddressSpace::Generic.into()),
+            "far_call_result_abi_data_casted",
+        )
+    }
+
+    ///
+    /// Returns the returned data length, which is encoded in the ABI data pointer.
+    ///
+    pub fn data_length<D>(&self, context: &Context<'ctx, D>) -> inkwell::values::IntValue<'ctx>
+    where
+        D: Dependency,
+    {
+        context.fat_ptr_field(
+            self.abi_data,
+            FatPointerField::Length,
+            "far_call_result_data_length",
+        )
+    }
+
+    ///
+    /// Copies `length` bytes of the returned data to the heap at `offset`.
+    ///
+    /// Nothing is emitted if `length` is the constant zero, which is the case for most of
+    /// the high-level calls, since the return data is then accessed via the return data pointer.
+    ///
+    pub fn copy_to_heap<D>(
+        &self,
+        context: &Context<'ctx, D>,
+        offset: inkwell::values::IntValue<'ctx>,
+        length: inkwell::values::IntValue<'ctx>,
+    ) where
+        D: Dependency,
+    {
+        self.copy_to(context, AddressSpace::Heap, offset, length);
+    }
+
+    ///
+    /// Copies `length` bytes of the returned data to `address_space` at `offset`.
+    ///
+    /// Only the heap and auxiliary heap are supported as the destination.
+    ///
+    pub fn copy_to<D>(
+        &self,
+        context: &Context<'ctx, D>,
+        address_space: AddressSpace,
+        offset: inkwell::values::IntValue<'ctx>,
+        length: inkwell::values::IntValue<'ctx>,
+    ) where
+        D: Dependency,
+    {
+        if length.is_null() {
+            return;
+        }
+
+        let intrinsic = match address_space {
+            AddressSpace::Heap => IntrinsicFunction::MemoryCopyFromGeneric,
+            AddressSpace::HeapAuxiliary => IntrinsicFunction::MemoryCopyFromGenericToAuxHeap,
+            address_space => panic!(
+                "The far call result cannot be copied to the {:?} address space",
+                address_space
+            ),
+        };
+        let source = self.data_pointer(context);
+        let destination =
+            context.access_memory(offset, address_space, "far_call_result_destination");
+        context.build_memcpy(
+            intrinsic,
+            destination,
+            source,
+            length,
+            "far_call_result_memcpy_from_child",
+        );
+    }
+
+    ///
+    /// If the call has failed, copies the returned data to the beginning of the heap and reverts
+    /// with it. Otherwise, continues in a new block.
+    ///
+    pub fn bubble_revert<D>(&self, context: &Context<'ctx, D>)
+    where
+        D: Dependency,
+    {
+        let success_block = context.append_basic_block("far_call_success_block");
+        let failure_block = context.append_basic_block("far_call_failure_block");
+        context.build_conditional_branch_weighted(
+            self.status,
+            success_block,
+            failure_block,
+            Context::<D>::BRANCH_WEIGHT_LIKELY,
+            Context::<D>::BRANCH_WEIGHT_UNLIKELY,
+        );
+
+        context.set_basic_block(failure_block);
+        let length = self.data_length(context);
+        self.copy_to_heap(context, context.field_const(0), length);
+        context.build_exit(IntrinsicFunction::Revert, context.field_const(0), length);
+
+        context.set_basic_block(success_block);
+    }
+}
@@ -0,0 +1,50 @@
+//!
+//! The zkEVM fat pointer field.
+//!
+
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+
+///
+/// The zkEVM fat pointer field.
+///
+/// The fat pointer occupies the lower 128 bits of its integer representation, with each field
+/// being 32 bits wide.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FatPointerField {
+    /// The offset from the start of the pointed slice.
+    Offset,
+    /// The memory page.
+    Page,
+    /// The start of the pointed slice in the memory page.
+    Start,
+    /// The length of the pointed slice.
+    Length,
+}
+
+impl FatPointerField {
+    ///
+    /// Returns the field offset in the integer representation in bits.
+    ///
+    pub fn shift(&self) -> usize {
+        let index = match self {
+            Self::Offset => 0,
+            Self::Page => 1,
+            Self::Start => 2,
+            Self::Length => 3,
+        };
+        index * compiler_common::BITLENGTH_X32
+    }
+
+    ///
+    /// Returns the back-end intrinsic getting the field.
+    ///
+    pub fn intrinsic(&self) -> IntrinsicFunction {
+        match self {
+            Self::Offset => IntrinsicFunction::PointerOffset,
+            Self::Page => IntrinsicFunction::PointerPage,
+            Self::Start => IntrinsicFunction::PointerStart,
+            Self::Length => IntrinsicFunction::PointerLength,
+        }
+    }
+}
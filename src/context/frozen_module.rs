@@ -0,0 +1,86 @@
+//!
+//! The LLVM module frozen after the build.
+//!
+
+use std::collections::BTreeMap;
+
+///
+/// The LLVM module frozen after the build.
+///
+/// Only allows read-only queries, which are used by the analysis and caching layers to inspect
+/// the optimized LLVM IR the build artifacts have been produced from.
+///
+#[derive(Debug)]
+pub struct FrozenModule<'ctx> {
+    /// The inner LLVM module.
+    module: inkwell::module::Module<'ctx>,
+}
+
+impl<'ctx> FrozenModule<'ctx> {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(module: inkwell::module::Module<'ctx>) -> Self {
+        Self { module }
+    }
+
+    ///
+    /// Returns the module name.
+    ///
+    pub fn name(&self) -> String {
+        self.module.get_name().to_string_lossy().into_owned()
+    }
+
+    ///
+    /// Returns the names of the functions defined in the module, in the definition order.
+    ///
+    /// The declarations, e.g. of the intrinsics and runtime functions, are skipped.
+    ///
+    pub fn functions(&self) -> Vec<String> {
+        self.module
+            .get_functions()
+            .filter(|function| function.count_basic_blocks() > 0)
+            .map(|function| function.get_name().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    ///
+    /// Returns the function-to-size mapping, where the sizes are measured in LLVM IR instructions.
+    ///
+    pub fn function_sizes(&self) -> BTreeMap<String, usize> {
+        self.module
+            .get_functions()
+            .filter(|function| function.count_basic_blocks() > 0)
+            .map(|function| {
+                let size = function
+                    .get_basic_blocks()
+                    .into_iter()
+                    .map(|block| {
+                        let mut count = 0;
+                        let mut instruction = block.get_first_instruction();
+                        while let Some(current) = instruction {
+                            count += 1;
+                            instruction = current.get_next_instruction();
+                        }
+                        count
+                    })
+                    .sum();
+                (function.get_name().to_string_lossy().into_owned(), size)
+            })
+            .collect()
+    }
+
+    ///
+    /// Returns the printed LLVM IR.
+    ///
+    pub fn print(&self) -> String {
+        self.module.print_to_string().to_string()
+    }
+
+    ///
+    /// Returns the inner LLVM module reference.
+    ///
+    pub fn module(&self) -> &inkwell::module::Module<'ctx> {
+        &self.module
+    }
+}
@@ -9,6 +9,9 @@ use crate::context::code_type::CodeType;
 ///
 /// Is only relevant to the EVM legacy assembly.
 ///
+/// The deploy and runtime code tags are numbered independently by solc, so the same tag
+/// may occur in both, and the code type is a part of the key to keep them distinct.
+///
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Key {
     /// The block code type.
@@ -24,6 +27,20 @@ impl Key {
     pub fn new(code_type: CodeType, tag: num::BigUint) -> Self {
         Self { code_type, tag }
     }
+
+    ///
+    /// A shortcut constructor for a deploy code block.
+    ///
+    pub fn deploy(tag: num::BigUint) -> Self {
+        Self::new(CodeType::Deploy, tag)
+    }
+
+    ///
+    /// A shortcut constructor for a runtime code block.
+    ///
+    pub fn runtime(tag: num::BigUint) -> Self {
+        Self::new(CodeType::Runtime, tag)
+    }
 }
 
 impl std::fmt::Display for Key {
@@ -67,7 +67,7 @@ where
 
         context.set_basic_block(context.function().entry_block);
         context.set_code_type(CodeType::Deploy);
-        self.inner.into_llvm(context)?;
+        context.write_llvm(self.inner)?;
         match context
             .basic_block()
             .get_last_instruction()
@@ -5,6 +5,8 @@
 use inkwell::types::BasicType;
 
 use crate::context::address_space::AddressSpace;
+use crate::context::contract_parts::ContractParts;
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 use crate::context::function::runtime::Runtime;
 use crate::context::Context;
 use crate::Dependency;
@@ -16,7 +18,10 @@ use crate::WriteLLVM;
 /// The function is a wrapper managing the runtime and deploy code calling logic.
 ///
 #[derive(Debug, Default)]
-pub struct Entry {}
+pub struct Entry {
+    /// The contract parts included in the artifact.
+    parts: ContractParts,
+}
 
 impl Entry {
     /// The calldata ABI argument index.
@@ -31,6 +36,13 @@ impl Entry {
     /// The extra ABI data second argument index.
     pub const ARGUMENT_INDEX_EXTRA_ABI_DATA_2: usize = 3;
 
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(parts: ContractParts) -> Self {
+        Self { parts }
+    }
+
     ///
     /// Initializes the global variables.
     ///
@@ -82,6 +94,16 @@ where
             Some(inkwell::module::Linkage::External),
         );
 
+        for name in [
+            crate::r#const::GLOBAL_CALLDATA_POINTER,
+            crate::r#const::GLOBAL_RETURN_DATA_POINTER,
+            crate::r#const::GLOBAL_ACTIVE_POINTER,
+        ]
+        .into_iter()
+        {
+            context.declare_global_pointer(name);
+        }
+
         Ok(())
     }
 
@@ -96,16 +118,26 @@ where
         let deploy_code_call_block = context.append_basic_block("deploy_code_call_block");
         let runtime_code_call_block = context.append_basic_block("runtime_code_call_block");
 
-        let deploy_code = context
-            .functions
-            .get(Runtime::FUNCTION_DEPLOY_CODE)
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("Contract deploy code not found"))?;
-        let runtime_code = context
-            .functions
-            .get(Runtime::FUNCTION_RUNTIME_CODE)
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("Contract runtime code not found"))?;
+        let deploy_code = if self.parts.has_deploy_code() {
+            let function = context
+                .functions
+                .get(Runtime::FUNCTION_DEPLOY_CODE)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Contract deploy code not found"))?;
+            Some(function)
+        } else {
+            None
+        };
+        let runtime_code = if self.parts.has_runtime_code() {
+            let function = context
+                .functions
+                .get(Runtime::FUNCTION_RUNTIME_CODE)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Contract runtime code not found"))?;
+            Some(function)
+        } else {
+            None
+        };
 
         context.set_basic_block(context.function().entry_block);
         Self::initialize_globals(context)?;
@@ -166,6 +198,8 @@ where
             context.build_store(array_element_pointer, argument_value);
         }
 
+        context.build_global_initializers()?;
+
         let is_deploy_call_flag_truncated = context.builder().build_and(
             call_flags,
             context.field_const(1),
@@ -184,12 +218,30 @@ where
         );
 
         context.set_basic_block(deploy_code_call_block);
-        context.build_invoke(deploy_code.value, &[], "deploy_code_call");
-        context.build_unconditional_branch(context.function().return_block);
+        match deploy_code {
+            Some(deploy_code) => {
+                context.build_invoke(deploy_code.value, &[], "deploy_code_call");
+                context.build_unconditional_branch(context.function().return_block);
+            }
+            None => context.build_exit(
+                IntrinsicFunction::Revert,
+                context.field_const(0),
+                context.field_const(0),
+            ),
+        }
 
         context.set_basic_block(runtime_code_call_block);
-        context.build_invoke(runtime_code.value, &[], "runtime_code_call");
-        context.build_unconditional_branch(context.function().return_block);
+        match runtime_code {
+            Some(runtime_code) => {
+                context.build_invoke(runtime_code.value, &[], "runtime_code_call");
+                context.build_unconditional_branch(context.function().return_block);
+            }
+            None => context.build_exit(
+                IntrinsicFunction::Revert,
+                context.field_const(0),
+                context.field_const(0),
+            ),
+        }
 
         context.set_basic_block(context.function().return_block);
         context.build_return(Some(&context.field_const(0)));
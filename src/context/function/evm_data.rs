@@ -18,10 +18,13 @@ pub struct EVMData<'ctx> {
     /// Is only used by the Solidity EVM compiler.
     pub blocks: BTreeMap<BlockKey, Vec<Block<'ctx>>>,
     /// The function stack size.
-    pub stack_size: usize,
+    stack_size: usize,
 }
 
 impl<'ctx> EVMData<'ctx> {
+    /// The maximal EVM stack size.
+    pub const STACK_SIZE_LIMIT: usize = 1024;
+
     ///
     /// A shortcut constructor.
     ///
@@ -32,15 +35,73 @@ impl<'ctx> EVMData<'ctx> {
         }
     }
 
+    ///
+    /// Returns the function stack size.
+    ///
+    pub fn stack_size(&self) -> usize {
+        self.stack_size
+    }
+
+    ///
+    /// Accounts for `count` elements pushed onto the stack.
+    ///
+    /// Returns an error if the stack size limit is exceeded.
+    ///
+    pub fn push(&mut self, count: usize) -> anyhow::Result<()> {
+        self.stack_size = self
+            .stack_size
+            .checked_add(count)
+            .filter(|stack_size| *stack_size <= Self::STACK_SIZE_LIMIT)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Stack overflow: pushing {} elements onto the stack of size {}, while the limit is {}",
+                    count,
+                    self.stack_size,
+                    Self::STACK_SIZE_LIMIT
+                )
+            })?;
+        Ok(())
+    }
+
+    ///
+    /// Accounts for `count` elements popped from the stack.
+    ///
+    /// Returns an error if there are fewer than `count` elements on the stack.
+    ///
+    pub fn pop(&mut self, count: usize) -> anyhow::Result<()> {
+        self.stack_size = self.stack_size.checked_sub(count).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Stack underflow: popping {} elements from the stack of size {}",
+                count,
+                self.stack_size
+            )
+        })?;
+        Ok(())
+    }
+
     ///
     /// Inserts a function block.
     ///
-    pub fn insert_block(&mut self, key: BlockKey, block: Block<'ctx>) {
-        if let Some(blocks) = self.blocks.get_mut(&key) {
-            blocks.push(block);
-        } else {
-            self.blocks.insert(key, vec![block]);
+    /// Several blocks with the same tag are allowed if their initial stack patterns differ.
+    /// Returns an error if a block with the same tag and initial stack pattern already exists,
+    /// since it would be impossible to select the correct one.
+    ///
+    pub fn insert_block(&mut self, key: BlockKey, block: Block<'ctx>) -> anyhow::Result<()> {
+        let blocks = self.blocks.entry(key.clone()).or_default();
+        if let Some(existing) = blocks
+            .iter()
+            .find(|existing| existing.evm().stack_hash == block.evm().stack_hash)
+        {
+            anyhow::bail!(
+                "Duplicate function block {} with the initial stack hash {:x}: `{}` and `{}`",
+                key,
+                block.evm().stack_hash,
+                existing.inner.get_name().to_string_lossy(),
+                block.inner.get_name().to_string_lossy(),
+            );
         }
+        blocks.push(block);
+        Ok(())
     }
 
     ///
@@ -78,3 +139,108 @@ impl<'ctx> EVMData<'ctx> {
             .ok_or_else(|| anyhow::anyhow!("Undeclared function block {}", key))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::function::block::evm_data::EVMData as BlockEVMData;
+    use crate::context::function::block::key::Key as BlockKey;
+    use crate::context::function::block::Block;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    use super::EVMData;
+
+    #[test]
+    fn push_pop() {
+        let mut evm_data = EVMData::new(2);
+
+        evm_data.push(3).expect("Always valid");
+        assert_eq!(evm_data.stack_size(), 5);
+        evm_data.pop(5).expect("Always valid");
+        assert_eq!(evm_data.stack_size(), 0);
+    }
+
+    #[test]
+    fn error_overflow() {
+        let mut evm_data = EVMData::new(EVMData::STACK_SIZE_LIMIT);
+
+        assert!(evm_data.push(1).is_err());
+        assert_eq!(evm_data.stack_size(), EVMData::STACK_SIZE_LIMIT);
+    }
+
+    #[test]
+    fn error_underflow() {
+        let mut evm_data = EVMData::new(1);
+
+        assert!(evm_data.pop(2).is_err());
+        assert_eq!(evm_data.stack_size(), 1);
+    }
+
+    #[test]
+    fn error_duplicate_block() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(
+            &llvm,
+            "error_duplicate_block",
+            optimizer,
+            None,
+            vec![],
+        );
+
+        context.add_function(
+            "error_duplicate_block",
+            context.function_type(0, vec![]),
+            None,
+        );
+        let function = context.functions["error_duplicate_block"].clone();
+        context.set_function(function);
+
+        let mut evm_data = EVMData::new(0);
+        let key = BlockKey::runtime(num::BigUint::from(1u64));
+        let first_hash = md5::compute(b"first");
+        let second_hash = md5::compute(b"second");
+        evm_data
+            .insert_block(
+                key.clone(),
+                Block::new_evm(
+                    context.append_basic_block("first"),
+                    BlockEVMData::new(first_hash),
+                ),
+            )
+            .expect("Always valid");
+        evm_data
+            .insert_block(
+                key.clone(),
+                Block::new_evm(
+                    context.append_basic_block("second"),
+                    BlockEVMData::new(second_hash),
+                ),
+            )
+            .expect("Always valid");
+
+        let error = evm_data
+            .insert_block(
+                key.clone(),
+                Block::new_evm(
+                    context.append_basic_block("duplicate"),
+                    BlockEVMData::new(first_hash),
+                ),
+            )
+            .expect_err("Always invalid");
+        assert!(error.to_string().contains("`first` and `duplicate`"));
+        assert_eq!(evm_data.blocks[&key].len(), 2);
+        assert_eq!(
+            evm_data
+                .find_block(&key, &second_hash)
+                .expect("Always exists")
+                .inner
+                .get_name()
+                .to_string_lossy(),
+            "second"
+        );
+    }
+}
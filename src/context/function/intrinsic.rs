@@ -45,6 +45,28 @@ pub enum Intrinsic {
     PointerShrink,
     /// The pointer pack.
     PointerPack,
+    /// The fat pointer to integer conversion.
+    PointerToInt,
+    /// The integer to fat pointer conversion.
+    IntToPointer,
+    /// The fat pointer offset getter.
+    PointerOffset,
+    /// The fat pointer memory page getter.
+    PointerPage,
+    /// The fat pointer slice start getter.
+    PointerStart,
+    /// The fat pointer slice length getter.
+    PointerLength,
+    /// The optimizer assumption.
+    Assume,
+    /// The optimizer value expectation.
+    Expect,
+    /// The byte order reversal.
+    ByteSwap,
+    /// The stack allocation lifetime start marker.
+    LifetimeStart,
+    /// The stack allocation lifetime end marker.
+    LifetimeEnd,
 
     /// The long return.
     Return,
@@ -55,6 +77,8 @@ pub enum Intrinsic {
     MemoryCopy,
     /// The memory copy from a generic page.
     MemoryCopyFromGeneric,
+    /// The memory copy from a generic page to the auxiliary heap.
+    MemoryCopyFromGenericToAuxHeap,
 }
 
 impl Intrinsic {
@@ -78,12 +102,24 @@ impl Intrinsic {
             Intrinsic::IncrementTxCounter => "llvm.syncvm.inctx",
             Intrinsic::PointerShrink => "llvm.syncvm.ptr.shrink",
             Intrinsic::PointerPack => "llvm.syncvm.ptr.pack",
+            Intrinsic::PointerToInt => "llvm.syncvm.ptrtoint",
+            Intrinsic::IntToPointer => "llvm.syncvm.inttoptr",
+            Intrinsic::PointerOffset => "llvm.syncvm.ptr.offset",
+            Intrinsic::PointerPage => "llvm.syncvm.ptr.page",
+            Intrinsic::PointerStart => "llvm.syncvm.ptr.start",
+            Intrinsic::PointerLength => "llvm.syncvm.ptr.length",
+            Intrinsic::Assume => "llvm.assume",
+            Intrinsic::Expect => "llvm.expect",
+            Intrinsic::ByteSwap => "llvm.bswap",
+            Intrinsic::LifetimeStart => "llvm.lifetime.start",
+            Intrinsic::LifetimeEnd => "llvm.lifetime.end",
 
             Intrinsic::Return => "llvm.syncvm.return",
             Intrinsic::Revert => "llvm.syncvm.revert",
 
             Intrinsic::MemoryCopy => "llvm.memcpy",
             Intrinsic::MemoryCopyFromGeneric => "llvm.memcpy",
+            Intrinsic::MemoryCopyFromGenericToAuxHeap => "llvm.memcpy",
         }
     }
 
@@ -120,6 +156,23 @@ impl Intrinsic {
                     .as_basic_type_enum(),
                 context.field_type().as_basic_type_enum(),
             ],
+            Self::MemoryCopyFromGenericToAuxHeap => vec![
+                context
+                    .field_type()
+                    .ptr_type(AddressSpace::HeapAuxiliary.into())
+                    .as_basic_type_enum(),
+                context
+                    .field_type()
+                    .ptr_type(AddressSpace::Generic.into())
+                    .as_basic_type_enum(),
+                context.field_type().as_basic_type_enum(),
+            ],
+            Self::Expect => vec![context.field_type().as_basic_type_enum()],
+            Self::ByteSwap => vec![context.field_type().as_basic_type_enum()],
+            Self::LifetimeStart | Self::LifetimeEnd => vec![context
+                .integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Stack.into())
+                .as_basic_type_enum()],
             _ => vec![],
         }
     }
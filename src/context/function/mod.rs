@@ -13,6 +13,8 @@ pub mod runtime_code;
 
 use std::collections::HashMap;
 
+use crate::context::code_type::CodeType;
+
 use self::evm_data::EVMData;
 use self::r#return::Return;
 
@@ -46,6 +48,19 @@ pub struct Function<'ctx> {
     /// Is only used by the Vyper LLL IR compiler.
     pub label_arguments: HashMap<String, Vec<String>>,
 
+    /// Whether the function may only be executed by a system contract, that is, checks that
+    /// the current contract address is in the kernel space.
+    pub is_kernel_only: bool,
+    /// The code type (deploy or runtime) the function belongs to, if known.
+    /// The shared functions may be left unassigned, falling back to the context code type.
+    pub code_type: Option<CodeType>,
+    /// The calldata size loaded in the entry block, reused by the subsequent reads.
+    pub calldata_size: Option<inkwell::values::IntValue<'ctx>>,
+    /// The calldata pointer loaded in the entry block, reused by the calldata word pointers.
+    pub calldata_pointer: Option<inkwell::values::PointerValue<'ctx>>,
+    /// The calldata word pointers at the constant offsets, computed in the entry block.
+    pub calldata_word_pointers: HashMap<u64, inkwell::values::PointerValue<'ctx>>,
+
     /// The EVM compiler data.
     pub evm_data: Option<EVMData<'ctx>>,
 }
@@ -83,6 +98,11 @@ impl<'ctx> Function<'ctx> {
             stack: HashMap::with_capacity(Self::STACK_HASHMAP_INITIAL_CAPACITY),
             constants: HashMap::new(),
             label_arguments: HashMap::new(),
+            is_kernel_only: false,
+            code_type: None,
+            calldata_size: None,
+            calldata_pointer: None,
+            calldata_word_pointers: HashMap::new(),
 
             evm_data: None,
         }
@@ -21,6 +21,8 @@ pub enum Return<'ctx> {
         pointer: inkwell::values::PointerValue<'ctx>,
         /// The function return type size.
         size: usize,
+        /// The component names and types, if the components are named.
+        components: Vec<(String, inkwell::types::BasicTypeEnum<'ctx>)>,
     },
 }
 
@@ -43,7 +45,25 @@ impl<'ctx> Return<'ctx> {
     /// A shortcut constructor.
     ///
     pub fn compound(pointer: inkwell::values::PointerValue<'ctx>, size: usize) -> Self {
-        Self::Compound { pointer, size }
+        Self::Compound {
+            pointer,
+            size,
+            components: Vec::new(),
+        }
+    }
+
+    ///
+    /// A shortcut constructor for a compound value with named components.
+    ///
+    pub fn compound_named(
+        pointer: inkwell::values::PointerValue<'ctx>,
+        components: Vec<(String, inkwell::types::BasicTypeEnum<'ctx>)>,
+    ) -> Self {
+        Self::Compound {
+            pointer,
+            size: components.len(),
+            components,
+        }
     }
 
     ///
@@ -57,6 +77,21 @@ impl<'ctx> Return<'ctx> {
         }
     }
 
+    ///
+    /// Returns the index and type of the compound value component `name`.
+    ///
+    /// Returns `None` if the value is not compound or the component does not exist.
+    ///
+    pub fn component(&self, name: &str) -> Option<(u32, inkwell::types::BasicTypeEnum<'ctx>)> {
+        match self {
+            Self::Compound { components, .. } => components
+                .iter()
+                .position(|(component_name, _)| component_name == name)
+                .map(|index| (index as u32, components[index].1)),
+            _ => None,
+        }
+    }
+
     ///
     /// Returns the return data size in bytes, based on the default stack alignment.
     ///
@@ -0,0 +1,116 @@
+//!
+//! The far call runtime function specification.
+//!
+
+use inkwell::types::BasicType;
+
+use crate::context::address_space::AddressSpace;
+
+///
+/// The far call runtime function kind.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    /// The ordinary far call.
+    Far,
+    /// The static far call.
+    Static,
+    /// The delegate far call.
+    Delegate,
+    /// The far call on behalf of another address.
+    Mimic,
+}
+
+///
+/// The far call runtime function specification.
+///
+/// The arguments are derived from the specification:
+/// - the ABI data, passed as a generic heap pointer if `is_byref`
+/// - the callee address
+/// - two extra ABI data values, if `is_system`
+/// - the address to mimic, if the kind is `Mimic`
+/// - the result pointer
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FarCall {
+    /// The runtime function name.
+    pub name: &'static str,
+    /// The far call kind.
+    pub kind: Kind,
+    /// Whether the ABI data is passed by reference.
+    pub is_byref: bool,
+    /// Whether the call is a system one, passing the extra ABI data.
+    pub is_system: bool,
+}
+
+impl FarCall {
+    /// The number of the extra ABI data arguments of the system calls.
+    pub const SYSTEM_EXTRA_ARGUMENTS: usize = 2;
+
+    ///
+    /// A shortcut constructor.
+    ///
+    pub const fn new(name: &'static str, kind: Kind, is_byref: bool, is_system: bool) -> Self {
+        Self {
+            name,
+            kind,
+            is_byref,
+            is_system,
+        }
+    }
+
+    ///
+    /// Returns the far call result pointer type, shared by all the far call runtime functions.
+    ///
+    pub fn result_type<'ctx>(
+        llvm: &'ctx inkwell::context::Context,
+    ) -> inkwell::types::BasicTypeEnum<'ctx> {
+        llvm.struct_type(
+            &[
+                llvm.custom_width_int_type(compiler_common::BITLENGTH_BYTE as u32)
+                    .ptr_type(AddressSpace::Generic.into())
+                    .as_basic_type_enum(),
+                llvm.bool_type().as_basic_type_enum(),
+            ],
+            false,
+        )
+        .ptr_type(AddressSpace::Stack.into())
+        .as_basic_type_enum()
+    }
+
+    ///
+    /// Generates the runtime function type from the specification.
+    ///
+    pub fn r#type<'ctx>(
+        &self,
+        llvm: &'ctx inkwell::context::Context,
+    ) -> inkwell::types::FunctionType<'ctx> {
+        let field_type = llvm
+            .custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+            .as_basic_type_enum();
+        let result_type = Self::result_type(llvm);
+
+        let mut argument_types = Vec::with_capacity(6);
+        argument_types.push(if self.is_byref {
+            llvm.custom_width_int_type(compiler_common::BITLENGTH_BYTE as u32)
+                .ptr_type(AddressSpace::Generic.into())
+                .as_basic_type_enum()
+        } else {
+            field_type
+        });
+        argument_types.push(field_type);
+        if self.is_system {
+            argument_types.extend(vec![field_type; Self::SYSTEM_EXTRA_ARGUMENTS]);
+        }
+        if let Kind::Mimic = self.kind {
+            argument_types.push(field_type);
+        }
+        argument_types.push(result_type);
+
+        let argument_types: Vec<inkwell::types::BasicMetadataTypeEnum> = argument_types
+            .into_iter()
+            .map(inkwell::types::BasicMetadataTypeEnum::from)
+            .collect();
+        result_type.fn_type(argument_types.as_slice(), false)
+    }
+}
@@ -0,0 +1,679 @@
+//!
+//! The LLVM runtime functions.
+//!
+
+pub mod far_call;
+
+use std::collections::HashMap;
+
+use inkwell::types::BasicType;
+
+use crate::context::address_space::AddressSpace;
+use crate::context::attribute::Attribute;
+
+use self::far_call::FarCall;
+use self::far_call::Kind as FarCallKind;
+
+///
+/// The LLVM runtime functions, implemented in the LLVM back-end.
+/// The functions are automatically linked to the LLVM implementations if the signatures match.
+///
+#[derive(Debug)]
+pub struct Runtime<'ctx> {
+    /// The personality function, used for exception handling.
+    pub personality: inkwell::values::FunctionValue<'ctx>,
+    /// The exception throwing function.
+    pub cxa_throw: inkwell::values::FunctionValue<'ctx>,
+
+    /// The corresponding runtime function.
+    pub add_mod: inkwell::values::FunctionValue<'ctx>,
+    /// The corresponding runtime function.
+    pub mul_mod: inkwell::values::FunctionValue<'ctx>,
+    /// The corresponding runtime function.
+    pub sign_extend: inkwell::values::FunctionValue<'ctx>,
+
+    /// The corresponding runtime function.
+    pub storage_load: inkwell::values::FunctionValue<'ctx>,
+    /// The corresponding runtime function.
+    pub storage_store: inkwell::values::FunctionValue<'ctx>,
+
+    /// The far call runtime functions, declared according to `FAR_CALLS`.
+    /// Are retrieved with `modify`.
+    pub far_calls: HashMap<&'static str, inkwell::values::FunctionValue<'ctx>>,
+    /// The custom runtime functions declared by front-ends.
+    pub custom: HashMap<String, inkwell::values::FunctionValue<'ctx>>,
+}
+
+impl<'ctx> Runtime<'ctx> {
+    /// The runtime function name prefix.
+    pub const FUNCTION_PREFIX: &'static str = "__";
+
+    /// The LLVM contract main entry function name.
+    pub const FUNCTION_ENTRY: &'static str = "__entry";
+
+    /// The LLVM contract deploy code function name.
+    pub const FUNCTION_DEPLOY_CODE: &'static str = "__deploy";
+
+    /// The LLVM contract runtime code function name.
+    pub const FUNCTION_RUNTIME_CODE: &'static str = "__runtime";
+
+    /// The LLVM personality function name.
+    pub const FUNCTION_PERSONALITY: &'static str = "__personality";
+
+    /// The LLVM exception throwing function name.
+    pub const FUNCTION_CXA_THROW: &'static str = "__cxa_throw";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_ADDMOD: &'static str = "__addmod";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_MULMOD: &'static str = "__mulmod";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SIGNEXTEND: &'static str = "__signextend";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SLOAD: &'static str = "__sload";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SSTORE: &'static str = "__sstore";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_FARCALL: &'static str = "__farcall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_FARCALL_BYREF: &'static str = "__farcall_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_FARCALL: &'static str = "__system_call";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_FARCALL_BYREF: &'static str = "__system_call_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_STATICCALL: &'static str = "__staticcall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_STATICCALL_BYREF: &'static str = "__staticcall_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_STATICCALL: &'static str = "__system_staticcall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_STATICCALL_BYREF: &'static str = "__system_staticcall_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_DELEGATECALL: &'static str = "__delegatecall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_DELEGATECALL_BYREF: &'static str = "__delegatecall_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_DELEGATECALL: &'static str = "__system_delegatecall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_DELEGATECALL_BYREF: &'static str = "__system_delegatecall_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_MIMICCALL: &'static str = "__mimiccall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_MIMICCALL_BYREF: &'static str = "__mimiccall_byref";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_MIMICCALL: &'static str = "__system_mimiccall";
+
+    /// The corresponding runtime function name.
+    pub const FUNCTION_SYSTEM_MIMICCALL_BYREF: &'static str = "__system_mimiccall_byref";
+
+    /// The far call dispatcher, which is called instead of the far call runtime functions in
+    /// the far call mocking mode.
+    pub const FUNCTION_TEST_DISPATCH: &'static str = "__test_dispatch";
+
+    /// The far call runtime functions specification table. The index of a function is passed
+    /// to the dispatcher in the far call mocking mode.
+    pub const FAR_CALLS: [FarCall; 16] = [
+        FarCall::new(Self::FUNCTION_FARCALL, FarCallKind::Far, false, false),
+        FarCall::new(Self::FUNCTION_FARCALL_BYREF, FarCallKind::Far, true, false),
+        FarCall::new(Self::FUNCTION_SYSTEM_FARCALL, FarCallKind::Far, false, true),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_FARCALL_BYREF,
+            FarCallKind::Far,
+            true,
+            true,
+        ),
+        FarCall::new(Self::FUNCTION_STATICCALL, FarCallKind::Static, false, false),
+        FarCall::new(
+            Self::FUNCTION_STATICCALL_BYREF,
+            FarCallKind::Static,
+            true,
+            false,
+        ),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_STATICCALL,
+            FarCallKind::Static,
+            false,
+            true,
+        ),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_STATICCALL_BYREF,
+            FarCallKind::Static,
+            true,
+            true,
+        ),
+        FarCall::new(
+            Self::FUNCTION_DELEGATECALL,
+            FarCallKind::Delegate,
+            false,
+            false,
+        ),
+        FarCall::new(
+            Self::FUNCTION_DELEGATECALL_BYREF,
+            FarCallKind::Delegate,
+            true,
+            false,
+        ),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_DELEGATECALL,
+            FarCallKind::Delegate,
+            false,
+            true,
+        ),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_DELEGATECALL_BYREF,
+            FarCallKind::Delegate,
+            true,
+            true,
+        ),
+        FarCall::new(Self::FUNCTION_MIMICCALL, FarCallKind::Mimic, false, false),
+        FarCall::new(
+            Self::FUNCTION_MIMICCALL_BYREF,
+            FarCallKind::Mimic,
+            true,
+            false,
+        ),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_MIMICCALL,
+            FarCallKind::Mimic,
+            false,
+            true,
+        ),
+        FarCall::new(
+            Self::FUNCTION_SYSTEM_MIMICCALL_BYREF,
+            FarCallKind::Mimic,
+            true,
+            true,
+        ),
+    ];
+
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(
+        llvm: &'ctx inkwell::context::Context,
+        module: &inkwell::module::Module<'ctx>,
+    ) -> Self {
+        let personality = Self::declare(
+            module,
+            Self::FUNCTION_PERSONALITY,
+            llvm.i32_type().fn_type(&[], false),
+            None,
+        );
+
+        let cxa_throw = Self::declare(
+            module,
+            Self::FUNCTION_CXA_THROW,
+            llvm.void_type().fn_type(
+                vec![
+                    llvm.i8_type()
+                        .ptr_type(AddressSpace::Stack.into())
+                        .as_basic_type_enum()
+                        .into();
+                    3
+                ]
+                .as_slice(),
+                false,
+            ),
+            Some(inkwell::module::Linkage::External),
+        );
+        cxa_throw.add_attribute(
+            inkwell::attributes::AttributeLoc::Function,
+            llvm.create_enum_attribute(Attribute::NoProfile as u32, 0),
+        );
+
+        let add_mod = Self::declare(
+            module,
+            Self::FUNCTION_ADDMOD,
+            llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                .fn_type(
+                    vec![
+                        llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                            .as_basic_type_enum()
+                            .into();
+                        3
+                    ]
+                    .as_slice(),
+                    false,
+                ),
+            Some(inkwell::module::Linkage::External),
+        );
+        Self::apply_default_attributes(llvm, add_mod);
+        let mul_mod = Self::declare(
+            module,
+            Self::FUNCTION_MULMOD,
+            llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                .fn_type(
+                    vec![
+                        llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                            .as_basic_type_enum()
+                            .into();
+                        3
+                    ]
+                    .as_slice(),
+                    false,
+                ),
+            Some(inkwell::module::Linkage::External),
+        );
+        Self::apply_default_attributes(llvm, mul_mod);
+        let sign_extend = Self::declare(
+            module,
+            Self::FUNCTION_SIGNEXTEND,
+            llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                .fn_type(
+                    vec![
+                        llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                            .as_basic_type_enum()
+                            .into();
+                        2
+                    ]
+                    .as_slice(),
+                    false,
+                ),
+            Some(inkwell::module::Linkage::External),
+        );
+        Self::apply_default_attributes(llvm, sign_extend);
+
+        let storage_load = Self::declare(
+            module,
+            Self::FUNCTION_SLOAD,
+            llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                .fn_type(
+                    vec![
+                        llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                            .as_basic_type_enum()
+                            .into();
+                        1
+                    ]
+                    .as_slice(),
+                    false,
+                ),
+            Some(inkwell::module::Linkage::External),
+        );
+        let storage_store = Self::declare(
+            module,
+            Self::FUNCTION_SSTORE,
+            llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                .fn_type(
+                    vec![
+                        llvm.custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                            .as_basic_type_enum()
+                            .into();
+                        2
+                    ]
+                    .as_slice(),
+                    false,
+                ),
+            Some(inkwell::module::Linkage::External),
+        );
+
+        let far_calls: HashMap<&'static str, inkwell::values::FunctionValue<'ctx>> =
+            Self::FAR_CALLS
+                .iter()
+                .map(|far_call| {
+                    let function = Self::declare(
+                        module,
+                        far_call.name,
+                        far_call.r#type(llvm),
+                        Some(inkwell::module::Linkage::External),
+                    );
+                    (far_call.name, function)
+                })
+                .collect();
+
+        Self {
+            personality,
+            cxa_throw,
+
+            add_mod,
+            mul_mod,
+
+            sign_extend,
+
+            storage_load,
+            storage_store,
+
+            far_calls,
+            custom: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Declares a custom runtime function implemented by the front-end or linked from elsewhere.
+    ///
+    /// The `name` is prefixed with `__` if it does not have the prefix yet. Declaring the same
+    /// function again returns the existing declaration if the types match.
+    ///
+    pub fn declare_custom(
+        &mut self,
+        llvm: &'ctx inkwell::context::Context,
+        module: &inkwell::module::Module<'ctx>,
+        name: &str,
+        r#type: inkwell::types::FunctionType<'ctx>,
+        attributes: &[Attribute],
+    ) -> anyhow::Result<inkwell::values::FunctionValue<'ctx>> {
+        let name = if name.starts_with(Self::FUNCTION_PREFIX) {
+            name.to_owned()
+        } else {
+            format!("{}{}", Self::FUNCTION_PREFIX, name)
+        };
+
+        if let Some(function) = self.custom.get(name.as_str()) {
+            if function.get_type() != r#type {
+                anyhow::bail!(
+                    "Runtime function `{}` is already declared with another type",
+                    name
+                );
+            }
+            return Ok(*function);
+        }
+        if module.get_function(name.as_str()).is_some() {
+            anyhow::bail!("Runtime function `{}` is reserved", name);
+        }
+
+        let function = module.add_function(
+            name.as_str(),
+            r#type,
+            Some(inkwell::module::Linkage::External),
+        );
+        for attribute in attributes.iter() {
+            function.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                llvm.create_enum_attribute(*attribute as u32, 0),
+            );
+        }
+        self.custom.insert(name, function);
+
+        Ok(function)
+    }
+
+    ///
+    /// Returns the kind of the far call runtime `function`.
+    ///
+    pub fn far_call_kind(
+        function: inkwell::values::FunctionValue<'ctx>,
+    ) -> anyhow::Result<FarCallKind> {
+        let name = function.get_name().to_string_lossy();
+        Self::FAR_CALLS
+            .iter()
+            .find(|far_call| far_call.name == name.as_ref())
+            .map(|far_call| far_call.kind)
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` is not a far call", name))
+    }
+
+    ///
+    /// Returns the index of the far call runtime `function` in `FAR_CALLS`.
+    ///
+    pub fn far_call_index(function: inkwell::values::FunctionValue<'ctx>) -> Option<usize> {
+        let name = function.get_name().to_string_lossy();
+        Self::FAR_CALLS
+            .iter()
+            .position(|far_call| far_call.name == name.as_ref())
+    }
+
+    ///
+    /// Returns the far call dispatcher type, which must be used to declare it.
+    ///
+    /// The dispatcher is variadic, since it accepts the far call function index followed by
+    /// the arguments of any far call runtime function.
+    ///
+    pub fn test_dispatch_type(
+        llvm: &'ctx inkwell::context::Context,
+    ) -> inkwell::types::FunctionType<'ctx> {
+        FarCall::result_type(llvm).fn_type(
+            &[llvm
+                .custom_width_int_type(compiler_common::BITLENGTH_FIELD as u32)
+                .as_basic_type_enum()
+                .into()],
+            true,
+        )
+    }
+
+    ///
+    /// Whether the function must be skipped by the function-level optimizer.
+    ///
+    /// The built-in runtime functions are implemented in the LLVM back-end, whereas the custom
+    /// ones are optimized as soon as the front-end has provided their bodies.
+    ///
+    pub fn is_optimizer_skipped(&self, function: inkwell::values::FunctionValue<'ctx>) -> bool {
+        let name = function.get_name().to_string_lossy();
+        if name.starts_with("llvm.") {
+            return true;
+        }
+        if !name.starts_with(Self::FUNCTION_PREFIX) {
+            return false;
+        }
+        if [
+            Self::FUNCTION_ENTRY,
+            Self::FUNCTION_DEPLOY_CODE,
+            Self::FUNCTION_RUNTIME_CODE,
+        ]
+        .contains(&name.as_ref())
+        {
+            return false;
+        }
+
+        !(self.custom.contains_key(name.as_ref()) && function.count_basic_blocks() > 0)
+    }
+
+    ///
+    /// Returns the far call runtime function of `kind` with the `is_byref` and `is_system`
+    /// modifiers.
+    ///
+    pub fn modify(
+        &self,
+        kind: FarCallKind,
+        is_byref: bool,
+        is_system: bool,
+    ) -> inkwell::values::FunctionValue<'ctx> {
+        let far_call = Self::FAR_CALLS
+            .iter()
+            .find(|far_call| {
+                far_call.kind == kind
+                    && far_call.is_byref == is_byref
+                    && far_call.is_system == is_system
+            })
+            .expect("All the combinations are specified");
+        self.far_calls[far_call.name]
+    }
+
+    ///
+    /// Declares the runtime function `name`, or returns the existing declaration, e.g. if
+    /// the module has been cloned.
+    ///
+    fn declare(
+        module: &inkwell::module::Module<'ctx>,
+        name: &str,
+        r#type: inkwell::types::FunctionType<'ctx>,
+        linkage: Option<inkwell::module::Linkage>,
+    ) -> inkwell::values::FunctionValue<'ctx> {
+        module
+            .get_function(name)
+            .unwrap_or_else(|| module.add_function(name, r#type, linkage))
+    }
+
+    ///
+    /// Applies the default attribute set for the math function.
+    ///
+    fn apply_default_attributes(
+        llvm: &'ctx inkwell::context::Context,
+        function: inkwell::values::FunctionValue<'ctx>,
+    ) {
+        for attribute_kind in [
+            Attribute::MustProgress,
+            Attribute::NoUnwind,
+            Attribute::ReadNone,
+            Attribute::WillReturn,
+        ]
+        .into_iter()
+        {
+            function.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                llvm.create_enum_attribute(attribute_kind as u32, 0),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::far_call::Kind as FarCallKind;
+    use super::Runtime;
+
+    #[test]
+    fn modify() {
+        let llvm = inkwell::context::Context::create();
+        let module = llvm.create_module("test");
+        let runtime = Runtime::new(&llvm, &module);
+
+        let expected = [
+            (FarCallKind::Far, false, false, Runtime::FUNCTION_FARCALL),
+            (
+                FarCallKind::Far,
+                true,
+                false,
+                Runtime::FUNCTION_FARCALL_BYREF,
+            ),
+            (
+                FarCallKind::Far,
+                false,
+                true,
+                Runtime::FUNCTION_SYSTEM_FARCALL,
+            ),
+            (
+                FarCallKind::Far,
+                true,
+                true,
+                Runtime::FUNCTION_SYSTEM_FARCALL_BYREF,
+            ),
+            (
+                FarCallKind::Static,
+                false,
+                false,
+                Runtime::FUNCTION_STATICCALL,
+            ),
+            (
+                FarCallKind::Static,
+                true,
+                false,
+                Runtime::FUNCTION_STATICCALL_BYREF,
+            ),
+            (
+                FarCallKind::Static,
+                false,
+                true,
+                Runtime::FUNCTION_SYSTEM_STATICCALL,
+            ),
+            (
+                FarCallKind::Static,
+                true,
+                true,
+                Runtime::FUNCTION_SYSTEM_STATICCALL_BYREF,
+            ),
+            (
+                FarCallKind::Delegate,
+                false,
+                false,
+                Runtime::FUNCTION_DELEGATECALL,
+            ),
+            (
+                FarCallKind::Delegate,
+                true,
+                false,
+                Runtime::FUNCTION_DELEGATECALL_BYREF,
+            ),
+            (
+                FarCallKind::Delegate,
+                false,
+                true,
+                Runtime::FUNCTION_SYSTEM_DELEGATECALL,
+            ),
+            (
+                FarCallKind::Delegate,
+                true,
+                true,
+                Runtime::FUNCTION_SYSTEM_DELEGATECALL_BYREF,
+            ),
+            (
+                FarCallKind::Mimic,
+                false,
+                false,
+                Runtime::FUNCTION_MIMICCALL,
+            ),
+            (
+                FarCallKind::Mimic,
+                true,
+                false,
+                Runtime::FUNCTION_MIMICCALL_BYREF,
+            ),
+            (
+                FarCallKind::Mimic,
+                false,
+                true,
+                Runtime::FUNCTION_SYSTEM_MIMICCALL,
+            ),
+            (
+                FarCallKind::Mimic,
+                true,
+                true,
+                Runtime::FUNCTION_SYSTEM_MIMICCALL_BYREF,
+            ),
+        ];
+        for (kind, is_byref, is_system, name) in expected.into_iter() {
+            let function = runtime.modify(kind, is_byref, is_system);
+            assert_eq!(
+                function.get_name().to_string_lossy(),
+                name,
+                "{:?} is_byref={} is_system={}",
+                kind,
+                is_byref,
+                is_system
+            );
+            assert_eq!(
+                Runtime::far_call_kind(function).expect("Always valid"),
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn mimic_call_arguments() {
+        let llvm = inkwell::context::Context::create();
+        let module = llvm.create_module("test");
+        let runtime = Runtime::new(&llvm, &module);
+
+        let mimic_call = runtime.modify(FarCallKind::Mimic, true, false);
+        assert_eq!(mimic_call.count_params(), 4);
+        assert!(mimic_call.get_type().get_param_types()[0].is_pointer_type());
+
+        let system_mimic_call = runtime.modify(FarCallKind::Mimic, true, true);
+        let parameter_types = system_mimic_call.get_type().get_param_types();
+        assert_eq!(parameter_types.len(), 6);
+        assert!(parameter_types[1..5]
+            .iter()
+            .all(|r#type| r#type.is_int_type()));
+        assert_eq!(
+            parameter_types[5],
+            super::far_call::FarCall::result_type(&llvm)
+        );
+    }
+}
@@ -0,0 +1,24 @@
+//!
+//! The translation pipeline hook stage.
+//!
+
+///
+/// The translation pipeline hook stage.
+///
+/// Defines where the hooks registered with `Context::add_hook` are invoked.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookStage {
+    /// Before an entity is translated with `Context::write_llvm`.
+    /// The hook subject is the entity type name.
+    BeforeTranslation,
+    /// After an entity has been translated with `Context::write_llvm`.
+    /// The hook subject is the entity type name.
+    AfterTranslation,
+    /// Before the LLVM IR optimization, after the unoptimized IR has been verified.
+    /// The hook subject is the contract path.
+    BeforeOptimization,
+    /// Before the assembly is generated and assembled, after the optimized IR has been verified.
+    /// The hook subject is the contract path.
+    BeforeAssembling,
+}
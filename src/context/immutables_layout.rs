@@ -0,0 +1,45 @@
+//!
+//! The constructor return data immutables layout.
+//!
+
+///
+/// The constructor return data immutables layout.
+///
+/// The deploy code returns the immutables written by the constructor in the auxiliary heap,
+/// prefixed with the array offset and the number of immutables.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ImmutablesLayout {
+    /// The `(index, value)` pairs, expected by the system contract deployer.
+    Pairs,
+    /// The values only, where the index of each value is implied by its position.
+    /// Halves the constructor return data size, but requires a deployer supporting the layout.
+    Compact,
+}
+
+impl Default for ImmutablesLayout {
+    fn default() -> Self {
+        Self::Pairs
+    }
+}
+
+impl ImmutablesLayout {
+    ///
+    /// Returns the size of the constructor return data entry per immutable value.
+    ///
+    pub fn entry_size(&self) -> usize {
+        match self {
+            Self::Pairs => compiler_common::SIZE_FIELD * 2,
+            Self::Compact => compiler_common::SIZE_FIELD,
+        }
+    }
+}
+
+impl std::fmt::Display for ImmutablesLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pairs => write!(f, "pairs"),
+            Self::Compact => write!(f, "compact"),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//!
+//! The unbounded memory growth analysis.
+//!
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use inkwell::values::AsValueRef;
+use inkwell::values::BasicValue;
+
+use crate::context::address_space::AddressSpace;
+
+///
+/// The unbounded memory growth analysis.
+///
+/// Detects the heap accesses whose offsets are derived from the calldata words without a prior
+/// bound check. Such offsets allow the caller to make the contract grow its heap arbitrarily,
+/// exhausting the ergs.
+///
+/// The analysis is intentionally coarse. The values loaded from the generic address space are
+/// tainted, and the taint is propagated through the arithmetic and the stack variables. Any
+/// comparison of a tainted value is treated as a bound check, as well as masking with a constant.
+///
+#[derive(Debug, Default)]
+pub struct MemoryGrowth {
+    /// The tainted values.
+    tainted: HashSet<llvm_sys::prelude::LLVMValueRef>,
+    /// The tainted values which have been compared, or the stack variables they were loaded from.
+    validated: HashSet<llvm_sys::prelude::LLVMValueRef>,
+}
+
+impl MemoryGrowth {
+    ///
+    /// Runs the analysis on `module`, returning the number of the unbounded heap accesses
+    /// per function name.
+    ///
+    pub fn analyze(module: &inkwell::module::Module) -> BTreeMap<String, usize> {
+        let mut warnings = BTreeMap::new();
+        let mut function = module.get_first_function();
+        while let Some(current) = function {
+            let count = Self::default().analyze_function(current);
+            if count > 0 {
+                warnings.insert(current.get_name().to_string_lossy().into_owned(), count);
+            }
+            function = current.get_next_function();
+        }
+        warnings
+    }
+
+    ///
+    /// Runs the analysis on `function`, returning the number of the unbounded heap accesses.
+    ///
+    fn analyze_function(&mut self, function: inkwell::values::FunctionValue) -> usize {
+        let mut count = 0;
+
+        let mut block = function.get_first_basic_block();
+        while let Some(current_block) = block {
+            let mut instruction = current_block.get_first_instruction();
+            while let Some(current) = instruction {
+                count += self.analyze_instruction(current) as usize;
+                instruction = current.get_next_instruction();
+            }
+            block = current_block.get_next_basic_block();
+        }
+
+        count
+    }
+
+    ///
+    /// Updates the taint with `instruction`, returning whether it is an unbounded heap access.
+    ///
+    fn analyze_instruction(&mut self, instruction: inkwell::values::InstructionValue) -> bool {
+        let operands: Vec<inkwell::values::BasicValueEnum> = (0..instruction.get_num_operands())
+            .filter_map(|index| {
+                instruction
+                    .get_operand(index)
+                    .and_then(|operand| operand.left())
+            })
+            .collect();
+
+        match instruction.get_opcode() {
+            inkwell::values::InstructionOpcode::Load => {
+                let pointer = operands[0].into_pointer_value();
+                if pointer.get_type().get_address_space() == AddressSpace::Generic.into()
+                    || self.is_tainted(&operands[0])
+                {
+                    self.tainted.insert(instruction.as_value_ref());
+                }
+            }
+            inkwell::values::InstructionOpcode::Store => {
+                if self.is_tainted(&operands[0]) {
+                    self.tainted.insert(operands[1].as_value_ref());
+                }
+            }
+            inkwell::values::InstructionOpcode::ICmp => {
+                let tainted: Vec<&inkwell::values::BasicValueEnum> = operands
+                    .iter()
+                    .filter(|operand| self.is_tainted(operand))
+                    .collect();
+                for operand in tainted.into_iter() {
+                    self.validated.insert(operand.as_value_ref());
+                    if let Some(load) = operand.as_instruction_value().filter(|value| {
+                        value.get_opcode() == inkwell::values::InstructionOpcode::Load
+                    }) {
+                        if let Some(pointer) =
+                            load.get_operand(0).and_then(|operand| operand.left())
+                        {
+                            self.validated.insert(pointer.as_value_ref());
+                        }
+                    }
+                }
+            }
+            inkwell::values::InstructionOpcode::And => {
+                let is_masked = operands
+                    .iter()
+                    .any(|operand| operand.is_int_value() && operand.into_int_value().is_const());
+                if !is_masked && operands.iter().any(|operand| self.is_tainted(operand)) {
+                    self.tainted.insert(instruction.as_value_ref());
+                }
+            }
+            inkwell::values::InstructionOpcode::Add
+            | inkwell::values::InstructionOpcode::Sub
+            | inkwell::values::InstructionOpcode::Mul
+            | inkwell::values::InstructionOpcode::Shl
+            | inkwell::values::InstructionOpcode::LShr
+            | inkwell::values::InstructionOpcode::AShr
+            | inkwell::values::InstructionOpcode::Or
+            | inkwell::values::InstructionOpcode::Xor
+            | inkwell::values::InstructionOpcode::ZExt
+            | inkwell::values::InstructionOpcode::SExt
+            | inkwell::values::InstructionOpcode::Trunc
+            | inkwell::values::InstructionOpcode::Select
+            | inkwell::values::InstructionOpcode::Phi => {
+                if operands.iter().any(|operand| self.is_tainted(operand)) {
+                    self.tainted.insert(instruction.as_value_ref());
+                }
+            }
+            inkwell::values::InstructionOpcode::IntToPtr => {
+                let address_space = instruction
+                    .get_type()
+                    .into_pointer_type()
+                    .get_address_space();
+                return address_space == AddressSpace::Heap.into() && self.is_tainted(&operands[0]);
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    ///
+    /// Whether `value` is tainted and has not been validated.
+    ///
+    fn is_tainted(&self, value: &inkwell::values::BasicValueEnum) -> bool {
+        let value = value.as_value_ref();
+        self.tainted.contains(&value) && !self.validated.contains(&value)
+    }
+}
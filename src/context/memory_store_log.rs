@@ -0,0 +1,172 @@
+//!
+//! The constant heap store log.
+//!
+
+use crate::context::address_space::AddressSpace;
+
+///
+/// The constant heap store log.
+///
+/// Records the heap stores of the constant words at the constant offsets within the current
+/// basic block, so the contents of a heap region filled exclusively with such stores can be
+/// computed at compile time, e.g. to fold the hashing of the constant data.
+///
+/// The log is restarted whenever a store is recorded in another basic block. The contents are
+/// only reported if no other heap store or call has been emitted since the first logged store.
+///
+#[derive(Debug, Default)]
+pub struct MemoryStoreLog<'ctx> {
+    /// The basic block the stores have been logged in.
+    block: Option<inkwell::basic_block::BasicBlock<'ctx>>,
+    /// The logged stores in the emission order.
+    entries: Vec<Entry<'ctx>>,
+}
+
+///
+/// The logged constant heap store.
+///
+#[derive(Debug, Clone, Copy)]
+struct Entry<'ctx> {
+    /// The heap offset.
+    offset: u64,
+    /// The stored word in the big-endian order.
+    word: [u8; compiler_common::SIZE_FIELD],
+    /// The store instruction.
+    instruction: inkwell::values::InstructionValue<'ctx>,
+}
+
+impl<'ctx> MemoryStoreLog<'ctx> {
+    ///
+    /// Records the store of `word` at `offset` emitted as `instruction` in `block`.
+    ///
+    pub fn record(
+        &mut self,
+        block: inkwell::basic_block::BasicBlock<'ctx>,
+        offset: u64,
+        word: [u8; compiler_common::SIZE_FIELD],
+        instruction: inkwell::values::InstructionValue<'ctx>,
+    ) {
+        if self.block != Some(block) {
+            self.block = Some(block);
+            self.entries.clear();
+        }
+        self.entries.push(Entry {
+            offset,
+            word,
+            instruction,
+        });
+    }
+
+    ///
+    /// Returns the contents of `length` bytes at `offset`, if they are known at the current
+    /// insertion point at the end of `block`.
+    ///
+    pub fn contents(
+        &self,
+        block: inkwell::basic_block::BasicBlock<'ctx>,
+        offset: u64,
+        length: u64,
+    ) -> Option<Vec<u8>> {
+        if self.block != Some(block) || !self.is_intact() {
+            return None;
+        }
+
+        let words: Vec<(u64, [u8; compiler_common::SIZE_FIELD])> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.offset, entry.word))
+            .collect();
+        Self::merge(words.as_slice(), offset, length)
+    }
+
+    ///
+    /// Checks that no heap store other than the logged ones and no call has been emitted since
+    /// the first logged store.
+    ///
+    fn is_intact(&self) -> bool {
+        let mut instruction = match self.entries.first() {
+            Some(entry) => Some(entry.instruction),
+            None => return false,
+        };
+        while let Some(current) = instruction {
+            match current.get_opcode() {
+                inkwell::values::InstructionOpcode::Call
+                | inkwell::values::InstructionOpcode::Invoke => return false,
+                inkwell::values::InstructionOpcode::Store
+                    if !self
+                        .entries
+                        .iter()
+                        .any(|entry| entry.instruction == current) =>
+                {
+                    let is_heap = current
+                        .get_operand(1)
+                        .and_then(|operand| operand.left())
+                        .map_or(true, |pointer| {
+                            pointer.into_pointer_value().get_type().get_address_space()
+                                == inkwell::AddressSpace::from(AddressSpace::Heap)
+                        });
+                    if is_heap {
+                        return false;
+                    }
+                }
+                _ => {}
+            }
+            instruction = current.get_next_instruction();
+        }
+        true
+    }
+
+    ///
+    /// Returns `length` bytes at `offset` written by `words` in order, if they are all covered.
+    ///
+    fn merge(
+        words: &[(u64, [u8; compiler_common::SIZE_FIELD])],
+        offset: u64,
+        length: u64,
+    ) -> Option<Vec<u8>> {
+        let end = offset.checked_add(length)?;
+        let mut contents = vec![None; usize::try_from(length).ok()?];
+        for (word_offset, word) in words.iter() {
+            for (index, byte) in word.iter().enumerate() {
+                let byte_offset = match word_offset.checked_add(index as u64) {
+                    Some(byte_offset) if (offset..end).contains(&byte_offset) => byte_offset,
+                    _ => continue,
+                };
+                contents[(byte_offset - offset) as usize] = Some(*byte);
+            }
+        }
+        contents.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryStoreLog;
+
+    fn word(byte: u8) -> [u8; compiler_common::SIZE_FIELD] {
+        [byte; compiler_common::SIZE_FIELD]
+    }
+
+    #[test]
+    fn merge_covered() {
+        let contents =
+            MemoryStoreLog::merge(&[(0, word(1)), (32, word(2))], 16, 32).expect("Always covered");
+
+        assert_eq!(contents[..16], word(1)[..16]);
+        assert_eq!(contents[16..], word(2)[..16]);
+    }
+
+    #[test]
+    fn merge_overwritten() {
+        let contents =
+            MemoryStoreLog::merge(&[(0, word(1)), (16, word(2))], 0, 32).expect("Always covered");
+
+        assert_eq!(contents[..16], word(1)[..16]);
+        assert_eq!(contents[16..], word(2)[..16]);
+    }
+
+    #[test]
+    fn merge_gap() {
+        assert!(MemoryStoreLog::merge(&[(0, word(1)), (64, word(2))], 0, 96).is_none());
+    }
+}
@@ -4,38 +4,102 @@
 
 pub mod address_space;
 pub mod argument;
+pub mod assembly_routine;
 pub mod attribute;
+pub mod aux_heap_layout;
 pub mod build;
+pub mod build_statistics;
+pub mod build_timings;
+pub mod calldata_slice;
+pub mod code_hash;
 pub mod code_type;
+pub mod cold_split;
+pub mod constructor_return_builder;
+pub mod contract_parts;
+pub mod coverage;
+pub mod dead_arguments;
+pub mod dependency_policy;
 pub mod evm_data;
+pub mod far_call_result;
+pub mod fat_pointer_field;
+pub mod frozen_module;
 pub mod function;
+pub mod hook_stage;
+pub mod immutables_layout;
 pub mod r#loop;
+pub mod memory_growth;
+pub mod memory_store_log;
+pub mod names;
 pub mod optimizer;
+pub mod output_format;
+pub mod path_sanitizer;
+pub mod peephole;
+pub mod remarks;
+pub mod snapshot;
+pub mod tail_merge;
+pub mod value_range;
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::time::Duration;
+use std::time::Instant;
 
+use inkwell::types::AsTypeRef;
 use inkwell::types::BasicType;
+use inkwell::values::AsValueRef;
 use inkwell::values::BasicValue;
 
+use crate::dependency_artifact::DependencyArtifact;
 use crate::dump_flag::DumpFlag;
+use crate::error::Error;
 use crate::Dependency;
+use crate::WriteLLVM;
 
 use self::address_space::AddressSpace;
+use self::assembly_routine::AssemblyRoutine;
 use self::attribute::Attribute;
+use self::aux_heap_layout::AuxHeapLayout;
 use self::build::Build;
+use self::build_statistics::BuildStatistics;
+use self::build_timings::BuildTimings;
 use self::code_type::CodeType;
+use self::dependency_policy::DependencyPolicy;
 use self::evm_data::EVMData;
+use self::far_call_result::FarCallResult;
+use self::fat_pointer_field::FatPointerField;
+use self::frozen_module::FrozenModule;
 use self::function::evm_data::EVMData as FunctionEVMData;
 use self::function::intrinsic::Intrinsic as IntrinsicFunction;
 use self::function::r#return::Return as FunctionReturn;
 use self::function::runtime::Runtime;
 use self::function::Function;
+use self::hook_stage::HookStage;
+use self::immutables_layout::ImmutablesLayout;
+use self::memory_growth::MemoryGrowth;
+use self::memory_store_log::MemoryStoreLog;
+use self::names::Names;
 use self::optimizer::settings::size_level::SizeLevel;
+use self::optimizer::settings::Settings as OptimizerSettings;
 use self::optimizer::Optimizer;
+use self::output_format::OutputFormat;
+use self::path_sanitizer::PathSanitizer;
 use self::r#loop::Loop;
+use self::remarks::Remarks;
+use self::snapshot::Snapshot;
+
+///
+/// The module initialization snippet, contributed by a front-end.
+///
+pub type GlobalInitializer<'ctx, D> =
+    Box<dyn FnOnce(&mut Context<'ctx, D>) -> anyhow::Result<()> + 'ctx>;
+
+///
+/// The translation pipeline hook, called with the stage subject, e.g. the contract path.
+///
+pub type Hook<'ctx, D> = Box<dyn FnMut(&mut Context<'ctx, D>, &str) -> anyhow::Result<()> + 'ctx>;
 
 ///
 /// The LLVM generator context.
@@ -59,12 +123,24 @@ where
     function: Option<Function<'ctx>>,
     /// The loop context stack.
     loop_stack: Vec<Loop<'ctx>>,
+    /// The value and block names interner.
+    names: Names,
+    /// The optimization remarks handler, if the remarks are enabled in this or another context.
+    remarks: Option<Box<Remarks>>,
+    /// The module initialization snippets, emitted at the beginning of the entry function.
+    global_initializers: Vec<GlobalInitializer<'ctx, D>>,
+    /// The translation pipeline hooks in the registration order.
+    hooks: Vec<(HookStage, Hook<'ctx, D>)>,
 
     /// The runtime functions, implemented in the LLVM back-end.
     /// The functions are automatically linked to the LLVM implementations if the signatures match.
     pub runtime: Runtime<'ctx>,
     /// The declared functions.
     pub functions: HashMap<String, Function<'ctx>>,
+    /// The current contract namespace, if several contracts are compiled into the same module.
+    contract_namespace: Option<String>,
+    /// The declared functions of the contracts whose namespaces are not currently entered.
+    namespaced_functions: BTreeMap<String, HashMap<String, Function<'ctx>>>,
 
     /// The current contract code type (deploy or runtime).
     code_type: Option<CodeType>,
@@ -72,6 +148,8 @@ where
     /// The manager is used to get information about contracts and their dependencies during
     /// the multi-threaded compilation process.
     dependency_manager: Option<Arc<RwLock<D>>>,
+    /// The dependency resolution policy if the dependency manager is unset.
+    dependency_policy: DependencyPolicy,
     /// The flags telling whether to dump the specified IRs.
     dump_flags: Vec<DumpFlag>,
 
@@ -83,6 +161,25 @@ where
     /// The immutables identifier-to-offset mapping. Is only used by Solidity due to
     /// the arbitrariness of its identifiers.
     immutables: BTreeMap<String, usize>,
+    /// The constructor return data immutables layout.
+    immutables_layout: ImmutablesLayout,
+    /// The auxiliary heap layout.
+    aux_heap_layout: AuxHeapLayout,
+    /// The hand-written assembly routines appended to the generated assembly.
+    assembly_routines: Vec<AssemblyRoutine>,
+    /// The deferred linker symbol-to-library-path mapping.
+    linker_symbols: BTreeMap<String, String>,
+    /// The hash-to-path mapping of the contract factory dependencies, including the transitive
+    /// ones.
+    factory_dependencies: BTreeMap<String, String>,
+    /// The context creation time, where the translation starts.
+    created_at: Instant,
+    /// The total duration of the dependency compilation callbacks.
+    dependencies_duration: Duration,
+    /// The sanitizer of the contract paths the module and dependency names are derived from.
+    path_sanitizer: PathSanitizer,
+    /// The constant heap stores of the current basic block.
+    memory_store_log: MemoryStoreLog<'ctx>,
 }
 
 impl<'ctx, D> Context<'ctx, D>
@@ -95,6 +192,18 @@ where
     /// The loop stack default capacity.
     const LOOP_STACK_INITIAL_CAPACITY: usize = 16;
 
+    /// The branch weight of the likely path, matching the LLVM `expect` lowering.
+    pub const BRANCH_WEIGHT_LIKELY: u32 = 2000;
+
+    /// The branch weight of the unlikely path, matching the LLVM `expect` lowering.
+    pub const BRANCH_WEIGHT_UNLIKELY: u32 = 1;
+
+    /// The separator between the contract namespace and the function or global variable name.
+    pub const CONTRACT_NAMESPACE_SEPARATOR: &'static str = ".";
+
+    /// The address bit-length, which is the same as in EVM.
+    pub const BITLENGTH_ADDRESS: usize = 160;
+
     ///
     /// Initializes a new LLVM context.
     ///
@@ -105,7 +214,18 @@ where
         dependency_manager: Option<Arc<RwLock<D>>>,
         dump_flags: Vec<DumpFlag>,
     ) -> Self {
-        let module = llvm.create_module(module_name);
+        let names = Names::new(llvm, optimizer.settings().discard_value_names);
+        let remarks = if optimizer.settings().is_remarks_enabled {
+            Some(Remarks::enable(llvm))
+        } else {
+            Remarks::discard(llvm)
+        };
+
+        let mut path_sanitizer = PathSanitizer::default();
+        let module_name = path_sanitizer
+            .sanitize(module_name)
+            .expect("The first path never collides");
+        let module = llvm.create_module(module_name.as_str());
         optimizer.set_module(&module);
         let runtime = Runtime::new(llvm, &module);
 
@@ -116,17 +236,33 @@ where
             module,
             function: None,
             loop_stack: Vec::with_capacity(Self::LOOP_STACK_INITIAL_CAPACITY),
+            names,
+            remarks,
+            global_initializers: Vec::new(),
+            hooks: Vec::new(),
 
             runtime,
             functions: HashMap::with_capacity(Self::FUNCTION_HASHMAP_INITIAL_CAPACITY),
+            contract_namespace: None,
+            namespaced_functions: BTreeMap::new(),
 
             code_type: None,
             dependency_manager,
+            dependency_policy: DependencyPolicy::default(),
             dump_flags,
 
             evm_data: None,
             immutables_size: 0,
             immutables: BTreeMap::new(),
+            immutables_layout: ImmutablesLayout::default(),
+            aux_heap_layout: AuxHeapLayout::default(),
+            assembly_routines: Vec::new(),
+            linker_symbols: BTreeMap::new(),
+            factory_dependencies: BTreeMap::new(),
+            created_at: Instant::now(),
+            dependencies_duration: Duration::ZERO,
+            path_sanitizer,
+            memory_store_log: MemoryStoreLog::default(),
         }
     }
 
@@ -146,37 +282,238 @@ where
         object
     }
 
+    ///
+    /// Clones the current module into a new context with another `optimizer`.
+    ///
+    /// Allows building the same translated IR with different optimizer settings and comparing
+    /// the results. The function declarations are carried over and bound to the cloned module,
+    /// but the rest of the translation state, such as the stack and return pointers, is not, so
+    /// the fork is only meant to be built. The optimization remarks are not collected for the fork,
+    /// since the LLVM context diagnostic handler is shared. For the same reason, the LLVM errors
+    /// emitted while building the fork are reported by the build of this context.
+    ///
+    pub fn fork(&self, mut optimizer: Optimizer<'ctx>) -> Self {
+        let module = self.module.clone();
+        optimizer.set_module(&module);
+        let mut runtime = Runtime::new(self.llvm, &module);
+        runtime.custom = self
+            .runtime
+            .custom
+            .keys()
+            .filter_map(|name| {
+                module
+                    .get_function(name.as_str())
+                    .map(|function| (name.to_owned(), function))
+            })
+            .collect();
+        let functions = Self::fork_functions(&module, &self.functions);
+        let namespaced_functions = self
+            .namespaced_functions
+            .iter()
+            .map(|(namespace, functions)| {
+                (
+                    namespace.to_owned(),
+                    Self::fork_functions(&module, functions),
+                )
+            })
+            .collect();
+
+        Self {
+            llvm: self.llvm,
+            builder: self.llvm.create_builder(),
+            optimizer,
+            module,
+            function: None,
+            loop_stack: Vec::with_capacity(Self::LOOP_STACK_INITIAL_CAPACITY),
+            names: Names::new(self.llvm, self.names.is_discarded()),
+            remarks: None,
+            global_initializers: Vec::new(),
+            hooks: Vec::new(),
+
+            runtime,
+            functions,
+            contract_namespace: self.contract_namespace.clone(),
+            namespaced_functions,
+
+            code_type: None,
+            dependency_manager: self.dependency_manager.clone(),
+            dependency_policy: self.dependency_policy,
+            dump_flags: self.dump_flags.clone(),
+
+            evm_data: None,
+            immutables_size: self.immutables_size,
+            immutables: self.immutables.clone(),
+            immutables_layout: self.immutables_layout,
+            aux_heap_layout: self.aux_heap_layout,
+            assembly_routines: self.assembly_routines.clone(),
+            linker_symbols: self.linker_symbols.clone(),
+            factory_dependencies: self.factory_dependencies.clone(),
+            created_at: Instant::now(),
+            dependencies_duration: Duration::ZERO,
+            path_sanitizer: self.path_sanitizer.clone(),
+            memory_store_log: MemoryStoreLog::default(),
+        }
+    }
+
+    ///
+    /// Binds the function declarations to their clones in the forked `module`.
+    ///
+    /// The entry and return blocks are matched by their positions in the function. The functions
+    /// missing in `module` are skipped.
+    ///
+    fn fork_functions(
+        module: &inkwell::module::Module<'ctx>,
+        functions: &HashMap<String, Function<'ctx>>,
+    ) -> HashMap<String, Function<'ctx>> {
+        functions
+            .iter()
+            .filter_map(|(key, function)| {
+                let value = module.get_function(function.value.get_name().to_str().ok()?)?;
+                let blocks = function.value.get_basic_blocks();
+                let cloned_blocks = value.get_basic_blocks();
+                let block = |original: inkwell::basic_block::BasicBlock<'ctx>| {
+                    blocks
+                        .iter()
+                        .position(|block| *block == original)
+                        .and_then(|index| cloned_blocks.get(index).copied())
+                };
+
+                let mut forked = Function::new(
+                    function.name.to_owned(),
+                    value,
+                    block(function.entry_block)?,
+                    block(function.return_block)?,
+                    None,
+                );
+                forked.is_kernel_only = function.is_kernel_only;
+                forked.code_type = function.code_type;
+                Some((key.to_owned(), forked))
+            })
+            .collect()
+    }
+
     ///
     /// Builds the LLVM IR module, returning the build artifacts.
     ///
     pub fn build(self, contract_path: &str) -> anyhow::Result<Build> {
-        if self.dump_flags.contains(&DumpFlag::LLVM) {
+        self.finalize(contract_path, &[])
+            .map(|(build, _module)| build)
+    }
+
+    ///
+    /// Builds the LLVM IR module, returning the build artifacts and the optimized module
+    /// frozen for the read-only post-build inspection.
+    ///
+    /// The `output_formats` are emitted into the build in addition to the assembly and bytecode.
+    ///
+    pub fn finalize(
+        mut self,
+        contract_path: &str,
+        output_formats: &[OutputFormat],
+    ) -> anyhow::Result<(Build, FrozenModule<'ctx>)> {
+        let mut timings = BuildTimings {
+            translation: self
+                .created_at
+                .elapsed()
+                .saturating_sub(self.dependencies_duration),
+            dependencies: self.dependencies_duration,
+            ..BuildTimings::default()
+        };
+
+        let coverage = match self.optimizer_settings().coverage {
+            Some(mode) => self::coverage::instrument(&self, mode),
+            None => Vec::new(),
+        };
+
+        let is_llvm_ir_retained = self.optimizer_settings().is_llvm_ir_retained
+            || output_formats.contains(&OutputFormat::LLVMIR);
+        let mut llvm_ir_unoptimized = None;
+        if self.dump_flags.contains(&DumpFlag::LLVM) || is_llvm_ir_retained {
             let llvm_code = self.module().print_to_string().to_string();
-            eprintln!("Contract `{}` LLVM IR unoptimized:\n", contract_path);
-            println!("{}", llvm_code);
-        }
-        self.verify().map_err(|error| {
-            anyhow::anyhow!(
-                "The contract `{}` unoptimized LLVM IR verification error: {}",
-                contract_path,
-                error
-            )
+            if self.dump_flags.contains(&DumpFlag::LLVM) {
+                Self::dump(contract_path, "LLVM IR unoptimized", llvm_code.as_str());
+            }
+            if is_llvm_ir_retained {
+                llvm_ir_unoptimized = Some(llvm_code);
+            }
+        }
+        let started_at = Instant::now();
+        self.module().verify().map_err(|error| {
+            self.verification_error(contract_path, Some("unoptimized"), error.to_string())
         })?;
+        timings.verification += started_at.elapsed();
+
+        if self.optimizer_settings().is_memory_growth_analysis_enabled {
+            for (function, count) in MemoryGrowth::analyze(self.module()).into_iter() {
+                log::warn!(
+                    target: crate::r#const::LOG_TARGET_ANALYSIS,
+                    "Contract `{}` function `{}`: {} heap access(es) with the offset derived from calldata without a bound check",
+                    contract_path,
+                    function,
+                    count
+                );
+            }
+        }
 
-        let is_optimized = self.optimize();
-        if self.dump_flags.contains(&DumpFlag::LLVM) && is_optimized {
+        self.run_hooks(HookStage::BeforeOptimization, contract_path)?;
+
+        let started_at = Instant::now();
+        let mut is_optimized = false;
+        if self
+            .optimizer_settings()
+            .is_dead_argument_elimination_enabled
+        {
+            is_optimized |= self::dead_arguments::eliminate(&mut self) > 0;
+        }
+        is_optimized |= self.optimize()?;
+        self.check_llvm_errors(contract_path, "optimization")?;
+        if self.optimizer_settings().is_return_forwarding_enabled {
+            is_optimized |= self::peephole::forward_return_data(&self) > 0;
+        }
+        if self.optimizer_settings().is_tail_merging_enabled {
+            is_optimized |= self::tail_merge::merge(&self) > 0;
+        }
+        timings.optimization = started_at.elapsed();
+        let mut llvm_ir_optimized = None;
+        if (self.dump_flags.contains(&DumpFlag::LLVM) && is_optimized) || is_llvm_ir_retained {
             let llvm_code = self.module().print_to_string().to_string();
-            eprintln!("Contract `{}` LLVM IR optimized:\n", contract_path);
-            println!("{}", llvm_code);
-        }
-        self.verify().map_err(|error| {
-            anyhow::anyhow!(
-                "The contract `{}` optimized LLVM IR verification error: {}",
-                contract_path,
-                error
-            )
+            if self.dump_flags.contains(&DumpFlag::LLVM) && is_optimized {
+                Self::dump(contract_path, "LLVM IR optimized", llvm_code.as_str());
+            }
+            if is_llvm_ir_retained {
+                llvm_ir_optimized = Some(llvm_code);
+            }
+        }
+        let started_at = Instant::now();
+        self.module().verify().map_err(|error| {
+            self.verification_error(contract_path, Some("optimized"), error.to_string())
         })?;
+        timings.verification += started_at.elapsed();
+
+        self.run_hooks(HookStage::BeforeAssembling, contract_path)?;
+
+        let started_at = Instant::now();
+        let bitcode = output_formats
+            .contains(&OutputFormat::Bitcode)
+            .then(|| self.module().write_bitcode_to_memory().as_slice().to_vec());
+        let object = if output_formats.contains(&OutputFormat::Object) {
+            let buffer = self
+                .target_machine()
+                .write_to_memory_buffer(self.module(), inkwell::targets::FileType::Object)
+                .map_err(|error| {
+                    anyhow::anyhow!(
+                        "The contract `{}` object file generating error: {}",
+                        contract_path,
+                        error
+                    )
+                })?;
+            Some(buffer.as_slice().to_vec())
+        } else {
+            None
+        };
+        timings.codegen += started_at.elapsed();
 
+        let started_at = Instant::now();
         let buffer = self
             .target_machine()
             .write_to_memory_buffer(self.module(), inkwell::targets::FileType::Assembly)
@@ -187,32 +524,111 @@ where
                     error
                 )
             })?;
-
-        let assembly_text = String::from_utf8_lossy(buffer.as_slice()).to_string();
+        self.check_llvm_errors(contract_path, "code generation")?;
+        timings.codegen += started_at.elapsed();
+
+        let started_at = Instant::now();
+        let mut assembly_text = String::from_utf8_lossy(buffer.as_slice()).to_string();
+        if !self.assembly_routines.is_empty() {
+            let generated_labels = AssemblyRoutine::parse_labels(assembly_text.as_str());
+            for routine in self.assembly_routines.iter() {
+                if let Some(label) = routine.labels().intersection(&generated_labels).next() {
+                    anyhow::bail!(
+                        "The contract `{}` assembly routine `{}` label `{}` collides with the generated code",
+                        contract_path,
+                        routine.name,
+                        label
+                    );
+                }
+                assembly_text.push('\n');
+                assembly_text.push_str(routine.text.as_str());
+            }
+        }
         if self.dump_flags.contains(&DumpFlag::Assembly) {
-            eprintln!("Contract `{}` assembly:\n", contract_path);
-            println!("{}", assembly_text);
+            Self::dump(contract_path, "assembly", assembly_text.as_str());
         }
+        timings.assembling = started_at.elapsed();
 
+        let started_at = Instant::now();
         let assembly =
             zkevm_assembly::Assembly::try_from(assembly_text.clone()).map_err(|error| {
-                anyhow::anyhow!(
-                    "The contract `{}` assembly parsing error: {}",
-                    contract_path,
-                    error
-                )
+                Error::AssemblyParsing {
+                    contract_path: contract_path.to_owned(),
+                    message: error.to_string(),
+                    assembly: assembly_text.clone(),
+                }
             })?;
 
         let bytecode_words = assembly.clone().compile_to_bytecode()?;
+        timings.assembling += started_at.elapsed();
+
+        let started_at = Instant::now();
         let hash = zkevm_opcode_defs::utils::bytecode_to_code_hash(bytecode_words.as_slice())
-            .map(hex::encode)
             .map_err(|_error| {
                 anyhow::anyhow!("The contract `{}` bytecode hashing error", contract_path,)
             })?;
+        timings.hashing = started_at.elapsed();
+
+        let stack_sizes = Build::parse_stack_sizes(assembly_text.as_str());
+        let statistics = BuildStatistics::parse(assembly_text.as_str());
+
+        let mut build = Build::new(assembly_text, assembly, bytecode_words, hash);
+        build.stack_sizes = stack_sizes;
+        build.statistics = statistics;
+        build.coverage = coverage;
+        build.timings = timings;
+        build.llvm_ir_unoptimized = llvm_ir_unoptimized;
+        build.llvm_ir_optimized = llvm_ir_optimized;
+        build.bitcode = bitcode;
+        build.object = object;
+        if self.optimizer_settings().is_evm_stub_enabled {
+            build.evm_stub = Some(Build::evm_stub(hash));
+        }
+        build.linker_symbols = self.linker_symbols;
+        build.factory_dependencies = self.factory_dependencies;
+        if let Some(remarks) = self.remarks.as_ref() {
+            build.remarks = remarks.take();
+        }
+        Ok((build, FrozenModule::new(self.module)))
+    }
+
+    ///
+    /// Returns an error if LLVM has reported errors at `stage` via the diagnostic handler.
+    ///
+    /// LLVM only aborts the compilation on errors with its default handler, so the errors
+    /// collected by the custom one must be checked explicitly.
+    ///
+    fn check_llvm_errors(&self, contract_path: &str, stage: &str) -> anyhow::Result<()> {
+        let errors = match self.remarks.as_ref() {
+            Some(remarks) => remarks.take_errors(),
+            None => return Ok(()),
+        };
+        if errors.is_empty() {
+            return Ok(());
+        }
 
-        let bytecode = bytecode_words.into_iter().flatten().collect();
+        anyhow::bail!(
+            "The contract `{}` LLVM {} error: {}",
+            contract_path,
+            stage,
+            errors.join("\n")
+        )
+    }
 
-        Ok(Build::new(assembly_text, assembly, bytecode, hash))
+    ///
+    /// Emits an intermediate representation dump via the `log` facade.
+    ///
+    /// Each dump is emitted as a single record tagged with the contract path and the compilation
+    /// stage, so the output of multi-threaded front-ends is not interleaved.
+    ///
+    fn dump(contract_path: &str, stage: &str, code: &str) {
+        log::info!(
+            target: crate::r#const::LOG_TARGET_DUMP,
+            "Contract `{}` {}:\n\n{}",
+            contract_path,
+            stage,
+            code
+        );
     }
 
     ///
@@ -229,6 +645,52 @@ where
         &self.module
     }
 
+    ///
+    /// Returns the static value or block `name`, or an empty string if the names are discarded.
+    ///
+    pub fn name<'a>(&self, name: &'a str) -> &'a str {
+        self.names.get(name)
+    }
+
+    ///
+    /// Returns the interned `{prefix}_{index}` value or block name.
+    ///
+    pub fn name_indexed(&self, prefix: &'static str, index: usize) -> Rc<str> {
+        self.names.indexed(prefix, index)
+    }
+
+    ///
+    /// Returns the next module-wide unique identifier, e.g. for the front-end labels.
+    ///
+    pub fn next_id(&self) -> usize {
+        self.names.next_id()
+    }
+
+    ///
+    /// Returns the `{prefix}_{id}` value or block name with the next unique identifier.
+    ///
+    /// Unlike the names left to LLVM to uniquify, the identifiers do not depend on the names
+    /// already present in the module.
+    ///
+    pub fn unique_name(&self, prefix: &'static str) -> Rc<str> {
+        self.names.unique(prefix)
+    }
+
+    ///
+    /// Returns the interned `{prefix}_{suffix}` value or block name.
+    ///
+    pub fn name_suffixed(&self, prefix: &'static str, suffix: &str) -> Rc<str> {
+        self.names.suffixed(prefix, suffix)
+    }
+
+    ///
+    /// Sets whether the value and block names are replaced with empty strings, and discarded
+    /// by the LLVM context.
+    ///
+    pub fn set_names_discarded(&mut self, is_discarded: bool) {
+        self.names.set_discarded(is_discarded);
+    }
+
     ///
     /// Returns the LLVM target machine reference.
     ///
@@ -236,18 +698,126 @@ where
         self.optimizer.target_machine()
     }
 
+    ///
+    /// Registers the hand-written assembly `routine`, which is appended to the generated assembly
+    /// before it is parsed.
+    ///
+    /// The collisions between the routines are checked here, and the collisions with the
+    /// generated code are checked when the contract is built.
+    ///
+    pub fn add_assembly_routine(&mut self, routine: AssemblyRoutine) -> anyhow::Result<()> {
+        let labels = routine.labels();
+        if !labels.contains(routine.name.as_str()) {
+            return Err(self.error(
+                "assembly routine",
+                format!("`{}` does not define its entry label", routine.name),
+            ));
+        }
+        for existing in self.assembly_routines.iter() {
+            if let Some(label) = existing.labels().intersection(&labels).next() {
+                return Err(self.error(
+                    "assembly routine",
+                    format!(
+                        "`{}` label `{}` collides with the routine `{}`",
+                        routine.name, label, existing.name
+                    ),
+                ));
+            }
+        }
+
+        self.assembly_routines.push(routine);
+        Ok(())
+    }
+
+    ///
+    /// Returns the optimizer settings reference.
+    ///
+    pub fn optimizer_settings(&self) -> &OptimizerSettings {
+        self.optimizer.settings()
+    }
+
     ///
     /// Sets the current code type (deploy or runtime).
     ///
+    /// The code type is also assigned to the current function, if any.
+    ///
     pub fn set_code_type(&mut self, code_type: CodeType) {
         self.code_type = Some(code_type);
+        if let Some(name) = self.function.as_ref().map(|function| function.name.clone()) {
+            self.set_function_code_type(name.as_str(), code_type);
+        }
+    }
+
+    ///
+    /// Sets the code type of the declared function `name`, so it does not depend on the order
+    /// the deploy and runtime code are translated in.
+    ///
+    pub fn set_function_code_type(&mut self, name: &str, code_type: CodeType) {
+        if let Some(function) = self.functions.get_mut(name) {
+            function.code_type = Some(code_type);
+        }
+        if let Some(function) = self.function.as_mut() {
+            if function.name == name {
+                function.code_type = Some(code_type);
+            }
+        }
     }
 
     ///
     /// Returns the current code type (deploy or runtime).
     ///
+    /// The code type of the current function takes precedence over the context one.
+    ///
     pub fn code_type(&self) -> CodeType {
-        self.code_type.expect("Always exists")
+        self.current_code_type().expect("Always exists")
+    }
+
+    ///
+    /// Returns the current code type (deploy or runtime), or an error if it has not been set.
+    ///
+    pub fn try_code_type(&self) -> anyhow::Result<CodeType> {
+        self.current_code_type().ok_or_else(|| {
+            self.error(
+                "code type",
+                "is only available inside the deploy or runtime code",
+            )
+        })
+    }
+
+    ///
+    /// Returns the code type of the current function, falling back to the context one.
+    ///
+    fn current_code_type(&self) -> Option<CodeType> {
+        self.function
+            .as_ref()
+            .and_then(|function| function.code_type)
+            .or(self.code_type)
+    }
+
+    ///
+    /// Creates a translation error naming the contract and the malformed `construct`.
+    ///
+    pub fn error<M>(&self, construct: &str, message: M) -> anyhow::Error
+    where
+        M: std::fmt::Display,
+    {
+        anyhow::anyhow!(
+            "Contract `{}` {}: {}",
+            self.contract_path(),
+            construct,
+            message
+        )
+    }
+
+    ///
+    /// Returns the original contract path the module name has been sanitized from.
+    ///
+    pub fn contract_path(&self) -> String {
+        let name = self.module.get_name().to_string_lossy();
+        self.path_sanitizer
+            .original(name.as_ref())
+            .map(str::to_owned)
+            .unwrap_or_else(|| name.into_owned())
     }
 
     ///
@@ -264,7 +834,7 @@ where
     ///
     /// Only returns `true` if any of the passes modified the function.
     ///
-    pub fn optimize(&self) -> bool {
+    pub fn optimize(&self) -> anyhow::Result<bool> {
         let mut is_optimized = false;
 
         let mut functions = Vec::new();
@@ -275,755 +845,2765 @@ where
                 current = function;
             }
         }
+        let mut skipped = Vec::new();
         for function in functions.into_iter() {
-            if function.get_name().to_string_lossy().starts_with("llvm.")
-                || (function.get_name().to_string_lossy().starts_with("__")
-                    && function.get_name().to_string_lossy() != Runtime::FUNCTION_ENTRY
-                    && function.get_name().to_string_lossy() != Runtime::FUNCTION_DEPLOY_CODE
-                    && function.get_name().to_string_lossy() != Runtime::FUNCTION_RUNTIME_CODE)
-            {
+            if self.runtime.is_optimizer_skipped(function) {
+                skipped.push(function);
                 continue;
             }
 
             is_optimized |= self.optimizer.run_on_function(function);
         }
-        is_optimized |= self.optimizer.run_on_module(self.module());
+        is_optimized |= self
+            .optimizer
+            .run_on_module(self.module(), skipped.as_slice())?;
+
+        Ok(is_optimized)
+    }
+
+    ///
+    /// Parses the textual LLVM IR `text` into a new module of the inner LLVM context.
+    ///
+    /// The module is not linked into the current one, and is mostly useful for asserting on
+    /// the IR round-trip in tests.
+    ///
+    pub fn parse_llvm_ir(&self, text: &str) -> anyhow::Result<inkwell::module::Module<'ctx>> {
+        let buffer = inkwell::memory_buffer::MemoryBuffer::create_from_memory_range_copy(
+            text.as_bytes(),
+            self.module.get_name().to_string_lossy().as_ref(),
+        );
+        self.llvm
+            .create_module_from_ir(buffer)
+            .map_err(|error| anyhow::anyhow!("LLVM IR parsing error: {}", error.to_string()))
+    }
 
-        is_optimized
+    ///
+    /// Prints the textual LLVM IR of the function `name` from the current module.
+    ///
+    /// The name is looked up as is first, and then in the current contract namespace.
+    ///
+    pub fn print_function(&self, name: &str) -> anyhow::Result<String> {
+        self.module
+            .get_function(name)
+            .or_else(|| {
+                self.module
+                    .get_function(self.namespaced_name(name).as_str())
+            })
+            .map(|function| function.print_to_string().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` does not exist", name))
     }
 
     ///
     /// Verifies the current LLVM IR module.
     ///
     pub fn verify(&self) -> anyhow::Result<()> {
-        self.module()
-            .verify()
-            .map_err(|error| anyhow::anyhow!(error.to_string()))
+        self.module().verify().map_err(|error| {
+            self.verification_error(self.contract_path().as_str(), None, error.to_string())
+                .into()
+        })
     }
 
     ///
-    /// Compiles a contract dependency, if the dependency manager is set.
+    /// Creates the verification error of the contract `contract_path` at the pipeline `stage`.
     ///
-    pub fn compile_dependency(&mut self, name: &str) -> anyhow::Result<String> {
-        self.dependency_manager
-            .to_owned()
-            .ok_or_else(|| anyhow::anyhow!("The dependency manager is unset"))
-            .and_then(|manager| {
-                Dependency::compile(
-                    manager,
-                    name,
-                    self.optimizer.settings().to_owned(),
-                    self.dump_flags.clone(),
-                )
-            })
+    /// The first function failing the verification is looked up to be reported, and the module
+    /// is attached as LLVM IR if it is dumped or retained in the build.
+    ///
+    fn verification_error(
+        &self,
+        contract_path: &str,
+        stage: Option<&'static str>,
+        message: String,
+    ) -> Error {
+        let function = self
+            .module()
+            .get_functions()
+            .find(|function| !function.verify(false))
+            .map(|function| function.get_name().to_string_lossy().into_owned());
+        let llvm_ir = (self.dump_flags.contains(&DumpFlag::LLVM)
+            || self.optimizer_settings().is_llvm_ir_retained)
+            .then(|| self.module().print_to_string().to_string());
+
+        Error::Verification {
+            contract_path: contract_path.to_owned(),
+            stage,
+            function,
+            message,
+            llvm_ir,
+        }
     }
 
     ///
-    /// Gets a full contract_path from the dependency manager.
+    /// Creates the missing `dependency` error of the current contract.
     ///
-    pub fn resolve_path(&self, identifier: &str) -> anyhow::Result<String> {
-        self.dependency_manager
-            .to_owned()
-            .ok_or_else(|| anyhow::anyhow!("The dependency manager is unset"))
-            .and_then(|manager| {
-                let full_path = manager.read().expect("Sync").resolve_path(identifier)?;
-                Ok(full_path)
-            })
+    fn missing_dependency_error<M>(&self, dependency: &str, message: M) -> Error
+    where
+        M: std::fmt::Display,
+    {
+        Error::MissingDependency {
+            contract_path: self.contract_path(),
+            dependency: dependency.to_owned(),
+            message: message.to_string(),
+        }
     }
 
     ///
-    /// Gets a deployed library address from the dependency manager.
+    /// Sets the dependency resolution policy if the dependency manager is unset.
     ///
-    pub fn resolve_library(&self, path: &str) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
-        self.dependency_manager
-            .to_owned()
-            .ok_or_else(|| anyhow::anyhow!("The dependency manager is unset"))
-            .map(
-                |manager| match manager.read().expect("Sync").resolve_library(path) {
-                    Ok(address) => self.field_const_str(address.as_str()),
-                    Err(_error) => self.field_const(0),
-                },
-            )
+    pub fn set_dependency_policy(&mut self, policy: DependencyPolicy) {
+        self.dependency_policy = policy;
     }
 
     ///
-    /// Appends a function to the current module.
+    /// Returns the dependency resolution policy if the dependency manager is unset.
     ///
-    /// The attributes only affect the LLVM optimizations.
+    pub fn dependency_policy(&self) -> DependencyPolicy {
+        self.dependency_policy
+    }
+
     ///
-    /// TODO: look into the `alwaysinline` attributes once the inlining problems have been
-    /// investigated and resolved in the LLVM framework.
+    /// Compiles a contract dependency, if the dependency manager is set.
     ///
-    pub fn add_function(
+    /// The dependency is recorded as a factory dependency of the current contract at `path`,
+    /// together with its own factory dependencies, since all of them must be known to
+    /// the deployer. The paths colliding after the sanitization with the contract or other
+    /// dependency paths are rejected, since their module names would be indistinguishable.
+    ///
+    /// If the manager is unset, the zero-filled artifact is returned with the `ZeroFill` policy,
+    /// and it is not recorded as a factory dependency.
+    ///
+    pub fn compile_dependency(
         &mut self,
         name: &str,
-        r#type: inkwell::types::FunctionType<'ctx>,
-        mut linkage: Option<inkwell::module::Linkage>,
-    ) {
-        if name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
-            || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER
-        {
-            linkage = Some(inkwell::module::Linkage::External);
-        }
-
-        let value = self.module().add_function(name, r#type, linkage);
+        path: &str,
+    ) -> anyhow::Result<DependencyArtifact> {
+        self.path_sanitizer.sanitize(path)?;
+
+        let manager = match (self.dependency_manager.to_owned(), self.dependency_policy) {
+            (Some(manager), _) => manager,
+            (None, DependencyPolicy::ZeroFill) => {
+                return Ok(DependencyArtifact::new(
+                    "0".repeat(compiler_common::SIZE_FIELD * 2),
+                    0,
+                    BTreeMap::new(),
+                ))
+            }
+            (None, DependencyPolicy::Defer) => {
+                return Err(self
+                    .missing_dependency_error(
+                        name,
+                        "cannot be deferred, as its hash must be known at compile time",
+                    )
+                    .into())
+            }
+            (None, DependencyPolicy::Require) => {
+                return Err(self
+                    .missing_dependency_error(name, "the dependency manager is unset")
+                    .into())
+            }
+        };
 
-        if name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
-            || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER
-        {
-            value.add_attribute(
-                inkwell::attributes::AttributeLoc::Function,
-                self.llvm
-                    .create_enum_attribute(Attribute::NoInline as u32, 0),
-            );
-        } else if self.optimizer.settings().level_middle_end_size == SizeLevel::Z
-            && self.optimizer.settings().is_inliner_enabled
-        {
-            // value.add_attribute(
-            //     inkwell::attributes::AttributeLoc::Function,
-            //     self.llvm
-            //         .create_enum_attribute(Attribute::AlwaysInline as u32, 0),
-            // );
-        }
-        if self.optimizer.settings().level_middle_end_size == SizeLevel::Z {
-            value.add_attribute(
-                inkwell::attributes::AttributeLoc::Function,
-                self.llvm
-                    .create_enum_attribute(Attribute::MinSize as u32, 0),
-            );
-        }
-        value.add_attribute(
-            inkwell::attributes::AttributeLoc::Function,
-            self.llvm.create_enum_attribute(Attribute::NoFree as u32, 0),
-        );
-        value.add_attribute(
-            inkwell::attributes::AttributeLoc::Function,
-            self.llvm.create_enum_attribute(Attribute::Cold as u32, 0),
+        let started_at = Instant::now();
+        let artifact = Dependency::compile(
+            manager,
+            name,
+            self.optimizer.settings().to_owned(),
+            self.dump_flags.clone(),
         );
-        value.add_attribute(
-            inkwell::attributes::AttributeLoc::Function,
-            self.llvm
-                .create_enum_attribute(Attribute::NullPointerIsValid as u32, 0),
+        self.dependencies_duration += started_at.elapsed();
+        let artifact = artifact?;
+
+        self.factory_dependencies
+            .insert(artifact.hash.clone(), path.to_owned());
+        self.factory_dependencies.extend(
+            artifact
+                .factory_dependencies
+                .iter()
+                .map(|(hash, path)| (hash.to_owned(), path.to_owned())),
         );
-
-        value.set_personality_function(self.runtime.personality);
-
-        let entry_block = self.llvm.append_basic_block(value, "entry");
-        let return_block = self.llvm.append_basic_block(value, "return");
-
-        let function = Function::new(name.to_owned(), value, entry_block, return_block, None);
-        self.functions.insert(name.to_string(), function.clone());
+        Ok(artifact)
     }
 
     ///
-    /// Appends a function to the current module.
+    /// Returns the hash-to-path mapping of the contract factory dependencies compiled so far.
     ///
-    pub fn add_function_evm(
-        &mut self,
-        name: &str,
-        r#type: inkwell::types::FunctionType<'ctx>,
-        linkage: Option<inkwell::module::Linkage>,
-        evm_data: FunctionEVMData<'ctx>,
-    ) {
-        self.add_function(name, r#type, linkage);
-        self.functions
-            .get_mut(name)
-            .expect("Always exists")
-            .evm_data = Some(evm_data);
+    pub fn factory_dependencies(&self) -> &BTreeMap<String, String> {
+        &self.factory_dependencies
     }
 
     ///
-    /// Returns the current function.
-    ///
-    pub fn function(&self) -> &Function<'ctx> {
-        self.function.as_ref().expect("Must be declared before use")
-    }
-
+    /// Gets a full contract_path from the dependency manager.
     ///
-    /// Returns the current function as a mutable reference.
+    /// If the manager is unset, the identifier is returned as is, unless the policy is `Require`.
     ///
-    pub fn function_mut(&mut self) -> &mut Function<'ctx> {
-        self.function.as_mut().expect("Must be declared before use")
+    pub fn resolve_path(&self, identifier: &str) -> anyhow::Result<String> {
+        match (self.dependency_manager.as_ref(), self.dependency_policy) {
+            (Some(manager), _) => manager
+                .read()
+                .expect("Sync")
+                .resolve_path(identifier)
+                .map_err(|error| self.missing_dependency_error(identifier, error).into()),
+            (None, DependencyPolicy::Require) => Err(self
+                .missing_dependency_error(identifier, "the dependency manager is unset")
+                .into()),
+            (None, DependencyPolicy::Defer | DependencyPolicy::ZeroFill) => {
+                Ok(identifier.to_owned())
+            }
+        }
     }
 
     ///
-    /// Sets the current function.
+    /// Gets a deployed library address from the dependency manager.
     ///
-    pub fn set_function(&mut self, function: Function<'ctx>) {
-        self.function = Some(function);
+    /// The library unknown to the manager, or any library if the manager is unset, is left to
+    /// the deferred linking with the `Defer` policy, and is zero-filled otherwise. Without
+    /// the manager, the `Require` policy is an error.
+    ///
+    pub fn resolve_library(
+        &mut self,
+        path: &str,
+    ) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
+        let address = match self.dependency_manager.as_ref() {
+            Some(manager) => manager.read().expect("Sync").resolve_library(path).ok(),
+            None if self.dependency_policy == DependencyPolicy::Require => {
+                return Err(self
+                    .missing_dependency_error(path, "the dependency manager is unset")
+                    .into())
+            }
+            None => None,
+        };
+
+        match (address, self.dependency_policy) {
+            (Some(address), _) => self.try_field_const_str(address.as_str()),
+            (None, DependencyPolicy::Defer) => Ok(self.resolve_library_deferred(path)),
+            (None, DependencyPolicy::Require | DependencyPolicy::ZeroFill) => {
+                Ok(self.field_const(0))
+            }
+        }
     }
 
     ///
-    /// Sets the return entity for the current function.
+    /// Returns the address of the library `path` to be linked after the compilation.
     ///
-    pub fn set_function_return(&mut self, r#return: FunctionReturn<'ctx>) {
-        let name = self.function().name.clone();
+    /// The address is loaded from a named external global declaration, which becomes an undefined
+    /// symbol in the assembly. The symbol is recorded in the build, so the library can be linked
+    /// at the assembly stage like with `solc`.
+    ///
+    pub fn resolve_library_deferred(&mut self, path: &str) -> inkwell::values::IntValue<'ctx> {
+        let name = format!(
+            "{}{}",
+            crate::r#const::GLOBAL_LINKER_SYMBOL_PREFIX,
+            crate::hashes::keccak256(path.as_bytes())
+        );
+        let global = match self.module.get_global(name.as_str()) {
+            Some(global) => global,
+            None => {
+                let global = self.module.add_global(
+                    self.field_type(),
+                    Some(AddressSpace::Stack.into()),
+                    name.as_str(),
+                );
+                global.set_linkage(inkwell::module::Linkage::External);
+                global
+            }
+        };
+        self.linker_symbols.insert(name, path.to_owned());
 
-        self.functions
-            .get_mut(name.as_str())
-            .expect("Always exists")
-            .set_return(r#return.clone());
-        self.function_mut().set_return(r#return);
+        self.build_load(global.as_pointer_value(), "linker_symbol_address")
+            .into_int_value()
     }
 
     ///
-    /// Returns the specified LLVM intrinsic function.
+    /// Enters the namespace of the contract `name`.
     ///
-    pub fn get_intrinsic_function(
-        &self,
-        function: IntrinsicFunction,
-    ) -> inkwell::values::FunctionValue<'ctx> {
-        let intrinsic = inkwell::intrinsics::Intrinsic::find(function.name())
-            .unwrap_or_else(|| panic!("Intrinsic function `{}` does not exist", function.name()));
-        intrinsic
-            .get_declaration(self.module(), function.argument_types(self).as_slice())
-            .unwrap_or_else(|| panic!("Intrinsic function `{}` declaration error", function.name()))
+    /// Is used to compile several contracts deployed together into the same module. The functions
+    /// and global variables declared afterwards are prefixed with the contract name, except for
+    /// the ones recognized by the back-end by name. The visibility is controlled by the linkage
+    /// passed to `add_function`. If the namespace has been entered before, its declared
+    /// functions are restored.
+    ///
+    pub fn enter_contract(&mut self, name: &str) {
+        self.leave_contract();
+
+        self.functions = self
+            .namespaced_functions
+            .remove(name)
+            .unwrap_or_else(|| HashMap::with_capacity(Self::FUNCTION_HASHMAP_INITIAL_CAPACITY));
+        self.contract_namespace = Some(name.to_owned());
     }
 
     ///
-    /// Appends a new basic block to the current function.
+    /// Leaves the current contract namespace, if any, saving its declared functions.
     ///
-    pub fn append_basic_block(&self, name: &str) -> inkwell::basic_block::BasicBlock<'ctx> {
-        self.llvm.append_basic_block(self.function().value, name)
+    pub fn leave_contract(&mut self) {
+        if let Some(namespace) = self.contract_namespace.take() {
+            let functions = std::mem::replace(
+                &mut self.functions,
+                HashMap::with_capacity(Self::FUNCTION_HASHMAP_INITIAL_CAPACITY),
+            );
+            self.namespaced_functions.insert(namespace, functions);
+        }
     }
 
     ///
-    /// Sets the current basic block.
+    /// Returns the current contract namespace, if any.
     ///
-    pub fn set_basic_block(&self, block: inkwell::basic_block::BasicBlock<'ctx>) {
-        self.builder.position_at_end(block);
+    pub fn contract_namespace(&self) -> Option<&str> {
+        self.contract_namespace.as_deref()
     }
 
     ///
-    /// Returns the current basic block.
-    ///
-    pub fn basic_block(&self) -> inkwell::basic_block::BasicBlock<'ctx> {
-        self.builder.get_insert_block().expect("Always exists")
+    /// Returns the LLVM module-level name of a function or global variable `name`, prefixed with
+    /// the current contract namespace, if any.
+    ///
+    /// The entry function and the near call ABI functions are recognized by the back-end by
+    /// name, so they are never prefixed.
+    ///
+    pub fn namespaced_name(&self, name: &str) -> String {
+        let is_back_end_name = name == Runtime::FUNCTION_ENTRY
+            || name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
+            || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER;
+        match self.contract_namespace.as_ref() {
+            Some(namespace) if !is_back_end_name => format!(
+                "{}{}{}",
+                namespace,
+                Self::CONTRACT_NAMESPACE_SEPARATOR,
+                name
+            ),
+            _ => name.to_owned(),
+        }
     }
 
     ///
-    /// Returns the value of a global variable.
+    /// Registers a module initialization snippet, e.g. pre-computing a hashed constant into
+    /// a global variable.
     ///
-    pub fn get_global(&self, name: &str) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>> {
-        let global_pointer = self.get_global_ptr(name)?;
-        let global_value =
-            self.build_load(global_pointer, format!("global_value_{}", name).as_str());
-        Ok(global_value)
+    /// The snippets are emitted at the beginning of the entry function in the registration
+    /// order, after the global variables are initialized and before the deploy or runtime code
+    /// is called.
+    ///
+    pub fn add_global_initializer<F>(&mut self, initializer: F)
+    where
+        F: FnOnce(&mut Self) -> anyhow::Result<()> + 'ctx,
+    {
+        self.global_initializers.push(Box::new(initializer));
     }
 
     ///
-    /// Returns the pointer to a global variable.
+    /// Emits the registered module initialization snippets at the current position.
     ///
-    pub fn get_global_ptr(
-        &self,
-        name: &str,
-    ) -> anyhow::Result<inkwell::values::PointerValue<'ctx>> {
-        match self.module.get_global(name) {
-            Some(global) => Ok(global.as_pointer_value()),
-            None => anyhow::bail!("Global variable {} is not declared", name),
+    pub fn build_global_initializers(&mut self) -> anyhow::Result<()> {
+        for initializer in std::mem::take(&mut self.global_initializers).into_iter() {
+            initializer(self)?;
         }
+        Ok(())
     }
 
     ///
-    /// Sets the value to a global variable.
+    /// Registers a hook invoked at `stage` of the translation pipeline, e.g. a linter or
+    /// a metric collector.
     ///
-    pub fn set_global<V: BasicValue<'ctx>>(&self, name: &str, value: V) {
-        let pointer = match self.module.get_global(name) {
-            Some(global) => global.as_pointer_value(),
-            None => {
-                let r#type = value.as_basic_value_enum().get_type();
-                let global = self
-                    .module
-                    .add_global(r#type, Some(AddressSpace::Stack.into()), name);
-                global.set_linkage(inkwell::module::Linkage::Private);
-                global.set_visibility(inkwell::GlobalVisibility::Default);
-                global.set_externally_initialized(false);
-
-                global.set_initializer(&r#type.const_zero());
-                global.as_pointer_value()
-            }
-        };
-        self.build_store(pointer, value);
+    /// The hooks of the same stage are invoked in the registration order, and an error returned
+    /// by a hook aborts the pipeline. The hooks are not carried over to the forks.
+    ///
+    pub fn add_hook<F>(&mut self, stage: HookStage, hook: F)
+    where
+        F: FnMut(&mut Self, &str) -> anyhow::Result<()> + 'ctx,
+    {
+        self.hooks.push((stage, Box::new(hook)));
     }
 
     ///
-    /// Pushes a new loop context to the stack.
+    /// Translates `entity` into LLVM IR, invoking the translation hooks around it.
     ///
-    pub fn push_loop(
-        &mut self,
-        body_block: inkwell::basic_block::BasicBlock<'ctx>,
-        continue_block: inkwell::basic_block::BasicBlock<'ctx>,
-        join_block: inkwell::basic_block::BasicBlock<'ctx>,
-    ) {
-        self.loop_stack
-            .push(Loop::new(body_block, continue_block, join_block));
+    /// The translation hooks are only invoked for the entities passed here, that is, around
+    /// the deploy and runtime code bodies translated by `DeployCode` and `RuntimeCode`, and
+    /// the entities passed by the front-end explicitly. The entities translated directly with
+    /// `WriteLLVM::into_llvm` do not invoke the hooks. The hooks are detached from the context
+    /// while they are running, so they are not invoked for the entities translated by a hook.
+    ///
+    pub fn write_llvm<T>(&mut self, entity: T) -> anyhow::Result<()>
+    where
+        T: WriteLLVM<D>,
+    {
+        let subject = std::any::type_name::<T>();
+        self.run_hooks(HookStage::BeforeTranslation, subject)?;
+        entity.into_llvm(self)?;
+        self.run_hooks(HookStage::AfterTranslation, subject)
     }
 
     ///
-    /// Pops the current loop context from the stack.
+    /// Translates `entities` into LLVM IR in two phases.
     ///
-    pub fn pop_loop(&mut self) {
-        self.loop_stack.pop();
+    /// All the entities are declared first, so they can reference each other regardless of
+    /// the order, and then translated in the original order with `write_llvm`.
+    ///
+    pub fn translate<T, I>(&mut self, entities: I) -> anyhow::Result<()>
+    where
+        T: WriteLLVM<D>,
+        I: IntoIterator<Item = T>,
+    {
+        let mut entities: Vec<T> = entities.into_iter().collect();
+        for entity in entities.iter_mut() {
+            entity.declare(self)?;
+        }
+        for entity in entities.into_iter() {
+            self.write_llvm(entity)?;
+        }
+        Ok(())
     }
 
     ///
-    /// Returns the current loop context.
+    /// Invokes the hooks registered for `stage` with `subject`.
     ///
-    pub fn r#loop(&self) -> &Loop<'ctx> {
-        self.loop_stack
-            .last()
-            .expect("The current context is not in a loop")
+    /// The hooks registered by other hooks are appended after the existing ones. The hooks are
+    /// detached while running, so the stages reached from within a hook do not invoke them.
+    ///
+    fn run_hooks(&mut self, stage: HookStage, subject: &str) -> anyhow::Result<()> {
+        let mut hooks = std::mem::take(&mut self.hooks);
+        let result = hooks
+            .iter_mut()
+            .filter(|(hook_stage, _hook)| *hook_stage == stage)
+            .try_for_each(|(_hook_stage, hook)| hook(self, subject));
+        hooks.append(&mut self.hooks);
+        self.hooks = hooks;
+        result
     }
 
     ///
-    /// Builds a stack allocation instruction.
+    /// Declares a custom runtime function, following the `__` runtime function naming rules.
     ///
-    /// Sets the alignment to 256 bits.
+    /// Allows front-ends to ship their own helpers implemented in LLVM IR or linked from elsewhere.
+    /// The helpers with bodies are not skipped by the optimizer, unlike the built-in ones.
     ///
-    pub fn build_alloca<T: BasicType<'ctx>>(
-        &self,
-        r#type: T,
+    pub fn declare_runtime_function(
+        &mut self,
         name: &str,
-    ) -> inkwell::values::PointerValue<'ctx> {
-        let pointer = self.builder.build_alloca(r#type, name);
-        self.basic_block()
-            .get_last_instruction()
-            .expect("Always exists")
-            .set_alignment(compiler_common::SIZE_FIELD as u32)
-            .expect("Alignment is valid");
-        pointer
+        r#type: inkwell::types::FunctionType<'ctx>,
+        attributes: &[Attribute],
+    ) -> anyhow::Result<inkwell::values::FunctionValue<'ctx>> {
+        self.runtime
+            .declare_custom(self.llvm, &self.module, name, r#type, attributes)
     }
 
     ///
-    /// Builds a stack store instruction.
-    ///
-    /// Sets the alignment to 256 bits for the stack and 1 bit for the heap, parent, and child.
+    /// Appends a function to the current module.
     ///
-    pub fn build_store<V: BasicValue<'ctx>>(
-        &self,
-        pointer: inkwell::values::PointerValue<'ctx>,
-        value: V,
-    ) {
-        let instruction = self.builder.build_store(pointer, value);
-
-        let alignment = if inkwell::AddressSpace::from(AddressSpace::Stack)
-            == pointer.get_type().get_address_space()
-        {
-            compiler_common::SIZE_FIELD
-        } else {
-            1
-        };
-
-        instruction
-            .set_alignment(alignment as u32)
-            .expect("Alignment is valid");
-    }
-
+    /// The attributes only affect the LLVM optimizations.
     ///
-    /// Builds a stack load instruction.
+    /// The weak `linkage` makes the function a default implementation, which is replaced with
+    /// a strong definition of the same name when the modules are linked. Such functions are
+    /// never inlined, since their bodies may be replaced.
     ///
-    /// Sets the alignment to 256 bits for the stack and 1 bit for the heap, parent, and child.
+    /// TODO: look into the `alwaysinline` attributes once the inlining problems have been
+    /// investigated and resolved in the LLVM framework.
     ///
-    pub fn build_load(
-        &self,
-        pointer: inkwell::values::PointerValue<'ctx>,
+    pub fn add_function(
+        &mut self,
         name: &str,
-    ) -> inkwell::values::BasicValueEnum<'ctx> {
-        let value = self.builder.build_load(pointer, name);
+        r#type: inkwell::types::FunctionType<'ctx>,
+        mut linkage: Option<inkwell::module::Linkage>,
+    ) {
+        if name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
+            || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER
+        {
+            linkage = Some(inkwell::module::Linkage::External);
+        }
 
-        let alignment = if inkwell::AddressSpace::from(AddressSpace::Stack)
-            == pointer.get_type().get_address_space()
+        let value =
+            self.module()
+                .add_function(self.namespaced_name(name).as_str(), r#type, linkage);
+
+        let is_weak = linkage == Some(inkwell::module::Linkage::WeakAny);
+        if name.starts_with(Function::ZKSYNC_NEAR_CALL_ABI_PREFIX)
+            || name == Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER
+            || is_weak
         {
-            compiler_common::SIZE_FIELD
-        } else {
-            1
-        };
+            value.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                self.llvm
+                    .create_enum_attribute(Attribute::NoInline as u32, 0),
+            );
+        } else if self.optimizer.settings().level_middle_end_size == SizeLevel::Z
+            && self.optimizer.settings().is_inliner_enabled
+        {
+            // value.add_attribute(
+            //     inkwell::attributes::AttributeLoc::Function,
+            //     self.llvm
+            //         .create_enum_attribute(Attribute::AlwaysInline as u32, 0),
+            // );
+        }
+        if self.optimizer.settings().level_middle_end_size == SizeLevel::Z {
+            value.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                self.llvm
+                    .create_enum_attribute(Attribute::MinSize as u32, 0),
+            );
+        }
+        for attribute in self.optimizer.settings().function_attributes.iter() {
+            value.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                self.llvm.create_enum_attribute(*attribute as u32, 0),
+            );
+        }
+        let is_always_inline = self
+            .optimizer
+            .settings()
+            .function_attributes
+            .contains(&Attribute::AlwaysInline);
+        for attribute in self
+            .optimizer
+            .settings()
+            .register_pressure
+            .enum_attributes()
+            .iter()
+            .filter(|attribute| !(is_always_inline && **attribute == Attribute::NoInline))
+        {
+            value.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                self.llvm.create_enum_attribute(*attribute as u32, 0),
+            );
+        }
+        for (key, value_string) in self
+            .optimizer
+            .settings()
+            .register_pressure
+            .string_attributes()
+            .iter()
+            .copied()
+            .chain(
+                self.optimizer
+                    .settings()
+                    .function_string_attributes
+                    .iter()
+                    .map(|(key, value_string)| (key.as_str(), value_string.as_str())),
+            )
+        {
+            value.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                self.llvm.create_string_attribute(key, value_string),
+            );
+        }
 
-        self.basic_block()
-            .get_last_instruction()
-            .expect("Always exists")
-            .set_alignment(alignment as u32)
-            .expect("Alignment is valid");
-        value
+        value.set_personality_function(self.runtime.personality);
+
+        let entry_block = self.llvm.append_basic_block(value, "entry");
+        let return_block = self.llvm.append_basic_block(value, "return");
+
+        let function = Function::new(name.to_owned(), value, entry_block, return_block, None);
+        self.functions.insert(name.to_string(), function.clone());
     }
 
     ///
-    /// Builds a conditional branch.
+    /// Marks the declared function `name` as pure, that is, only computing its return value from
+    /// the arguments, so LLVM may eliminate the repeated calls with the same arguments.
     ///
-    /// Checks if there are no other terminators in the block.
+    /// The function must not access the storage, memory, or globals, call other functions with
+    /// side effects, or exit the contract, including reverting. Otherwise, the behavior is
+    /// undefined. The weak functions cannot be marked, since their bodies may be replaced.
     ///
-    pub fn build_conditional_branch(
-        &self,
-        comparison: inkwell::values::IntValue<'ctx>,
-        then_block: inkwell::basic_block::BasicBlock<'ctx>,
-        else_block: inkwell::basic_block::BasicBlock<'ctx>,
-    ) {
-        if self.basic_block().get_terminator().is_some() {
-            return;
+    pub fn set_function_pure(&mut self, name: &str) -> anyhow::Result<()> {
+        let function = self
+            .functions
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` does not exist", name))?;
+        if function.value.get_linkage() == inkwell::module::Linkage::WeakAny {
+            anyhow::bail!("Function `{}` is weak and cannot be marked as pure", name);
         }
 
-        self.builder
-            .build_conditional_branch(comparison, then_block, else_block);
+        for attribute in [
+            Attribute::ReadNone,
+            Attribute::WillReturn,
+            Attribute::NoUnwind,
+        ] {
+            function.value.add_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                self.llvm.create_enum_attribute(attribute as u32, 0),
+            );
+        }
+        Ok(())
     }
 
     ///
-    /// Builds an unconditional branch.
+    /// Adds the weak `alias` of the declared function `target` to the current module.
     ///
-    /// Checks if there are no other terminators in the block.
+    /// The alias is a default implementation, which is replaced with a strong definition of
+    /// the same name when the modules are linked. Otherwise, it resolves to `target`.
     ///
-    pub fn build_unconditional_branch(
-        &self,
-        destination_block: inkwell::basic_block::BasicBlock<'ctx>,
-    ) {
-        if self.basic_block().get_terminator().is_some() {
-            return;
+    pub fn add_function_alias(&mut self, alias: &str, target: &str) -> anyhow::Result<()> {
+        let target = self
+            .functions
+            .get(target)
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` does not exist", target))?;
+        let alias_name = self.namespaced_name(alias);
+        if self.functions.contains_key(alias)
+            || self.module.get_function(alias_name.as_str()).is_some()
+        {
+            anyhow::bail!("Function `{}` already exists", alias);
         }
 
-        self.builder.build_unconditional_branch(destination_block);
+        let alias_name = std::ffi::CString::new(alias_name)
+            .map_err(|error| anyhow::anyhow!("Function alias `{}` name: {}", alias, error))?;
+        unsafe {
+            let alias = llvm_sys::core::LLVMAddAlias(
+                self.module.as_mut_ptr(),
+                target
+                    .value
+                    .get_type()
+                    .ptr_type(AddressSpace::Stack.into())
+                    .as_type_ref(),
+                target.value.as_value_ref(),
+                alias_name.as_ptr(),
+            );
+            llvm_sys::core::LLVMSetLinkage(alias, llvm_sys::LLVMLinkage::LLVMWeakAnyLinkage);
+        }
+        Ok(())
     }
 
     ///
-    /// Builds a call.
+    /// Appends a function to the current module.
     ///
-    pub fn build_call(
+    pub fn add_function_evm(
+        &mut self,
+        name: &str,
+        r#type: inkwell::types::FunctionType<'ctx>,
+        linkage: Option<inkwell::module::Linkage>,
+        evm_data: FunctionEVMData<'ctx>,
+    ) {
+        self.add_function(name, r#type, linkage);
+        self.functions
+            .get_mut(name)
+            .expect("Always exists")
+            .evm_data = Some(evm_data);
+    }
+
+    ///
+    /// Marks the declared function `name` as kernel-only, prepending its body with the check
+    /// that the current contract address is in the kernel space. Otherwise, the function reverts
+    /// with empty data.
+    ///
+    /// The check is placed into a new first block, so it must be called before the function
+    /// body is translated. The stack allocations already made in the entry block are moved to
+    /// the new first block, so they remain static, and the subsequent ones are placed there by
+    /// `build_alloca` as well.
+    ///
+    pub fn set_function_kernel_only(&mut self, name: &str) -> anyhow::Result<()> {
+        let function = self
+            .functions
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` does not exist", name))?;
+        if function.is_kernel_only {
+            return Ok(());
+        }
+        function.is_kernel_only = true;
+        let function = function.clone();
+        if let Some(current) = self.function.as_mut() {
+            if current.name == name {
+                current.is_kernel_only = true;
+            }
+        }
+
+        let current_block = self.builder.get_insert_block();
+
+        let check_block = self
+            .llvm
+            .prepend_basic_block(function.entry_block, "kernel_check_block");
+        let failure_block = self
+            .llvm
+            .append_basic_block(function.value, "kernel_check_failure_block");
+
+        self.builder.position_at_end(check_block);
+        let mut instruction = function.entry_block.get_first_instruction();
+        while let Some(current) = instruction {
+            instruction = current.get_next_instruction();
+            if current.get_opcode() == inkwell::values::InstructionOpcode::Alloca {
+                current.remove_from_basic_block();
+                self.builder.insert_instruction(&current, None);
+            }
+        }
+
+        let address = self
+            .build_call(
+                self.get_intrinsic_function(IntrinsicFunction::Address),
+                &[],
+                "kernel_check_address",
+            )
+            .expect("Always returns a value")
+            .into_int_value();
+        let is_kernel_space = self.builder.build_int_compare(
+            inkwell::IntPredicate::ULT,
+            address,
+            self.field_const(crate::r#const::KERNEL_SPACE_ADDRESS_BOUND),
+            "kernel_check_is_kernel_space",
+        );
+        self.build_conditional_branch(is_kernel_space, function.entry_block, failure_block);
+
+        self.builder.position_at_end(failure_block);
+        self.build_exit(
+            IntrinsicFunction::Revert,
+            self.field_const(0),
+            self.field_const(0),
+        );
+
+        if let Some(block) = current_block {
+            self.set_basic_block(block);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Generates the near call ABI wrapper of the declared function `name`.
+    ///
+    /// The wrapper has the same type, forwards all its arguments to the wrapped function, and
+    /// gets the near call ABI linkage and attributes from `add_function`. Calls of the wrapper are
+    /// expected to be made with `build_invoke_near_call_abi`, which links the exception handler.
+    ///
+    /// Returns the wrapper name.
+    ///
+    pub fn add_near_call_abi_wrapper(&mut self, name: &str) -> anyhow::Result<String> {
+        let function = self.functions.get(name).cloned().ok_or_else(|| {
+            self.error(
+                format!("near call ABI wrapper of `{}`", name).as_str(),
+                "the function is not declared",
+            )
+        })?;
+        let wrapper_name = format!("{}_{}", Function::ZKSYNC_NEAR_CALL_ABI_PREFIX, name);
+        if self.functions.contains_key(wrapper_name.as_str()) {
+            return Err(self.error(
+                format!("near call ABI wrapper of `{}`", name).as_str(),
+                "the wrapper is already declared",
+            ));
+        }
+
+        self.add_function(wrapper_name.as_str(), function.value.get_type(), None);
+        let wrapper = self
+            .functions
+            .get_mut(wrapper_name.as_str())
+            .expect("Always exists");
+        wrapper.r#return = function.r#return.clone();
+        let wrapper = wrapper.clone();
+
+        let current_block = self.builder.get_insert_block();
+
+        self.set_basic_block(wrapper.entry_block);
+        let arguments = wrapper.value.get_params();
+        let result = self.build_call(
+            function.value,
+            arguments.as_slice(),
+            "near_call_abi_wrapper_call",
+        );
+        self.build_unconditional_branch(wrapper.return_block);
+
+        self.set_basic_block(wrapper.return_block);
+        self.build_return(
+            result
+                .as_ref()
+                .map(|result| result as &dyn BasicValue<'ctx>),
+        );
+
+        if let Some(current_block) = current_block {
+            self.set_basic_block(current_block);
+        }
+
+        Ok(wrapper_name)
+    }
+
+    ///
+    /// Moves the cold functions to the companion library contract `library_path`, replacing
+    /// their bodies with the delegate call thunks. Is an experimental code size escape hatch
+    /// for the contracts exceeding the bytecode size limit.
+    ///
+    /// Must be called after the translation. The companion is compiled by the front-end from
+    /// the same source with `build_cold_dispatcher` in its runtime code, given the returned
+    /// function names in order, and its address is linked like a deferred library.
+    ///
+    /// The splitting is requested per contract rather than in the optimizer settings, which are
+    /// shared with the dependencies, so the companion is not split again.
+    /// The functions with fewer than `threshold` instructions are left in place.
+    ///
+    pub fn split_cold(
+        &mut self,
+        library_path: &str,
+        threshold: usize,
+    ) -> anyhow::Result<Vec<String>> {
+        let functions = self::cold_split::select(self, threshold);
+        let mut names = Vec::with_capacity(functions.len());
+        for (index, function) in functions.into_iter().enumerate() {
+            names.push(function.get_name().to_string_lossy().into_owned());
+            self::cold_split::thunk(self, function, index, library_path)?;
+        }
+        Ok(names)
+    }
+
+    ///
+    /// Emits the companion library dispatcher of the functions `names` returned by `split_cold`
+    /// for the primary contract, terminating the current block.
+    ///
+    pub fn build_cold_dispatcher(&mut self, names: &[String]) -> anyhow::Result<()> {
+        let functions = names
+            .iter()
+            .map(|name| {
+                self.module()
+                    .get_function(name.as_str())
+                    .ok_or_else(|| anyhow::anyhow!("Function `{}` does not exist", name))
+            })
+            .collect::<anyhow::Result<Vec<inkwell::values::FunctionValue<'ctx>>>>()?;
+        self::cold_split::dispatcher(self, functions.as_slice())
+    }
+
+    ///
+    /// Returns the current function.
+    ///
+    pub fn function(&self) -> &Function<'ctx> {
+        self.function.as_ref().expect("Must be declared before use")
+    }
+
+    ///
+    /// Captures the translation state, so the IR emitted by a speculative translation can be
+    /// discarded with `rollback`.
+    ///
+    pub fn snapshot(&self) -> Snapshot<'ctx> {
+        Snapshot::new(self)
+    }
+
+    ///
+    /// Discards the IR emitted since `snapshot` was taken, and restores the current function,
+    /// basic block, and loop stack.
+    ///
+    /// The snapshots must be rolled back in the reverse order of taking them. Returns an error
+    /// if the IR emitted since the snapshot is referred to by the IR kept.
+    ///
+    pub fn rollback(&mut self, snapshot: Snapshot<'ctx>) -> anyhow::Result<()> {
+        snapshot.restore(self)
+    }
+
+    ///
+    /// Returns the current function as a mutable reference.
+    ///
+    pub fn function_mut(&mut self) -> &mut Function<'ctx> {
+        self.function.as_mut().expect("Must be declared before use")
+    }
+
+    ///
+    /// Sets the current function.
+    ///
+    pub fn set_function(&mut self, function: Function<'ctx>) {
+        self.function = Some(function);
+    }
+
+    ///
+    /// Sets the return entity for the current function.
+    ///
+    pub fn set_function_return(&mut self, r#return: FunctionReturn<'ctx>) {
+        let name = self.function().name.clone();
+
+        self.functions
+            .get_mut(name.as_str())
+            .expect("Always exists")
+            .set_return(r#return.clone());
+        self.function_mut().set_return(r#return);
+    }
+
+    ///
+    /// Returns the pointer to the named component `name` of the current function compound return.
+    ///
+    pub fn get_return_component_pointer(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<inkwell::values::PointerValue<'ctx>> {
+        let function = self.function();
+        let r#return = function.r#return.as_ref().ok_or_else(|| {
+            self.error(
+                format!("function `{}` return", function.name).as_str(),
+                "has not been set",
+            )
+        })?;
+        let (index, _type) = r#return.component(name).ok_or_else(|| {
+            self.error(
+                format!("function `{}` return", function.name).as_str(),
+                format!("has no component `{}`", name),
+            )
+        })?;
+        let pointer = r#return.return_pointer().expect("Always exists");
+        self.builder
+            .build_struct_gep(
+                pointer,
+                index,
+                self.name_suffixed("return_component_pointer", name)
+                    .as_ref(),
+            )
+            .map_err(|_| {
+                self.error(
+                    format!("function `{}` return", function.name).as_str(),
+                    format!("component `{}` is out of bounds", name),
+                )
+            })
+    }
+
+    ///
+    /// Loads the named component `name` of the current function compound return.
+    ///
+    pub fn get_return_component(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>> {
+        let pointer = self.get_return_component_pointer(name)?;
+        Ok(self.build_load(
+            pointer,
+            self.name_suffixed("return_component", name).as_ref(),
+        ))
+    }
+
+    ///
+    /// Stores `value` to the named component `name` of the current function compound return.
+    ///
+    pub fn set_return_component<V>(&self, name: &str, value: V) -> anyhow::Result<()>
+    where
+        V: BasicValue<'ctx>,
+    {
+        let pointer = self.get_return_component_pointer(name)?;
+        self.build_store(pointer, value);
+        Ok(())
+    }
+
+    ///
+    /// Returns the specified LLVM intrinsic function.
+    ///
+    pub fn get_intrinsic_function(
+        &self,
+        function: IntrinsicFunction,
+    ) -> inkwell::values::FunctionValue<'ctx> {
+        self.try_get_intrinsic_function(function)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    ///
+    /// Returns the specified LLVM intrinsic function, or an error if it is unknown to LLVM.
+    ///
+    pub fn try_get_intrinsic_function(
+        &self,
+        function: IntrinsicFunction,
+    ) -> anyhow::Result<inkwell::values::FunctionValue<'ctx>> {
+        let intrinsic = inkwell::intrinsics::Intrinsic::find(function.name()).ok_or_else(|| {
+            Error::MissingIntrinsic {
+                contract_path: self.contract_path(),
+                name: function.name().to_owned(),
+            }
+        })?;
+        intrinsic
+            .get_declaration(self.module(), function.argument_types(self).as_slice())
+            .ok_or_else(|| {
+                self.error(
+                    format!("intrinsic function `{}`", function.name()).as_str(),
+                    "declaration error",
+                )
+            })
+    }
+
+    ///
+    /// Appends a new basic block to the current function.
+    ///
+    pub fn append_basic_block(&self, name: &str) -> inkwell::basic_block::BasicBlock<'ctx> {
+        self.llvm.append_basic_block(self.function().value, name)
+    }
+
+    ///
+    /// Sets the current basic block.
+    ///
+    pub fn set_basic_block(&self, block: inkwell::basic_block::BasicBlock<'ctx>) {
+        self.builder.position_at_end(block);
+    }
+
+    ///
+    /// Returns the current basic block.
+    ///
+    pub fn basic_block(&self) -> inkwell::basic_block::BasicBlock<'ctx> {
+        self.builder.get_insert_block().expect("Always exists")
+    }
+
+    ///
+    /// Returns the value of a global variable.
+    ///
+    /// The fat pointer globals still holding the null sentinel have not been assigned yet, so
+    /// reading them is checked at runtime if the assertions are enabled.
+    ///
+    pub fn get_global(&self, name: &str) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>> {
+        let global_pointer = self.get_global_ptr(name)?;
+        let global_value = self.build_load(
+            global_pointer,
+            self.name_suffixed("global_value", name).as_ref(),
+        );
+
+        if let inkwell::values::BasicValueEnum::PointerValue(pointer) = global_value {
+            if pointer.get_type().get_address_space() == AddressSpace::Generic.into()
+                && self.optimizer.settings().is_assertions_enabled
+            {
+                let pointer_value = self.fat_ptr_to_int(
+                    pointer,
+                    self.name_suffixed("global_pointer_value", name).as_ref(),
+                );
+                let is_assigned = self.builder.build_int_compare(
+                    inkwell::IntPredicate::NE,
+                    pointer_value,
+                    self.field_const(0),
+                    self.name_suffixed("global_pointer_is_assigned", name)
+                        .as_ref(),
+                );
+                self.build_assertion(
+                    is_assigned,
+                    format!("Pointer `{}` is read before assignment", name).as_str(),
+                );
+            }
+        }
+
+        Ok(global_value)
+    }
+
+    ///
+    /// Returns the calldata size, loading it once per function.
+    ///
+    /// The value is loaded at the beginning of the function entry block, so it dominates all
+    /// its uses. Thus, it must not be used in the functions writing the calldata size, that is,
+    /// in the entry function, which must read it with `get_global` instead.
+    ///
+    pub fn calldata_size(&mut self) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
+        if let Some(value) = self.function().calldata_size {
+            return Ok(value);
+        }
+
+        let current_block = self.basic_block();
+        self.position_at_allocas(self.function().entry_block);
+        let value = self.get_global(crate::r#const::GLOBAL_CALLDATA_SIZE);
+        self.set_basic_block(current_block);
+        let value = value?.into_int_value();
+
+        self.function_mut().calldata_size = Some(value);
+        Ok(value)
+    }
+
+    ///
+    /// Returns the generic pointer to the calldata word at `offset`.
+    ///
+    /// The calldata pointer is loaded once per function in the entry block. The word pointers
+    /// at the constant offsets are computed there as well and reused afterwards, so the repeated
+    /// reads at the same offset are not obscured by the redundant pointer casts. Like
+    /// `calldata_size`, must not be used in the entry function, which assigns the calldata
+    /// pointer, so the pointer is not checked for assignment.
+    ///
+    pub fn calldata_word_pointer(
+        &mut self,
+        offset: inkwell::values::IntValue<'ctx>,
+    ) -> anyhow::Result<inkwell::values::PointerValue<'ctx>> {
+        let constant = self.const_u64(offset);
+        if let Some(pointer) =
+            constant.and_then(|constant| self.function().calldata_word_pointers.get(&constant))
+        {
+            return Ok(*pointer);
+        }
+
+        let current_block = self.basic_block();
+        let entry_block = self.function().entry_block;
+        let calldata_pointer = match self.function().calldata_pointer {
+            Some(pointer) => pointer,
+            None => {
+                let global_pointer =
+                    self.get_global_ptr(crate::r#const::GLOBAL_CALLDATA_POINTER)?;
+                self.position_at_allocas(entry_block);
+                let pointer = self
+                    .build_load(global_pointer, "calldata_pointer")
+                    .into_pointer_value();
+                self.set_basic_block(current_block);
+                self.function_mut().calldata_pointer = Some(pointer);
+                pointer
+            }
+        };
+
+        if constant.is_some() {
+            match calldata_pointer
+                .as_instruction_value()
+                .and_then(|instruction| instruction.get_next_instruction())
+            {
+                Some(next) => self.builder.position_before(&next),
+                None => self.builder.position_at_end(entry_block),
+            }
+        }
+        let pointer = unsafe {
+            self.builder
+                .build_gep(calldata_pointer, &[offset], "calldata_word_pointer")
+        };
+        let pointer = self.builder.build_pointer_cast(
+            pointer,
+            self.field_type().ptr_type(AddressSpace::Generic.into()),
+            "calldata_word_pointer_casted",
+        );
+        if let Some(constant) = constant {
+            self.set_basic_block(current_block);
+            self.function_mut()
+                .calldata_word_pointers
+                .insert(constant, pointer);
+        }
+        Ok(pointer)
+    }
+
+    ///
+    /// Returns the pointer to a global variable.
+    ///
+    pub fn get_global_ptr(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<inkwell::values::PointerValue<'ctx>> {
+        match self.module.get_global(self.namespaced_name(name).as_str()) {
+            Some(global) => Ok(global.as_pointer_value()),
+            None => anyhow::bail!("Global variable {} is not declared", name),
+        }
+    }
+
+    ///
+    /// Sets the value to a global variable.
+    ///
+    pub fn set_global<V: BasicValue<'ctx>>(&self, name: &str, value: V) {
+        let pointer = self.declare_global(name, value.as_basic_value_enum().get_type());
+        self.build_store(pointer, value);
+    }
+
+    ///
+    /// Declares a generic address space fat pointer global variable, if it has not been declared
+    /// yet.
+    ///
+    /// The variable is zero-initialized, that is, holds the null sentinel recognized by
+    /// `get_global`.
+    /// The pointer globals must be declared before the function bodies are translated, since
+    /// their assignments may be translated after the reads.
+    ///
+    pub fn declare_global_pointer(&self, name: &str) -> inkwell::values::PointerValue<'ctx> {
+        self.declare_global(
+            name,
+            self.integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Generic.into()),
+        )
+    }
+
+    ///
+    /// Declares a zero-initialized global variable, if it has not been declared yet.
+    ///
+    /// Returns the pointer to the global variable.
+    ///
+    pub fn declare_global<T: BasicType<'ctx>>(
+        &self,
+        name: &str,
+        r#type: T,
+    ) -> inkwell::values::PointerValue<'ctx> {
+        let name = self.namespaced_name(name);
+        if let Some(global) = self.module.get_global(name.as_str()) {
+            return global.as_pointer_value();
+        }
+
+        let r#type = r#type.as_basic_type_enum();
+        let global =
+            self.module
+                .add_global(r#type, Some(AddressSpace::Stack.into()), name.as_str());
+        global.set_linkage(inkwell::module::Linkage::Private);
+        global.set_visibility(inkwell::GlobalVisibility::Default);
+        global.set_externally_initialized(false);
+
+        global.set_initializer(&r#type.const_zero());
+        global.as_pointer_value()
+    }
+
+    ///
+    /// Pushes a new loop context to the stack.
+    ///
+    pub fn push_loop(
+        &mut self,
+        body_block: inkwell::basic_block::BasicBlock<'ctx>,
+        continue_block: inkwell::basic_block::BasicBlock<'ctx>,
+        join_block: inkwell::basic_block::BasicBlock<'ctx>,
+    ) {
+        self.loop_stack
+            .push(Loop::new(body_block, continue_block, join_block));
+    }
+
+    ///
+    /// Pops the current loop context from the stack.
+    ///
+    pub fn pop_loop(&mut self) {
+        self.loop_stack.pop();
+    }
+
+    ///
+    /// Returns the current loop context.
+    ///
+    pub fn r#loop(&self) -> &Loop<'ctx> {
+        self.loop_stack
+            .last()
+            .expect("The current context is not in a loop")
+    }
+
+    ///
+    /// Returns the current loop context, or an error if the current context is not in a loop.
+    ///
+    /// Is used to report `break` and `continue` statements outside of loops.
+    ///
+    pub fn try_loop(&self) -> anyhow::Result<&Loop<'ctx>> {
+        self.loop_stack
+            .last()
+            .ok_or_else(|| self.error("loop", "the current context is not in a loop"))
+    }
+
+    ///
+    /// Builds a stack allocation instruction.
+    ///
+    /// The allocation is inserted into the entry block of the current function, so there are
+    /// no dynamic allocations inside loops. The builder is moved back to the end of the current
+    /// block afterwards.
+    ///
+    /// Sets the alignment to 256 bits.
+    ///
+    pub fn build_alloca<T: BasicType<'ctx>>(
+        &self,
+        r#type: T,
+        name: &str,
+    ) -> inkwell::values::PointerValue<'ctx> {
+        let current_block = self.basic_block();
+        let entry_block = current_block
+            .get_parent()
+            .and_then(|function| function.get_first_basic_block())
+            .expect("Always exists");
+        self.position_at_allocas(entry_block);
+
+        let pointer = self.builder.build_alloca(r#type, name);
+        pointer
+            .as_instruction_value()
+            .expect("Always exists")
+            .set_alignment(compiler_common::SIZE_FIELD as u32)
+            .expect("Alignment is valid");
+
+        self.set_basic_block(current_block);
+        pointer
+    }
+
+    ///
+    /// Positions the builder after the stack allocations at the beginning of `block`.
+    ///
+    pub fn position_at_allocas(&self, block: inkwell::basic_block::BasicBlock<'ctx>) {
+        let mut instruction = block.get_first_instruction();
+        while let Some(current) = instruction {
+            if current.get_opcode() != inkwell::values::InstructionOpcode::Alloca {
+                self.builder.position_before(&current);
+                return;
+            }
+            instruction = current.get_next_instruction();
+        }
+        self.builder.position_at_end(block);
+    }
+
+    ///
+    /// Builds a stack store instruction.
+    ///
+    /// Sets the alignment to 256 bits for the stack and 1 bit for the heap, parent, and child.
+    ///
+    /// # Panics
+    /// If `pointer` is in the read-only code page.
+    ///
+    pub fn build_store<V: BasicValue<'ctx>>(
+        &self,
+        pointer: inkwell::values::PointerValue<'ctx>,
+        value: V,
+    ) {
+        let address_space = AddressSpace::try_from(pointer.get_type().get_address_space())
+            .expect("The address space is always known");
+        assert_ne!(
+            address_space,
+            AddressSpace::Code,
+            "The code page is read-only"
+        );
+        let instruction = self.builder.build_store(pointer, value);
+
+        let alignment = address_space.alignment();
+
+        instruction
+            .set_alignment(alignment as u32)
+            .expect("Alignment is valid");
+    }
+
+    ///
+    /// Builds a stack load instruction.
+    ///
+    /// Sets the alignment to 256 bits for the stack and the code page, and 1 bit for the heap,
+    /// parent, and child.
+    ///
+    pub fn build_load(
+        &self,
+        pointer: inkwell::values::PointerValue<'ctx>,
+        name: &str,
+    ) -> inkwell::values::BasicValueEnum<'ctx> {
+        let value = self.builder.build_load(pointer, name);
+
+        let alignment = AddressSpace::try_from(pointer.get_type().get_address_space())
+            .expect("The address space is always known")
+            .alignment();
+
+        self.basic_block()
+            .get_last_instruction()
+            .expect("Always exists")
+            .set_alignment(alignment as u32)
+            .expect("Alignment is valid");
+        value
+    }
+
+    ///
+    /// Builds a conditional branch.
+    ///
+    /// Checks if there are no other terminators in the block.
+    ///
+    pub fn build_conditional_branch(
+        &self,
+        comparison: inkwell::values::IntValue<'ctx>,
+        then_block: inkwell::basic_block::BasicBlock<'ctx>,
+        else_block: inkwell::basic_block::BasicBlock<'ctx>,
+    ) {
+        if self.basic_block().get_terminator().is_some() {
+            return;
+        }
+
+        self.builder
+            .build_conditional_branch(comparison, then_block, else_block);
+    }
+
+    ///
+    /// Builds a conditional branch with the `then_weight` and `else_weight` branch weights.
+    ///
+    /// Allows marking the error paths unlikely, which improves the block placement.
+    ///
+    /// Checks if there are no other terminators in the block.
+    ///
+    pub fn build_conditional_branch_weighted(
+        &self,
+        comparison: inkwell::values::IntValue<'ctx>,
+        then_block: inkwell::basic_block::BasicBlock<'ctx>,
+        else_block: inkwell::basic_block::BasicBlock<'ctx>,
+        then_weight: u32,
+        else_weight: u32,
+    ) {
+        if self.basic_block().get_terminator().is_some() {
+            return;
+        }
+
+        let instruction = self
+            .builder
+            .build_conditional_branch(comparison, then_block, else_block);
+        let weight_type = self.integer_type(compiler_common::BITLENGTH_X32);
+        let weights = self.llvm.metadata_node(&[
+            self.llvm.metadata_string("branch_weights").into(),
+            weight_type.const_int(then_weight as u64, false).into(),
+            weight_type.const_int(else_weight as u64, false).into(),
+        ]);
+        instruction
+            .set_metadata(weights, self.llvm.get_kind_id("prof"))
+            .expect("Always valid");
+    }
+
+    ///
+    /// Builds the `value` expectation, telling the optimizer that `value` is likely `expected`.
+    ///
+    /// Returns the value to be used instead of the original one.
+    ///
+    pub fn build_expect(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+        expected: inkwell::values::IntValue<'ctx>,
+    ) -> inkwell::values::IntValue<'ctx> {
+        let name = IntrinsicFunction::Expect.name();
+        let function = inkwell::intrinsics::Intrinsic::find(name)
+            .and_then(|intrinsic| {
+                intrinsic.get_declaration(self.module(), &[value.get_type().as_basic_type_enum()])
+            })
+            .unwrap_or_else(|| panic!("Intrinsic function `{}` declaration error", name));
+        self.build_call(
+            function,
+            &[value.as_basic_value_enum(), expected.as_basic_value_enum()],
+            "expect",
+        )
+        .expect("Always returns a value")
+        .into_int_value()
+    }
+
+    ///
+    /// Builds the byte order reversal of `value`, e.g. for the little-endian encodings.
+    ///
+    /// The intrinsic is declared with the `value` type, which must be a multiple of 16 bits wide.
+    ///
+    pub fn build_byte_swap(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+    ) -> inkwell::values::IntValue<'ctx> {
+        let name = IntrinsicFunction::ByteSwap.name();
+        let function = inkwell::intrinsics::Intrinsic::find(name)
+            .and_then(|intrinsic| {
+                intrinsic.get_declaration(self.module(), &[value.get_type().as_basic_type_enum()])
+            })
+            .unwrap_or_else(|| panic!("Intrinsic function `{}` declaration error", name));
+        self.build_call(function, &[value.as_basic_value_enum()], "byte_swap")
+            .expect("Always returns a value")
+            .into_int_value()
+    }
+
+    ///
+    /// Marks the beginning of the stack allocation `pointer` lifetime.
+    ///
+    /// The allocations are hoisted into the entry block by `build_alloca`, so the short-lived
+    /// ones must be marked to let the back-end reuse their stack slots.
+    ///
+    pub fn build_lifetime_start(&self, pointer: inkwell::values::PointerValue<'ctx>) {
+        self.build_lifetime_marker(IntrinsicFunction::LifetimeStart, pointer);
+    }
+
+    ///
+    /// Marks the end of the stack allocation `pointer` lifetime.
+    ///
+    /// The allocation must not be accessed afterwards until its lifetime is started again.
+    ///
+    pub fn build_lifetime_end(&self, pointer: inkwell::values::PointerValue<'ctx>) {
+        self.build_lifetime_marker(IntrinsicFunction::LifetimeEnd, pointer);
+    }
+
+    ///
+    /// Builds a stack allocation lifetime marker of the unknown size, which stands for the
+    /// whole allocation.
+    ///
+    fn build_lifetime_marker(
+        &self,
+        marker: IntrinsicFunction,
+        pointer: inkwell::values::PointerValue<'ctx>,
+    ) {
+        let pointer = self.builder.build_pointer_cast(
+            pointer,
+            self.integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Stack.into()),
+            "lifetime_pointer",
+        );
+        self.build_call(
+            self.get_intrinsic_function(marker),
+            &[
+                self.integer_type(compiler_common::BITLENGTH_X64)
+                    .const_all_ones()
+                    .as_basic_value_enum(),
+                pointer.as_basic_value_enum(),
+            ],
+            "lifetime_marker",
+        );
+    }
+
+    ///
+    /// Builds an unconditional branch.
+    ///
+    /// Checks if there are no other terminators in the block.
+    ///
+    pub fn build_unconditional_branch(
+        &self,
+        destination_block: inkwell::basic_block::BasicBlock<'ctx>,
+    ) {
+        if self.basic_block().get_terminator().is_some() {
+            return;
+        }
+
+        self.builder.build_unconditional_branch(destination_block);
+    }
+
+    ///
+    /// Builds a call.
+    ///
+    /// The callee is not checked, so the calls of the functions obtained not from the context,
+    /// e.g. by name from the module, must be built with `try_build_call`.
+    ///
+    pub fn build_call(
+        &self,
+        function: inkwell::values::FunctionValue<'ctx>,
+        arguments: &[inkwell::values::BasicValueEnum<'ctx>],
+        name: &str,
+    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
+        let arguments_wrapped: Vec<inkwell::values::BasicMetadataValueEnum> = arguments
+            .iter()
+            .copied()
+            .map(inkwell::values::BasicMetadataValueEnum::from)
+            .collect();
+        let call_site_value = self
+            .builder
+            .build_call(function, arguments_wrapped.as_slice(), name);
+        self.modify_call_site_value(arguments, call_site_value);
+        call_site_value.try_as_basic_value().left()
+    }
+
+    ///
+    /// Builds a call, checking the callee with `check_callee` first.
+    ///
+    pub fn try_build_call(
+        &self,
+        function: inkwell::values::FunctionValue<'ctx>,
+        arguments: &[inkwell::values::BasicValueEnum<'ctx>],
+        name: &str,
+    ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>> {
+        self.check_callee(function)?;
+        Ok(self.build_call(function, arguments, name))
+    }
+
+    ///
+    /// Checks that the callee is an intrinsic or runtime function, a function with a body, or
+    /// a function declared by the front-end, since otherwise calling a never declared function
+    /// only surfaces as a cryptic verifier or linker failure.
+    ///
+    /// The declared functions are looked up by the callee name in the current namespace and
+    /// the namespace the name is prefixed with.
+    ///
+    pub fn check_callee(
+        &self,
+        function: inkwell::values::FunctionValue<'ctx>,
+    ) -> anyhow::Result<()> {
+        let name = function.get_name().to_string_lossy();
+        if name.starts_with("llvm.")
+            || name.starts_with(Runtime::FUNCTION_PREFIX)
+            || function.count_basic_blocks() > 0
+        {
+            return Ok(());
+        }
+
+        let mut declared = vec![self.functions.get(name.as_ref())];
+        if let Some((namespace, name)) = name.split_once(Self::CONTRACT_NAMESPACE_SEPARATOR) {
+            declared.push(match self.contract_namespace.as_deref() {
+                Some(current) if current == namespace => self.functions.get(name),
+                _ => self
+                    .namespaced_functions
+                    .get(namespace)
+                    .and_then(|functions| functions.get(name)),
+            });
+        }
+        if declared
+            .into_iter()
+            .flatten()
+            .any(|declared| declared.value == function)
+        {
+            return Ok(());
+        }
+
+        Err(self.error(
+            format!("function `{}` call", name).as_str(),
+            "the callee is not declared",
+        ))
+    }
+
+    ///
+    /// Builds an invoke.
+    ///
+    /// Is defaulted to a call if there is no global exception handler. The callee is not checked,
+    /// see `try_build_invoke`.
+    ///
+    pub fn build_invoke(
+        &self,
+        function: inkwell::values::FunctionValue<'ctx>,
+        arguments: &[inkwell::values::BasicValueEnum<'ctx>],
+        name: &str,
+    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
+        if !self
+            .functions
+            .contains_key(Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER)
+        {
+            return self.build_call(function, arguments, name);
+        }
+
+        let return_pointer = if let Some(r#type) = function.get_type().get_return_type() {
+            let pointer = self.build_alloca(r#type, "invoke_return_pointer");
+            self.build_store(pointer, r#type.const_zero());
+            Some(pointer)
+        } else {
+            None
+        };
+
+        let success_block = self.append_basic_block("invoke_success_block");
+        let catch_block = self.append_basic_block("invoke_catch_block");
+        let current_block = self.basic_block();
+
+        self.set_basic_block(catch_block);
+        let landing_pad_type = self.structure_type(vec![
+            self.integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Stack.into())
+                .as_basic_type_enum(),
+            self.integer_type(compiler_common::BITLENGTH_X32)
+                .as_basic_type_enum(),
+        ]);
+        self.builder.build_landing_pad(
+            landing_pad_type,
+            self.runtime.personality,
+            &[self
+                .integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Stack.into())
+                .const_zero()
+                .as_basic_value_enum()],
+            false,
+            "invoke_catch_landing",
+        );
+        self.build_call(
+            self.runtime.cxa_throw,
+            &[self
+                .integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Stack.into())
+                .const_null()
+                .as_basic_value_enum(); 3],
+            Runtime::FUNCTION_CXA_THROW,
+        );
+        self.build_unreachable();
+
+        self.set_basic_block(current_block);
+        let call_site_value =
+            self.builder
+                .build_invoke(function, arguments, success_block, catch_block, name);
+        self.modify_call_site_value(arguments, call_site_value);
+
+        self.set_basic_block(success_block);
+        if let (Some(return_pointer), Some(mut return_value)) =
+            (return_pointer, call_site_value.try_as_basic_value().left())
+        {
+            if let Some(return_type) = function.get_type().get_return_type() {
+                if return_type.is_pointer_type() {
+                    return_value = self
+                        .builder()
+                        .build_int_to_ptr(
+                            return_value.into_int_value(),
+                            return_type.into_pointer_type(),
+                            self.name_suffixed("invoke_return_pointer_casted", name)
+                                .as_ref(),
+                        )
+                        .as_basic_value_enum();
+                }
+            }
+            self.build_store(return_pointer, return_value);
+        }
+        return_pointer.map(|pointer| self.build_load(pointer, "invoke_result"))
+    }
+
+    ///
+    /// Builds an invoke, checking the callee with `check_callee` first.
+    ///
+    pub fn try_build_invoke(
+        &self,
+        function: inkwell::values::FunctionValue<'ctx>,
+        arguments: &[inkwell::values::BasicValueEnum<'ctx>],
+        name: &str,
+    ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>> {
+        self.check_callee(function)?;
+        Ok(self.build_invoke(function, arguments, name))
+    }
+
+    ///
+    /// Builds a type-checked call of the declared user function `name`.
+    ///
+    /// The hidden return pointer of the functions returning compound values is allocated and
+    /// passed transparently, so only the actual `arguments` must be provided. Returns nothing,
+    /// the primitive value, or the compound value structure, depending on the function return
+    /// kind.
+    ///
+    pub fn call_user_function(
+        &self,
+        name: &str,
+        mut arguments: Vec<inkwell::values::BasicValueEnum<'ctx>>,
+    ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>> {
+        let construct = format!("function `{}` call", name);
+
+        let function = self
+            .functions
+            .get(name)
+            .ok_or_else(|| self.error(construct.as_str(), "the function is not declared"))?
+            .value;
+
+        let mut parameter_types: Vec<inkwell::types::BasicTypeEnum<'ctx>> = function
+            .get_params()
+            .into_iter()
+            .map(|parameter| parameter.get_type())
+            .collect();
+        let compound_type = match function.get_type().get_return_type() {
+            Some(inkwell::types::BasicTypeEnum::PointerType(return_type))
+                if parameter_types.first() == Some(&return_type.as_basic_type_enum()) =>
+            {
+                parameter_types.remove(0);
+                Some(return_type.get_element_type().into_struct_type())
+            }
+            _ => None,
+        };
+
+        if arguments.len() != parameter_types.len() {
+            return Err(self.error(
+                construct.as_str(),
+                format!(
+                    "expected {} arguments, found {}",
+                    parameter_types.len(),
+                    arguments.len()
+                ),
+            ));
+        }
+        for (index, (argument, parameter_type)) in
+            arguments.iter().zip(parameter_types.iter()).enumerate()
+        {
+            if argument.get_type() != *parameter_type {
+                return Err(self.error(
+                    construct.as_str(),
+                    format!("argument #{} type mismatch", index),
+                ));
+            }
+        }
+
+        if let Some(compound_type) = compound_type {
+            let return_pointer =
+                self.build_alloca(compound_type, "call_user_function_return_pointer");
+            arguments.insert(0, return_pointer.as_basic_value_enum());
+        }
+
+        let result = self.build_invoke(function, arguments.as_slice(), name);
+        match (compound_type, result) {
+            (Some(_), Some(return_pointer)) => Ok(Some(self.build_load(
+                return_pointer.into_pointer_value(),
+                "call_user_function_return_value",
+            ))),
+            (_, result) => Ok(result),
+        }
+    }
+
+    ///
+    /// Builds an invoke of an external contract.
+    ///
+    /// In the far call mocking mode, the far call dispatcher is called instead, with the far
+    /// call function index prepended to the arguments.
+    ///
+    /// The result structure is only alive during the call, since its fields are loaded right
+    /// afterwards, so its stack slot can be reused by the other calls.
+    ///
+    /// # Panics
+    /// If the far call dispatcher has not been declared in the far call mocking mode.
+    ///
+    pub fn build_invoke_far_call(
+        &self,
+        mut function: inkwell::values::FunctionValue<'ctx>,
+        mut arguments: Vec<inkwell::values::BasicValueEnum<'ctx>>,
+        name: &str,
+    ) -> FarCallResult<'ctx> {
+        let result_type = FarCallResult::r#type(self);
+        let result_pointer = self.build_alloca(result_type, "far_call_result_pointer");
+        self.build_lifetime_start(result_pointer);
+        arguments.push(result_pointer.as_basic_value_enum());
+
+        if self.optimizer_settings().is_far_call_mocking_enabled {
+            let kind = Runtime::far_call_index(function).expect("Always exists");
+            arguments.insert(0, self.field_const(kind as u64).as_basic_value_enum());
+            function = self
+                .runtime
+                .custom
+                .get(Runtime::FUNCTION_TEST_DISPATCH)
+                .copied()
+                .expect("The far call dispatcher must be declared in the far call mocking mode");
+        }
+
+        let returned_pointer = self
+            .build_call(function, arguments.as_slice(), name)
+            .expect("The far call runtime functions always return a value")
+            .into_pointer_value();
+        let result = FarCallResult::load(self, returned_pointer);
+        self.build_lifetime_end(result_pointer);
+        result
+    }
+
+    ///
+    /// Builds an invoke of local call covered with an exception handler.
+    ///
+    /// Yul does not the exception handling, so the user can declare a special handling function
+    /// called (see constant `ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER`. If the enclosed function
+    /// panics, the control flow will be transferred to the exception handler.
+    ///
+    pub fn build_invoke_near_call_abi(
+        &self,
+        function: inkwell::values::FunctionValue<'ctx>,
+        arguments: Vec<inkwell::values::BasicValueEnum<'ctx>>,
+        name: &str,
+    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
+        let join_block = self.append_basic_block("near_call_join_block");
+
+        let return_pointer = if let Some(r#type) = function.get_type().get_return_type() {
+            let pointer = self.build_alloca(r#type, "near_call_return_pointer");
+            self.build_store(pointer, r#type.const_zero());
+            Some(pointer)
+        } else {
+            None
+        };
+
+        let call_site_value = if let Some(handler) = self
+            .functions
+            .get(Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER)
+        {
+            let success_block = self.append_basic_block("near_call_success_block");
+            let catch_block = self.append_basic_block("near_call_catch_block");
+            let current_block = self.basic_block();
+
+            self.set_basic_block(catch_block);
+            let landing_pad_type = self.structure_type(vec![
+                self.integer_type(compiler_common::BITLENGTH_BYTE)
+                    .ptr_type(AddressSpace::Stack.into())
+                    .as_basic_type_enum(),
+                self.integer_type(compiler_common::BITLENGTH_X32)
+                    .as_basic_type_enum(),
+            ]);
+            self.builder.build_landing_pad(
+                landing_pad_type,
+                self.runtime.personality,
+                &[self
+                    .integer_type(compiler_common::BITLENGTH_BYTE)
+                    .ptr_type(AddressSpace::Stack.into())
+                    .const_zero()
+                    .as_basic_value_enum()],
+                false,
+                "near_call_catch_landing",
+            );
+            self.build_call(handler.value, &[], "near_call_catch_call");
+            self.build_unconditional_branch(join_block);
+
+            self.set_basic_block(current_block);
+            let call_site_value = self.builder.build_invoke(
+                self.get_intrinsic_function(IntrinsicFunction::NearCall),
+                arguments.as_slice(),
+                success_block,
+                catch_block,
+                name,
+            );
+            self.modify_call_site_value(arguments.as_slice(), call_site_value);
+            self.set_basic_block(success_block);
+            call_site_value.try_as_basic_value().left()
+        } else {
+            self.build_call(
+                self.get_intrinsic_function(IntrinsicFunction::NearCall),
+                arguments.as_slice(),
+                name,
+            )
+        };
+
+        if let (Some(return_pointer), Some(mut return_value)) = (return_pointer, call_site_value) {
+            if let Some(return_type) = function.get_type().get_return_type() {
+                if return_type.is_pointer_type() {
+                    return_value = self
+                        .builder()
+                        .build_int_to_ptr(
+                            return_value.into_int_value(),
+                            return_type.into_pointer_type(),
+                            self.name_suffixed("near_call_return_pointer_casted", name)
+                                .as_ref(),
+                        )
+                        .as_basic_value_enum();
+                }
+            }
+            self.build_store(return_pointer, return_value);
+        }
+        self.build_unconditional_branch(join_block);
+
+        self.set_basic_block(join_block);
+        return_pointer.map(|pointer| self.build_load(pointer, "near_call_result"))
+    }
+
+    ///
+    /// Builds a memory copy call.
+    ///
+    /// Sets the alignment to 1 bit for heap, parent, and child.
+    ///
+    pub fn build_memcpy(
         &self,
-        function: inkwell::values::FunctionValue<'ctx>,
-        arguments: &[inkwell::values::BasicValueEnum<'ctx>],
+        intrinsic: IntrinsicFunction,
+        destination: inkwell::values::PointerValue<'ctx>,
+        source: inkwell::values::PointerValue<'ctx>,
+        size: inkwell::values::IntValue<'ctx>,
         name: &str,
-    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
-        let arguments_wrapped: Vec<inkwell::values::BasicMetadataValueEnum> = arguments
-            .iter()
-            .copied()
-            .map(inkwell::values::BasicMetadataValueEnum::from)
-            .collect();
-        let call_site_value = self
+    ) {
+        let intrinsic = self.get_intrinsic_function(intrinsic);
+
+        let call_site_value = self.builder.build_call(
+            intrinsic,
+            &[
+                destination.as_basic_value_enum().into(),
+                source.as_basic_value_enum().into(),
+                size.as_basic_value_enum().into(),
+                self.integer_type(compiler_common::BITLENGTH_BOOLEAN)
+                    .const_zero()
+                    .as_basic_value_enum()
+                    .into(),
+            ],
+            name,
+        );
+
+        call_site_value.set_alignment_attribute(inkwell::attributes::AttributeLoc::Param(0), 1);
+        call_site_value.set_alignment_attribute(inkwell::attributes::AttributeLoc::Param(1), 1);
+    }
+
+    ///
+    /// Builds a return.
+    ///
+    /// Checks if there are no other terminators in the block.
+    ///
+    pub fn build_return(&self, value: Option<&dyn BasicValue<'ctx>>) {
+        if self.basic_block().get_terminator().is_some() {
+            return;
+        }
+
+        self.builder.build_return(value);
+    }
+
+    ///
+    /// Builds an unreachable.
+    ///
+    /// Checks if there are no other terminators in the block.
+    ///
+    pub fn build_unreachable(&self) {
+        if self.basic_block().get_terminator().is_some() {
+            return;
+        }
+
+        self.builder.build_unreachable();
+    }
+
+    ///
+    /// Builds a jump table dispatching `value` to the `cases` blocks.
+    ///
+    /// If `default_unreachable` is set, the `default_block` is ignored and the fall-through path
+    /// is marked as unreachable, so LLVM can remove it. It must only be set if the front-end
+    /// has proven the cases exhaustive, e.g. for the selector switches.
+    ///
+    /// Unlike the branches, the jump table is not skipped if the current block is already
+    /// terminated, since the cases would silently become unreachable, so an error is returned.
+    ///
+    pub fn build_jump_table(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+        default_block: inkwell::basic_block::BasicBlock<'ctx>,
+        cases: &[(
+            inkwell::values::IntValue<'ctx>,
+            inkwell::basic_block::BasicBlock<'ctx>,
+        )],
+        default_unreachable: bool,
+    ) -> anyhow::Result<()> {
+        if self.basic_block().get_terminator().is_some() {
+            return Err(self.error(
+                "jump table",
+                format!(
+                    "the block `{}` is already terminated",
+                    self.basic_block().get_name().to_string_lossy()
+                ),
+            ));
+        }
+
+        let default_block = if default_unreachable {
+            let current_block = self.basic_block();
+            let unreachable_block = self.append_basic_block("jump_table_default_unreachable");
+            self.set_basic_block(unreachable_block);
+            self.build_unreachable();
+            self.set_basic_block(current_block);
+            unreachable_block
+        } else {
+            default_block
+        };
+
+        self.builder.build_switch(value, default_block, cases);
+        Ok(())
+    }
+
+    ///
+    /// Builds a switch over `value` to the `cases` blocks, falling through to the `default_block`.
+    ///
+    /// Unlike the raw builder, checks that the case values are constants of the `value` type
+    /// sorted in the strictly ascending order, so there are no duplicates, whose behavior would
+    /// depend on the back-end. The current block must not be terminated yet.
+    ///
+    pub fn build_switch(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+        default_block: inkwell::basic_block::BasicBlock<'ctx>,
+        cases: &[(
+            inkwell::values::IntValue<'ctx>,
+            inkwell::basic_block::BasicBlock<'ctx>,
+        )],
+    ) -> anyhow::Result<()> {
+        for (case, _block) in cases.iter() {
+            if !case.is_constant_int() || case.get_type() != value.get_type() {
+                return Err(self.error(
+                    "switch",
+                    format!(
+                        "the case value `{}` is not a constant of the switch value type",
+                        case.print_to_string().to_string()
+                    ),
+                ));
+            }
+        }
+        for window in cases.windows(2) {
+            let (previous, current) = (window[0].0, window[1].0);
+            let is_ascending = previous
+                .const_int_compare(inkwell::IntPredicate::ULT, current)
+                .get_zero_extended_constant()
+                == Some(1);
+            if !is_ascending {
+                return Err(self.error(
+                    "switch",
+                    format!(
+                        "the case value `{}` is {} the previous one",
+                        current.print_to_string().to_string(),
+                        if previous == current {
+                            "a duplicate of"
+                        } else {
+                            "not greater than"
+                        },
+                    ),
+                ));
+            }
+        }
+
+        self.build_jump_table(value, default_block, cases, false)
+    }
+
+    ///
+    /// Builds a dispatch of the function `selector` to the `cases` blocks, falling through to
+    /// the `default_block` if there is no matching case.
+    ///
+    /// If the number of cases reaches the selector table threshold, the selectors are emitted
+    /// as a sorted read-only table in the code page, which is binary-searched for the case index. The index is
+    /// then dispatched with a dense switch, which is lowered to a jump table. Otherwise, a plain
+    /// switch over the selectors is built.
+    ///
+    pub fn build_selector_dispatch(
+        &self,
+        selector: inkwell::values::IntValue<'ctx>,
+        default_block: inkwell::basic_block::BasicBlock<'ctx>,
+        cases: &[(u32, inkwell::basic_block::BasicBlock<'ctx>)],
+    ) -> anyhow::Result<()> {
+        let mut cases = cases.to_vec();
+        cases.sort_by_key(|(selector, _block)| *selector);
+        if let Some(window) = cases.windows(2).find(|window| window[0].0 == window[1].0) {
+            return Err(self.error(
+                "selector dispatch",
+                format!("selector {:#010x} is duplicated", window[0].0),
+            ));
+        }
+
+        let is_table = self
+            .optimizer
+            .settings()
+            .selector_table_threshold
+            .map_or(false, |threshold| cases.len() >= threshold);
+        if !is_table {
+            let cases: Vec<_> = cases
+                .into_iter()
+                .map(|(selector, block)| (self.field_const(selector as u64), block))
+                .collect();
+            return self.build_jump_table(selector, default_block, cases.as_slice(), false);
+        }
+
+        let table = self.declare_code_constant(
+            format!(
+                "{}{}",
+                crate::r#const::GLOBAL_SELECTOR_TABLE_PREFIX,
+                self.next_id()
+            )
+            .as_str(),
+            cases
+                .iter()
+                .map(|(selector, _block)| self.field_const(*selector as u64))
+                .collect::<Vec<_>>()
+                .as_slice(),
+        );
+
+        let condition_block = self.append_basic_block("selector_table_search_condition");
+        let body_block = self.append_basic_block("selector_table_search_body");
+        let found_block = self.append_basic_block("selector_table_search_found");
+        let next_block = self.append_basic_block("selector_table_search_next");
+
+        let low_pointer = self.build_alloca(self.field_type(), "selector_table_low_pointer");
+        let high_pointer = self.build_alloca(self.field_type(), "selector_table_high_pointer");
+        self.build_store(low_pointer, self.field_const(0));
+        self.build_store(high_pointer, self.field_const(cases.len() as u64));
+        self.build_unconditional_branch(condition_block);
+
+        self.set_basic_block(condition_block);
+        let low = self
+            .build_load(low_pointer, "selector_table_low")
+            .into_int_value();
+        let high = self
+            .build_load(high_pointer, "selector_table_high")
+            .into_int_value();
+        let is_range_empty = self.builder.build_int_compare(
+            inkwell::IntPredicate::UGE,
+            low,
+            high,
+            "selector_table_is_range_empty",
+        );
+        self.build_conditional_branch(is_range_empty, default_block, body_block);
+
+        self.set_basic_block(body_block);
+        let sum = self.builder.build_int_add(low, high, "selector_table_sum");
+        let middle = self.builder.build_right_shift(
+            sum,
+            self.field_const(1),
+            false,
+            "selector_table_middle",
+        );
+        let entry_pointer = unsafe {
+            self.builder.build_in_bounds_gep(
+                table.as_pointer_value(),
+                &[self.field_const(0), middle],
+                "selector_table_entry_pointer",
+            )
+        };
+        let entry = self
+            .build_load(entry_pointer, "selector_table_entry")
+            .into_int_value();
+        let is_found = self.builder.build_int_compare(
+            inkwell::IntPredicate::EQ,
+            entry,
+            selector,
+            "selector_table_is_found",
+        );
+        self.build_conditional_branch(is_found, found_block, next_block);
+
+        self.set_basic_block(next_block);
+        let is_entry_less = self.builder.build_int_compare(
+            inkwell::IntPredicate::ULT,
+            entry,
+            selector,
+            "selector_table_is_entry_less",
+        );
+        let middle_incremented = self.builder.build_int_add(
+            middle,
+            self.field_const(1),
+            "selector_table_middle_incremented",
+        );
+        let low_next = self
             .builder
-            .build_call(function, arguments_wrapped.as_slice(), name);
-        self.modify_call_site_value(arguments, call_site_value);
-        call_site_value.try_as_basic_value().left()
+            .build_select(
+                is_entry_less,
+                middle_incremented,
+                low,
+                "selector_table_low_next",
+            )
+            .into_int_value();
+        let high_next = self
+            .builder
+            .build_select(is_entry_less, high, middle, "selector_table_high_next")
+            .into_int_value();
+        self.build_store(low_pointer, low_next);
+        self.build_store(high_pointer, high_next);
+        self.build_unconditional_branch(condition_block);
+
+        self.set_basic_block(found_block);
+        let cases: Vec<_> = cases
+            .into_iter()
+            .enumerate()
+            .map(|(index, (_selector, block))| (self.field_const(index as u64), block))
+            .collect();
+        self.build_jump_table(middle, default_block, cases.as_slice(), true)
+    }
+
+    ///
+    /// Truncates `value` to 32 bits, e.g. for packing into the ABI data.
+    ///
+    /// The truncation is skipped if `value` is already known to fit into 32 bits.
+    ///
+    pub fn build_truncate_x32(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+        name: &str,
+    ) -> inkwell::values::IntValue<'ctx> {
+        if self::value_range::fits_x32(self, value) {
+            return value;
+        }
+
+        self.builder
+            .build_and(value, self.field_const(u32::MAX as u64), name)
+    }
+
+    ///
+    /// Builds a long contract exit sequence.
+    ///
+    /// The deploy code does not return the runtime code like in EVM. Instead, it returns some
+    /// additional contract metadata, e.g. the array of immutables.
+    /// The deploy code uses the auxiliary heap for the return, because otherwise it is not possible
+    /// to allocate memory together with the Yul allocator safely.
+    ///
+    pub fn build_exit(
+        &self,
+        return_function: IntrinsicFunction,
+        offset: inkwell::values::IntValue<'ctx>,
+        length: inkwell::values::IntValue<'ctx>,
+    ) {
+        let offset = self.build_truncate_x32(offset, "contract_exit_offset_truncated");
+        let length = self.build_truncate_x32(length, "contract_exit_length_truncated");
+
+        let offset_shifted = self.builder.build_left_shift(
+            offset,
+            self.field_const((compiler_common::BITLENGTH_X32 * 2) as u64),
+            "contract_exit_offset_shifted",
+        );
+        let length_shifted = self.builder.build_left_shift(
+            length,
+            self.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
+            "contract_exit_length_shifted",
+        );
+
+        let mut abi_data =
+            self.builder
+                .build_int_add(offset_shifted, length_shifted, "contract_exit_abi_data");
+        if let (Some(CodeType::Deploy), IntrinsicFunction::Return) =
+            (self.current_code_type(), return_function)
+        {
+            let auxiliary_heap_marker_shifted = self.builder().build_left_shift(
+                self.field_const(zkevm_opcode_defs::RetForwardPageType::UseAuxHeap as u64),
+                self.field_const((compiler_common::BITLENGTH_X32 * 7) as u64),
+                "contract_exit_abi_data_heap_auxiliary_marker_shifted",
+            );
+            abi_data = self.builder().build_int_add(
+                abi_data,
+                auxiliary_heap_marker_shifted,
+                "contract_exit_abi_data_add_heap_auxiliary_marker",
+            );
+        }
+
+        self.build_call(
+            self.get_intrinsic_function(return_function),
+            &[abi_data.as_basic_value_enum()],
+            self.name_suffixed("contract_exit", return_function.name())
+                .as_ref(),
+        );
+        self.build_unreachable();
+    }
+
+    ///
+    /// Converts `value` to an address by clearing its upper 96 bits, like EVM does with the
+    /// address operands.
+    ///
+    /// If the address check is enabled, reverts if the upper bits are not zero instead, which
+    /// helps to catch the dirty addresses produced by the front-end or user code in testing.
+    ///
+    pub fn to_address(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+    ) -> inkwell::values::IntValue<'ctx> {
+        if self.optimizer.settings().is_address_check_enabled {
+            let upper_bits = self.builder.build_right_shift(
+                value,
+                self.field_const(Self::BITLENGTH_ADDRESS as u64),
+                false,
+                "address_upper_bits",
+            );
+            let is_clean = self.builder.build_int_compare(
+                inkwell::IntPredicate::EQ,
+                upper_bits,
+                self.field_const(0),
+                "address_is_clean",
+            );
+            let success_block = self.append_basic_block("address_clean_block");
+            let failure_block = self.append_basic_block("address_dirty_block");
+            self.build_conditional_branch_weighted(
+                is_clean,
+                success_block,
+                failure_block,
+                Self::BRANCH_WEIGHT_LIKELY,
+                Self::BRANCH_WEIGHT_UNLIKELY,
+            );
+
+            self.set_basic_block(failure_block);
+            self.build_exit(
+                IntrinsicFunction::Revert,
+                self.field_const(0),
+                self.field_const(0),
+            );
+
+            self.set_basic_block(success_block);
+        }
+
+        let mask = self.field_type().const_int_arbitrary_precision(&[
+            u64::MAX,
+            u64::MAX,
+            u32::MAX as u64,
+            0,
+        ]);
+        self.builder.build_and(value, mask, "address")
+    }
+
+    ///
+    /// Builds a front-end assertion of the boolean `condition`.
+    ///
+    /// If the assertions are enabled, reverts with the Solidity `Error(string)` encoded `message`
+    /// if the condition is false. Otherwise, only passes the condition to the optimizer as an
+    /// assumption, so a violated assertion results in undefined behavior.
+    ///
+    pub fn build_assertion(&self, condition: inkwell::values::IntValue<'ctx>, message: &str) {
+        if !self.optimizer.settings().is_assertions_enabled {
+            self.build_call(
+                self.get_intrinsic_function(IntrinsicFunction::Assume),
+                &[condition.as_basic_value_enum()],
+                "assertion_assume",
+            );
+            return;
+        }
+
+        let success_block = self.append_basic_block("assertion_success_block");
+        let failure_block = self.append_basic_block("assertion_failure_block");
+        self.build_conditional_branch(condition, success_block, failure_block);
+
+        self.set_basic_block(failure_block);
+        self.build_revert_error_string(message);
+
+        self.set_basic_block(success_block);
+    }
+
+    ///
+    /// Reverts with the Solidity `Panic(uint256)` encoded `code`, written to the beginning of
+    /// the heap.
+    ///
+    pub fn build_revert_panic(&self, code: u64) {
+        let selector = self.field_const_str_hex(
+            format!(
+                "{}{}",
+                crate::r#const::PANIC_SELECTOR,
+                "0".repeat(2 * (compiler_common::SIZE_FIELD - compiler_common::SIZE_X32))
+            )
+            .as_str(),
+        );
+        self.build_store(
+            self.access_memory(
+                self.field_const(0),
+                AddressSpace::Heap,
+                "revert_panic_selector_pointer",
+            ),
+            selector,
+        );
+        self.build_store(
+            self.access_memory(
+                self.field_const(compiler_common::SIZE_X32 as u64),
+                AddressSpace::Heap,
+                "revert_panic_code_pointer",
+            ),
+            self.field_const(code),
+        );
+        self.build_exit(
+            IntrinsicFunction::Revert,
+            self.field_const(0),
+            self.field_const((compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD) as u64),
+        );
     }
 
     ///
-    /// Builds an invoke.
+    /// Reverts with the memory overflow panic if the heap word access at `offset` exceeds the
+    /// limit set in the optimizer settings.
     ///
-    /// Is defaulted to a call if there is no global exception handler.
+    /// The whole word must fit, so the offsets above the limit minus the field size revert.
+    /// Nothing is emitted if the limit is not set or `offset` is a constant within the limit.
     ///
-    pub fn build_invoke(
-        &self,
-        function: inkwell::values::FunctionValue<'ctx>,
-        arguments: &[inkwell::values::BasicValueEnum<'ctx>],
-        name: &str,
-    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
-        if !self
-            .functions
-            .contains_key(Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER)
-        {
-            return self.build_call(function, arguments, name);
+    pub fn build_heap_offset_check(&self, offset: inkwell::values::IntValue<'ctx>) {
+        let limit = match self.optimizer.settings().heap_offset_limit {
+            Some(limit) => limit.checked_sub(compiler_common::SIZE_FIELD as u64),
+            None => return,
+        };
+        if let Some(limit) = limit {
+            if self
+                .const_u64(offset)
+                .map_or(false, |offset| offset <= limit)
+            {
+                return;
+            }
         }
 
-        let return_pointer = if let Some(r#type) = function.get_type().get_return_type() {
-            let pointer = self.build_alloca(r#type, "invoke_return_pointer");
-            self.build_store(pointer, r#type.const_zero());
-            Some(pointer)
-        } else {
-            None
+        let is_exceeded = match limit {
+            Some(limit) => self.builder.build_int_compare(
+                inkwell::IntPredicate::UGT,
+                offset,
+                self.field_const(limit),
+                "heap_offset_is_exceeded",
+            ),
+            None => self.bool_const(true),
         };
-
-        let success_block = self.append_basic_block("invoke_success_block");
-        let catch_block = self.append_basic_block("invoke_catch_block");
-        let current_block = self.basic_block();
-
-        self.set_basic_block(catch_block);
-        let landing_pad_type = self.structure_type(vec![
-            self.integer_type(compiler_common::BITLENGTH_BYTE)
-                .ptr_type(AddressSpace::Stack.into())
-                .as_basic_type_enum(),
-            self.integer_type(compiler_common::BITLENGTH_X32)
-                .as_basic_type_enum(),
-        ]);
-        self.builder.build_landing_pad(
-            landing_pad_type,
-            self.runtime.personality,
-            &[self
-                .integer_type(compiler_common::BITLENGTH_BYTE)
-                .ptr_type(AddressSpace::Stack.into())
-                .const_zero()
-                .as_basic_value_enum()],
-            false,
-            "invoke_catch_landing",
-        );
-        self.build_call(
-            self.runtime.cxa_throw,
-            &[self
-                .integer_type(compiler_common::BITLENGTH_BYTE)
-                .ptr_type(AddressSpace::Stack.into())
-                .const_null()
-                .as_basic_value_enum(); 3],
-            Runtime::FUNCTION_CXA_THROW,
+        let success_block = self.append_basic_block("heap_offset_success_block");
+        let failure_block = self.append_basic_block("heap_offset_failure_block");
+        self.build_conditional_branch_weighted(
+            is_exceeded,
+            failure_block,
+            success_block,
+            Self::BRANCH_WEIGHT_UNLIKELY,
+            Self::BRANCH_WEIGHT_LIKELY,
         );
-        self.build_unreachable();
 
-        self.set_basic_block(current_block);
-        let call_site_value =
-            self.builder
-                .build_invoke(function, arguments, success_block, catch_block, name);
-        self.modify_call_site_value(arguments, call_site_value);
+        self.set_basic_block(failure_block);
+        self.build_revert_panic(crate::r#const::PANIC_CODE_MEMORY_OVERFLOW);
 
         self.set_basic_block(success_block);
-        if let (Some(return_pointer), Some(mut return_value)) =
-            (return_pointer, call_site_value.try_as_basic_value().left())
-        {
-            if let Some(return_type) = function.get_type().get_return_type() {
-                if return_type.is_pointer_type() {
-                    return_value = self
-                        .builder()
-                        .build_int_to_ptr(
-                            return_value.into_int_value(),
-                            return_type.into_pointer_type(),
-                            format!("{}_invoke_return_pointer_casted", name).as_str(),
-                        )
-                        .as_basic_value_enum();
-                }
-            }
-            self.build_store(return_pointer, return_value);
-        }
-        return_pointer.map(|pointer| self.build_load(pointer, "invoke_result"))
     }
 
     ///
-    /// Builds an invoke of an external contract.
+    /// Logs the heap store of `value` at `offset` just emitted, if both are constant.
     ///
-    pub fn build_invoke_far_call(
-        &self,
-        function: inkwell::values::FunctionValue<'ctx>,
-        mut arguments: Vec<inkwell::values::BasicValueEnum<'ctx>>,
-        name: &str,
-    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
-        let result_type = self
-            .structure_type(vec![
-                self.integer_type(compiler_common::BITLENGTH_BYTE)
-                    .ptr_type(AddressSpace::Generic.into())
-                    .as_basic_type_enum(),
-                self.integer_type(compiler_common::BITLENGTH_BOOLEAN)
-                    .as_basic_type_enum(),
-            ])
-            .as_basic_type_enum();
-        let result_pointer = self.build_alloca(result_type, "far_call_result_pointer");
-        arguments.push(result_pointer.as_basic_value_enum());
-
-        self.build_call(function, arguments.as_slice(), name)
+    /// Must be called right after the store, since the last instruction of the current block
+    /// is logged as the store.
+    ///
+    pub fn log_heap_store(
+        &mut self,
+        offset: inkwell::values::IntValue<'ctx>,
+        value: inkwell::values::IntValue<'ctx>,
+    ) {
+        let offset = match self.const_u64(offset) {
+            Some(offset) => offset,
+            None => return,
+        };
+        let word = match self.field_const_bytes(value) {
+            Some(word) => word,
+            None => return,
+        };
+        let block = self.basic_block();
+        let instruction = block
+            .get_last_instruction()
+            .expect("The store has just been emitted");
+        self.memory_store_log
+            .record(block, offset, word, instruction);
     }
 
     ///
-    /// Builds an invoke of local call covered with an exception handler.
-    ///
-    /// Yul does not the exception handling, so the user can declare a special handling function
-    /// called (see constant `ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER`. If the enclosed function
-    /// panics, the control flow will be transferred to the exception handler.
+    /// Returns the heap contents of `length` bytes at `offset`, if the region has been filled
+    /// with the logged constant stores in the current block and not modified otherwise since.
     ///
-    pub fn build_invoke_near_call_abi(
+    pub fn heap_constant_contents(
         &self,
-        function: inkwell::values::FunctionValue<'ctx>,
-        arguments: Vec<inkwell::values::BasicValueEnum<'ctx>>,
-        name: &str,
-    ) -> Option<inkwell::values::BasicValueEnum<'ctx>> {
-        let join_block = self.append_basic_block("near_call_join_block");
+        offset: inkwell::values::IntValue<'ctx>,
+        length: inkwell::values::IntValue<'ctx>,
+    ) -> Option<Vec<u8>> {
+        let offset = self.const_u64(offset)?;
+        let length = self.const_u64(length)?;
+        self.memory_store_log
+            .contents(self.basic_block(), offset, length)
+    }
 
-        let return_pointer = if let Some(r#type) = function.get_type().get_return_type() {
-            let pointer = self.build_alloca(r#type, "near_call_return_pointer");
-            self.build_store(pointer, r#type.const_zero());
-            Some(pointer)
-        } else {
-            None
-        };
+    ///
+    /// Returns the value of the integer constant `value`, if it fits into 64 bits.
+    ///
+    /// Unlike `get_zero_extended_constant`, the constants wider than 64 bits, such as the field
+    /// type ones, are never mistaken for their low word.
+    ///
+    pub fn const_u64(&self, value: inkwell::values::IntValue<'ctx>) -> Option<u64> {
+        if !value.is_constant_int() {
+            return None;
+        }
+        if value.get_type().get_bit_width() <= compiler_common::BITLENGTH_X64 as u32 {
+            return value.get_zero_extended_constant();
+        }
 
-        let call_site_value = if let Some(handler) = self
-            .functions
-            .get(Function::ZKSYNC_NEAR_CALL_ABI_EXCEPTION_HANDLER)
-        {
-            let success_block = self.append_basic_block("near_call_success_block");
-            let catch_block = self.append_basic_block("near_call_catch_block");
-            let current_block = self.basic_block();
+        let is_x64 = value
+            .const_int_compare(
+                inkwell::IntPredicate::ULE,
+                value.get_type().const_int(u64::MAX, false),
+            )
+            .get_zero_extended_constant()
+            == Some(1);
+        if !is_x64 {
+            return None;
+        }
+        value
+            .const_truncate(self.integer_type(compiler_common::BITLENGTH_X64))
+            .get_zero_extended_constant()
+    }
 
-            self.set_basic_block(catch_block);
-            let landing_pad_type = self.structure_type(vec![
-                self.integer_type(compiler_common::BITLENGTH_BYTE)
-                    .ptr_type(AddressSpace::Stack.into())
-                    .as_basic_type_enum(),
-                self.integer_type(compiler_common::BITLENGTH_X32)
-                    .as_basic_type_enum(),
-            ]);
-            self.builder.build_landing_pad(
-                landing_pad_type,
-                self.runtime.personality,
-                &[self
-                    .integer_type(compiler_common::BITLENGTH_BYTE)
-                    .ptr_type(AddressSpace::Stack.into())
-                    .const_zero()
-                    .as_basic_value_enum()],
-                false,
-                "near_call_catch_landing",
-            );
-            self.build_call(handler.value, &[], "near_call_catch_call");
-            self.build_unconditional_branch(join_block);
+    ///
+    /// Returns the big-endian bytes of the field type constant `value`, if it is a constant
+    /// integer.
+    ///
+    pub fn field_const_bytes(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+    ) -> Option<[u8; compiler_common::SIZE_FIELD]> {
+        if value.get_type() != self.field_type() || !value.is_constant_int() {
+            return None;
+        }
 
-            self.set_basic_block(current_block);
-            let call_site_value = self.builder.build_invoke(
-                self.get_intrinsic_function(IntrinsicFunction::NearCall),
-                arguments.as_slice(),
-                success_block,
-                catch_block,
-                name,
-            );
-            self.modify_call_site_value(arguments.as_slice(), call_site_value);
-            self.set_basic_block(success_block);
-            call_site_value.try_as_basic_value().left()
-        } else {
-            self.build_call(
-                self.get_intrinsic_function(IntrinsicFunction::NearCall),
-                arguments.as_slice(),
-                name,
-            )
-        };
+        let chunk_type = self.integer_type(compiler_common::BITLENGTH_X64);
+        let mut bytes = [0u8; compiler_common::SIZE_FIELD];
+        for (index, chunk) in bytes
+            .chunks_exact_mut(compiler_common::BITLENGTH_X64 / compiler_common::BITLENGTH_BYTE)
+            .rev()
+            .enumerate()
+        {
+            let shift = self.field_const((index * compiler_common::BITLENGTH_X64) as u64);
+            let value = value
+                .const_rshr(shift)
+                .const_truncate(chunk_type)
+                .get_zero_extended_constant()?;
+            chunk.copy_from_slice(value.to_be_bytes().as_slice());
+        }
+        Some(bytes)
+    }
 
-        if let (Some(return_pointer), Some(mut return_value)) = (return_pointer, call_site_value) {
-            if let Some(return_type) = function.get_type().get_return_type() {
-                if return_type.is_pointer_type() {
-                    return_value = self
-                        .builder()
-                        .build_int_to_ptr(
-                            return_value.into_int_value(),
-                            return_type.into_pointer_type(),
-                            format!("{}_near_call_return_pointer_casted", name).as_str(),
-                        )
-                        .as_basic_value_enum();
-                }
-            }
-            self.build_store(return_pointer, return_value);
+    ///
+    /// Declares the read-only code page global with the Solidity `Error(string)` encoded `message`,
+    /// if it has not been declared yet.
+    ///
+    /// The globals are de-duplicated by the message hash across the whole module, so the same
+    /// message shared by several revert sites or contracts is only stored once.
+    /// The first word contains the selector, and the rest of the words follow it unaligned.
+    ///
+    pub fn declare_revert_string(&self, message: &str) -> inkwell::values::GlobalValue<'ctx> {
+        let name = format!(
+            "{}{}",
+            crate::r#const::GLOBAL_REVERT_STRING_PREFIX,
+            crate::hashes::keccak256(message.as_bytes())
+        );
+        if let Some(global) = self.module.get_global(name.as_str()) {
+            return global;
         }
-        self.build_unconditional_branch(join_block);
 
-        self.set_basic_block(join_block);
-        return_pointer.map(|pointer| self.build_load(pointer, "near_call_result"))
+        let mut words = vec![
+            self.field_const_str_hex(
+                format!(
+                    "{}{}",
+                    crate::r#const::ERROR_STRING_SELECTOR,
+                    "0".repeat(2 * (compiler_common::SIZE_FIELD - compiler_common::SIZE_X32))
+                )
+                .as_str(),
+            ),
+            self.field_const(compiler_common::SIZE_FIELD as u64),
+            self.field_const(message.len() as u64),
+        ];
+        words.extend(
+            message
+                .as_bytes()
+                .chunks(compiler_common::SIZE_FIELD)
+                .map(|chunk| {
+                    let mut chunk = chunk.to_vec();
+                    chunk.resize(compiler_common::SIZE_FIELD, 0);
+                    self.field_const_str_hex(hex::encode(chunk).as_str())
+                }),
+        );
+
+        self.declare_code_constant(name.as_str(), words.as_slice())
     }
 
     ///
-    /// Builds a memory copy call.
+    /// Declares the read-only global with the field `words` in the code page.
     ///
-    /// Sets the alignment to 1 bit for heap, parent, and child.
+    /// The back-end emits the initialized code page globals to the contract data section, so
+    /// the words are loaded from the bytecode instead of being written to the stack on each call.
     ///
-    pub fn build_memcpy(
+    pub fn declare_code_constant(
         &self,
-        intrinsic: IntrinsicFunction,
-        destination: inkwell::values::PointerValue<'ctx>,
-        source: inkwell::values::PointerValue<'ctx>,
-        size: inkwell::values::IntValue<'ctx>,
         name: &str,
-    ) {
-        let intrinsic = self.get_intrinsic_function(intrinsic);
-
-        let call_site_value = self.builder.build_call(
-            intrinsic,
-            &[
-                destination.as_basic_value_enum().into(),
-                source.as_basic_value_enum().into(),
-                size.as_basic_value_enum().into(),
-                self.integer_type(compiler_common::BITLENGTH_BOOLEAN)
-                    .const_zero()
-                    .as_basic_value_enum()
-                    .into(),
-            ],
+        words: &[inkwell::values::IntValue<'ctx>],
+    ) -> inkwell::values::GlobalValue<'ctx> {
+        let initializer = self.field_type().const_array(words);
+        let global = self.module.add_global(
+            initializer.get_type(),
+            Some(AddressSpace::Code.into()),
             name,
         );
+        global.set_linkage(inkwell::module::Linkage::Private);
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        global.set_initializer(&initializer);
+        global
+    }
 
-        call_site_value.set_alignment_attribute(inkwell::attributes::AttributeLoc::Param(0), 1);
-        call_site_value.set_alignment_attribute(inkwell::attributes::AttributeLoc::Param(1), 1);
+    ///
+    /// Reverts with the Solidity `Error(string)` encoded `message`, written to the beginning of
+    /// the heap.
+    ///
+    /// The message is copied from its read-only global with a loop, so the revert site size does
+    /// not depend on the message length.
+    ///
+    pub fn build_revert_error_string(&self, message: &str) {
+        let global = self.declare_revert_string(message);
+        let words_number = global
+            .as_pointer_value()
+            .get_type()
+            .get_element_type()
+            .into_array_type()
+            .len() as u64;
+
+        let condition_block = self.append_basic_block("revert_string_loop_condition");
+        let body_block = self.append_basic_block("revert_string_loop_body");
+        let join_block = self.append_basic_block("revert_string_loop_join");
+
+        let selector_pointer = unsafe {
+            self.builder.build_in_bounds_gep(
+                global.as_pointer_value(),
+                &[self.field_const(0), self.field_const(0)],
+                "revert_string_selector_pointer",
+            )
+        };
+        let selector = self.build_load(selector_pointer, "revert_string_selector");
+        self.build_store(
+            self.access_memory(
+                self.field_const(0),
+                AddressSpace::Heap,
+                "revert_string_selector_heap_pointer",
+            ),
+            selector,
+        );
+
+        let index_pointer = self.build_alloca(self.field_type(), "revert_string_index_pointer");
+        self.build_store(index_pointer, self.field_const(1));
+        self.build_unconditional_branch(condition_block);
+
+        self.set_basic_block(condition_block);
+        let index = self
+            .build_load(index_pointer, "revert_string_index")
+            .into_int_value();
+        let condition = self.builder.build_int_compare(
+            inkwell::IntPredicate::ULT,
+            index,
+            self.field_const(words_number),
+            "revert_string_loop_condition",
+        );
+        self.build_conditional_branch(condition, body_block, join_block);
+
+        self.set_basic_block(body_block);
+        let word_pointer = unsafe {
+            self.builder.build_in_bounds_gep(
+                global.as_pointer_value(),
+                &[self.field_const(0), index],
+                "revert_string_word_pointer",
+            )
+        };
+        let word = self.build_load(word_pointer, "revert_string_word");
+        let offset = self.builder.build_int_mul(
+            index,
+            self.field_const(compiler_common::SIZE_FIELD as u64),
+            "revert_string_offset_aligned",
+        );
+        let offset = self.builder.build_int_sub(
+            offset,
+            self.field_const((compiler_common::SIZE_FIELD - compiler_common::SIZE_X32) as u64),
+            "revert_string_offset",
+        );
+        self.build_store(
+            self.access_memory(offset, AddressSpace::Heap, "revert_string_heap_pointer"),
+            word,
+        );
+        let index_incremented = self.builder.build_int_add(
+            index,
+            self.field_const(1),
+            "revert_string_index_incremented",
+        );
+        self.build_store(index_pointer, index_incremented);
+        self.build_unconditional_branch(condition_block);
+
+        self.set_basic_block(join_block);
+        let length = compiler_common::SIZE_X32 as u64
+            + (words_number - 1) * compiler_common::SIZE_FIELD as u64;
+        self.build_exit(
+            IntrinsicFunction::Revert,
+            self.field_const(0),
+            self.field_const(length),
+        );
     }
 
     ///
-    /// Builds a return.
+    /// Converts the `pointer` to the field type integer.
     ///
-    /// Checks if there are no other terminators in the block.
+    /// The generic address space fat pointers are converted with the back-end intrinsic if it is
+    /// available, since the plain `ptrtoint` is undefined for them. Other pointers, and the fat
+    /// pointers if the back-end does not provide the intrinsic, are converted as usual.
     ///
-    pub fn build_return(&self, value: Option<&dyn BasicValue<'ctx>>) {
-        if self.basic_block().get_terminator().is_some() {
-            return;
-        }
+    pub fn fat_ptr_to_int(
+        &self,
+        pointer: inkwell::values::PointerValue<'ctx>,
+        name: &str,
+    ) -> inkwell::values::IntValue<'ctx> {
+        let intrinsic = match self.try_get_intrinsic_function(IntrinsicFunction::PointerToInt) {
+            Ok(intrinsic)
+                if pointer.get_type().get_address_space() == AddressSpace::Generic.into() =>
+            {
+                intrinsic
+            }
+            _ => {
+                return self
+                    .builder
+                    .build_ptr_to_int(pointer, self.field_type(), name)
+            }
+        };
 
-        self.builder.build_return(value);
+        let pointer = self.builder.build_pointer_cast(
+            pointer,
+            self.integer_type(compiler_common::BITLENGTH_BYTE)
+                .ptr_type(AddressSpace::Generic.into()),
+            self.name_suffixed("fat_pointer_casted", name).as_ref(),
+        );
+        self.build_call(intrinsic, &[pointer.as_basic_value_enum()], name)
+            .expect("Always returns a value")
+            .into_int_value()
     }
 
     ///
-    /// Builds an unreachable.
+    /// Converts the field type integer `value` to a generic address space fat pointer.
     ///
-    /// Checks if there are no other terminators in the block.
+    /// The back-end intrinsic is used if it is available, since the plain `inttoptr` is undefined
+    /// for the fat pointers. Otherwise, the value is converted as usual.
     ///
-    pub fn build_unreachable(&self) {
-        if self.basic_block().get_terminator().is_some() {
-            return;
+    pub fn int_to_fat_ptr(
+        &self,
+        value: inkwell::values::IntValue<'ctx>,
+        name: &str,
+    ) -> inkwell::values::PointerValue<'ctx> {
+        match self.try_get_intrinsic_function(IntrinsicFunction::IntToPointer) {
+            Ok(intrinsic) => self
+                .build_call(intrinsic, &[value.as_basic_value_enum()], name)
+                .expect("Always returns a value")
+                .into_pointer_value(),
+            Err(_) => self.builder.build_int_to_ptr(
+                value,
+                self.integer_type(compiler_common::BITLENGTH_BYTE)
+                    .ptr_type(AddressSpace::Generic.into()),
+                name,
+            ),
         }
-
-        self.builder.build_unreachable();
     }
 
     ///
-    /// Builds a long contract exit sequence.
+    /// Extracts the `field` of the generic address space fat `pointer`, zero-extended to the field
+    /// type.
     ///
-    /// The deploy code does not return the runtime code like in EVM. Instead, it returns some
-    /// additional contract metadata, e.g. the array of immutables.
-    /// The deploy code uses the auxiliary heap for the return, because otherwise it is not possible
-    /// to allocate memory together with the Yul allocator safely.
+    /// The field getter intrinsic is used if the back-end provides it. Otherwise, the field is
+    /// extracted from the pointer integer with a shift and a truncation.
     ///
-    pub fn build_exit(
+    pub fn fat_ptr_field(
         &self,
-        return_function: IntrinsicFunction,
-        offset: inkwell::values::IntValue<'ctx>,
-        length: inkwell::values::IntValue<'ctx>,
-    ) {
-        let offset = self.builder.build_and(
-            offset,
-            self.field_const(u32::MAX as u64),
-            "contract_exit_offset_truncated",
-        );
-        let length = self.builder.build_and(
-            length,
-            self.field_const(u32::MAX as u64),
-            "contract_exit_length_truncated",
-        );
+        pointer: inkwell::values::PointerValue<'ctx>,
+        field: FatPointerField,
+        name: &str,
+    ) -> inkwell::values::IntValue<'ctx> {
+        if let Ok(getter) = self.try_get_intrinsic_function(field.intrinsic()) {
+            let pointer = self.builder.build_pointer_cast(
+                pointer,
+                self.integer_type(compiler_common::BITLENGTH_BYTE)
+                    .ptr_type(AddressSpace::Generic.into()),
+                self.name_suffixed("fat_pointer_casted", name).as_ref(),
+            );
+            return self
+                .build_call(getter, &[pointer.as_basic_value_enum()], name)
+                .expect("Always returns a value")
+                .into_int_value();
+        }
 
-        let offset_shifted = self.builder.build_left_shift(
-            offset,
-            self.field_const((compiler_common::BITLENGTH_X32 * 2) as u64),
-            "contract_exit_offset_shifted",
-        );
-        let length_shifted = self.builder.build_left_shift(
-            length,
-            self.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
-            "contract_exit_length_shifted",
+        let mut value = self.fat_ptr_to_int(
+            pointer,
+            self.name_suffixed("fat_pointer_value", name).as_ref(),
         );
-
-        let mut abi_data =
-            self.builder
-                .build_int_add(offset_shifted, length_shifted, "contract_exit_abi_data");
-        if let (CodeType::Deploy, IntrinsicFunction::Return) = (self.code_type(), return_function) {
-            let auxiliary_heap_marker_shifted = self.builder().build_left_shift(
-                self.field_const(zkevm_opcode_defs::RetForwardPageType::UseAuxHeap as u64),
-                self.field_const((compiler_common::BITLENGTH_X32 * 7) as u64),
-                "contract_exit_abi_data_heap_auxiliary_marker_shifted",
-            );
-            abi_data = self.builder().build_int_add(
-                abi_data,
-                auxiliary_heap_marker_shifted,
-                "contract_exit_abi_data_add_heap_auxiliary_marker",
+        if field.shift() > 0 {
+            value = self.builder.build_right_shift(
+                value,
+                self.field_const(field.shift() as u64),
+                false,
+                self.name_suffixed("fat_pointer_value_shifted", name)
+                    .as_ref(),
             );
         }
-
-        self.build_call(
-            self.get_intrinsic_function(return_function),
-            &[abi_data.as_basic_value_enum()],
-            format!("contract_exit_{}", return_function.name()).as_str(),
+        let value = self.builder.build_int_truncate(
+            value,
+            self.integer_type(compiler_common::BITLENGTH_X32),
+            self.name_suffixed("fat_pointer_field", name).as_ref(),
         );
-        self.build_unreachable();
+        self.builder
+            .build_int_z_extend(value, self.field_type(), name)
     }
 
     ///
@@ -1032,20 +3612,8 @@ where
     pub fn write_abi_calldata(&self, pointer: inkwell::values::PointerValue<'ctx>) {
         self.set_global(crate::r#const::GLOBAL_CALLDATA_POINTER, pointer);
 
-        let abi_pointer_value =
-            self.builder()
-                .build_ptr_to_int(pointer, self.field_type(), "abi_pointer_value");
-        let abi_pointer_value_shifted = self.builder().build_right_shift(
-            abi_pointer_value,
-            self.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
-            false,
-            "abi_pointer_value_shifted",
-        );
-        let abi_length_value = self.builder().build_and(
-            abi_pointer_value_shifted,
-            self.field_const(u32::MAX as u64),
-            "abi_length_value",
-        );
+        let abi_length_value =
+            self.fat_ptr_field(pointer, FatPointerField::Length, "abi_length_value");
         self.set_global(crate::r#const::GLOBAL_CALLDATA_SIZE, abi_length_value);
     }
 
@@ -1055,20 +3623,8 @@ where
     pub fn write_abi_return_data(&self, pointer: inkwell::values::PointerValue<'ctx>) {
         self.set_global(crate::r#const::GLOBAL_RETURN_DATA_POINTER, pointer);
 
-        let abi_pointer_value =
-            self.builder()
-                .build_ptr_to_int(pointer, self.field_type(), "abi_pointer_value");
-        let abi_pointer_value_shifted = self.builder().build_right_shift(
-            abi_pointer_value,
-            self.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
-            false,
-            "abi_pointer_value_shifted",
-        );
-        let abi_length_value = self.builder().build_and(
-            abi_pointer_value_shifted,
-            self.field_const(u32::MAX as u64),
-            "abi_length_value",
-        );
+        let abi_length_value =
+            self.fat_ptr_field(pointer, FatPointerField::Length, "abi_length_value");
         self.set_global(crate::r#const::GLOBAL_RETURN_DATA_SIZE, abi_length_value);
     }
 
@@ -1131,34 +3687,169 @@ where
     }
 
     ///
-    /// Returns a field type constant from a decimal or hexadecimal string.
+    /// Returns a field type constant from a decimal or `0x`-prefixed hexadecimal string.
     ///
     pub fn field_const_str(&self, value: &str) -> inkwell::values::IntValue<'ctx> {
-        match value.strip_prefix("0x") {
-            Some(hexadecimal) => self.field_const_str_hex(hexadecimal),
-            None => self.field_const_str_hex(value),
+        self.try_field_const_str(value)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    ///
+    /// Returns a field type constant from a decimal or `0x`-prefixed hexadecimal string, or an
+    /// error if the string is invalid or the value does not fit into the field type.
+    ///
+    pub fn try_field_const_str(
+        &self,
+        value: &str,
+    ) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
+        let (digits, radix) = Self::split_radix(value);
+        let number = num::BigUint::parse_bytes(digits.as_bytes(), radix).ok_or_else(|| {
+            self.error(format!("constant `{}`", value).as_str(), "invalid literal")
+        })?;
+        if number.bits() > compiler_common::BITLENGTH_FIELD as u64 {
+            return Err(self.error(
+                format!("constant `{}`", value).as_str(),
+                format!(
+                    "the value exceeds the {}-bit field type",
+                    compiler_common::BITLENGTH_FIELD
+                ),
+            ));
+        }
+
+        match radix {
+            16 => self.try_field_const_str_hex(digits),
+            _ => self.try_field_const_str_dec(digits),
         }
     }
 
     ///
-    /// Returns a field type constant from a hexadecimal string.
+    /// Returns a field type constant from a signed 128-bit integer, encoded as a two's complement
+    /// 256-bit value.
+    ///
+    pub fn field_const_signed(&self, value: i128) -> inkwell::values::IntValue<'ctx> {
+        let extension = if value < 0 { u64::MAX } else { 0 };
+        self.field_type().const_int_arbitrary_precision(&[
+            value as u64,
+            (value >> 64) as u64,
+            extension,
+            extension,
+        ])
+    }
+
+    ///
+    /// Returns a field type constant from an optionally negative decimal or `0x`-prefixed
+    /// hexadecimal string, encoded as a two's complement 256-bit value.
+    ///
+    pub fn field_const_str_signed(&self, value: &str) -> inkwell::values::IntValue<'ctx> {
+        self.try_field_const_str_signed(value)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    ///
+    /// Returns a field type constant from an optionally negative decimal or `0x`-prefixed
+    /// hexadecimal string, or an error if the string is invalid or the value does not fit into
+    /// the signed 256-bit range.
+    ///
+    pub fn try_field_const_str_signed(
+        &self,
+        value: &str,
+    ) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
+        let (magnitude, is_negative) = match value.strip_prefix('-') {
+            Some(magnitude) => (magnitude, true),
+            None => (value, false),
+        };
+
+        let (digits, radix) = Self::split_radix(magnitude);
+        let number = num::BigUint::parse_bytes(digits.as_bytes(), radix).ok_or_else(|| {
+            self.error(
+                format!("signed constant `{}`", value).as_str(),
+                "invalid literal",
+            )
+        })?;
+        let limit = num::BigUint::from(1u8) << (compiler_common::BITLENGTH_FIELD - 1);
+        if (is_negative && number > limit) || (!is_negative && number >= limit) {
+            return Err(self.error(
+                format!("signed constant `{}`", value).as_str(),
+                format!(
+                    "the value exceeds the signed {}-bit range",
+                    compiler_common::BITLENGTH_FIELD
+                ),
+            ));
+        }
+
+        let magnitude = self.try_field_const_str(magnitude)?;
+        Ok(if is_negative {
+            magnitude.const_neg()
+        } else {
+            magnitude
+        })
+    }
+
+    ///
+    /// Splits a numeric literal into its digits and radix, detected by the `0x` prefix.
+    ///
+    fn split_radix(value: &str) -> (&str, u32) {
+        match value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            Some(hexadecimal) => (hexadecimal, 16),
+            None => (value, 10),
+        }
+    }
+
+    ///
+    /// Returns a field type constant from a decimal string.
     ///
     pub fn field_const_str_dec(&self, value: &str) -> inkwell::values::IntValue<'ctx> {
+        self.try_field_const_str_dec(value)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    ///
+    /// Returns a field type constant from a decimal string, or an error if the string is invalid.
+    ///
+    pub fn try_field_const_str_dec(
+        &self,
+        value: &str,
+    ) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
         self.field_type()
             .const_int_from_string(value, inkwell::types::StringRadix::Decimal)
-            .unwrap_or_else(|| panic!("Invalid string constant `{}`", value))
+            .ok_or_else(|| {
+                self.error(
+                    format!("decimal constant `{}`", value).as_str(),
+                    "invalid literal",
+                )
+            })
     }
 
     ///
     /// Returns a field type constant from a hexadecimal string.
     ///
     pub fn field_const_str_hex(&self, value: &str) -> inkwell::values::IntValue<'ctx> {
+        self.try_field_const_str_hex(value)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    ///
+    /// Returns a field type constant from a hexadecimal string, or an error if the string is
+    /// invalid.
+    ///
+    pub fn try_field_const_str_hex(
+        &self,
+        value: &str,
+    ) -> anyhow::Result<inkwell::values::IntValue<'ctx>> {
         self.field_type()
             .const_int_from_string(
                 value.strip_prefix("0x").unwrap_or(value),
                 inkwell::types::StringRadix::Hexadecimal,
             )
-            .unwrap_or_else(|| panic!("Invalid string constant `{}`", value))
+            .ok_or_else(|| {
+                self.error(
+                    format!("hexadecimal constant `{}`", value).as_str(),
+                    "invalid literal",
+                )
+            })
     }
 
     ///
@@ -1262,6 +3953,14 @@ where
             .get_type()
             .get_return_type();
 
+        let function_name = match self.contract_namespace.as_ref() {
+            Some(namespace) => function_name
+                .strip_prefix(namespace.as_str())
+                .and_then(|name| name.strip_prefix(Self::CONTRACT_NAMESPACE_SEPARATOR))
+                .map(|name| name.to_owned())
+                .unwrap_or(function_name),
+            None => function_name,
+        };
         let return_data_size = self
             .functions
             .get(function_name.as_str())
@@ -1441,4 +4140,772 @@ where
     pub fn set_immutable_size(&mut self, value: usize) {
         self.immutables_size = value;
     }
+
+    ///
+    /// Returns the constructor return data immutables layout.
+    ///
+    pub fn immutables_layout(&self) -> ImmutablesLayout {
+        self.immutables_layout
+    }
+
+    ///
+    /// Sets the constructor return data immutables layout.
+    ///
+    pub fn set_immutables_layout(&mut self, value: ImmutablesLayout) {
+        self.immutables_layout = value;
+    }
+
+    ///
+    /// Returns the auxiliary heap layout.
+    ///
+    pub fn aux_heap_layout(&self) -> AuxHeapLayout {
+        self.aux_heap_layout
+    }
+
+    ///
+    /// Sets the auxiliary heap layout, e.g. for another VM version.
+    ///
+    pub fn set_aux_heap_layout(&mut self, value: AuxHeapLayout) {
+        self.aux_heap_layout = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::values::BasicValue;
+
+    use crate::context::address_space::AddressSpace;
+    use crate::context::attribute::Attribute;
+    use crate::context::code_type::CodeType;
+    use crate::context::function::deploy_code::DeployCode;
+    use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+    use crate::context::function::runtime_code::RuntimeCode;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+    use crate::DummyLLVMWritable;
+    use crate::HookStage;
+    use crate::WriteLLVM;
+
+    #[test]
+    fn llvm_ir_round_trip() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let context = Context::<DummyDependency>::new(&llvm, "round_trip", optimizer, None, vec![]);
+
+        let field_type = context.field_type();
+        let function = context.module().add_function(
+            "identity",
+            field_type.fn_type(&[field_type.into()], false),
+            None,
+        );
+        context
+            .builder()
+            .position_at_end(llvm.append_basic_block(function, "entry"));
+        context
+            .builder()
+            .build_return(Some(&function.get_first_param().expect("Always exists")));
+
+        let printed = context.print_function("identity").expect("Always exists");
+        let module_text = context.module().print_to_string().to_string();
+        let parsed = context
+            .parse_llvm_ir(module_text.as_str())
+            .expect("Always valid");
+        let reprinted = parsed
+            .get_function("identity")
+            .expect("Always exists")
+            .print_to_string()
+            .to_string();
+        assert_eq!(printed, reprinted);
+    }
+
+    #[test]
+    fn alloca_hoisting() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "alloca_hoisting", optimizer, None, vec![]);
+
+        context.add_function("event_loop", context.function_type(0, vec![]), None);
+        let function = context
+            .functions
+            .get("event_loop")
+            .cloned()
+            .expect("Always exists");
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let range_start = context.field_const(0);
+        let length = context.field_const((compiler_common::SIZE_FIELD * 3) as u64);
+        crate::evm::event::data_loop(&mut context, range_start, length).expect("Always valid");
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+
+        let function = context.function().value;
+        let entry_block = function.get_first_basic_block().expect("Always exists");
+        for block in function.get_basic_blocks().into_iter() {
+            let mut instruction = block.get_first_instruction();
+            while let Some(current) = instruction {
+                if current.get_opcode() == inkwell::values::InstructionOpcode::Alloca {
+                    assert_eq!(block, entry_block, "The stack allocation is not hoisted");
+                }
+                instruction = current.get_next_instruction();
+            }
+        }
+    }
+
+    #[test]
+    fn code_type_mixed_translation_order() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "code_type", optimizer, None, vec![]);
+
+        for name in ["deploy_helper", "runtime_helper", "shared_helper"].into_iter() {
+            context.add_function(name, context.function_type(0, vec![]), None);
+        }
+        context.set_function_code_type("deploy_helper", CodeType::Deploy);
+        context.set_function_code_type("runtime_helper", CodeType::Runtime);
+        context.set_code_type(CodeType::Deploy);
+
+        let runtime_helper = context.functions["runtime_helper"].clone();
+        context.set_function(runtime_helper);
+        assert_eq!(context.code_type(), CodeType::Runtime);
+        context.set_basic_block(context.function().entry_block);
+        context.build_exit(
+            IntrinsicFunction::Return,
+            context.field_const(0),
+            context.field_const(0),
+        );
+
+        let shared_helper = context.functions["shared_helper"].clone();
+        context.set_function(shared_helper);
+        assert_eq!(context.code_type(), CodeType::Deploy);
+
+        let deploy_helper = context.functions["deploy_helper"].clone();
+        context.set_function(deploy_helper);
+        assert_eq!(context.code_type(), CodeType::Deploy);
+        context.set_basic_block(context.function().entry_block);
+        context.build_exit(
+            IntrinsicFunction::Return,
+            context.field_const(0),
+            context.field_const(0),
+        );
+
+        let marker = "contract_exit_abi_data_add_heap_auxiliary_marker";
+        assert!(!context
+            .print_function("runtime_helper")
+            .expect("Always exists")
+            .contains(marker));
+        assert!(context
+            .print_function("deploy_helper")
+            .expect("Always exists")
+            .contains(marker));
+    }
+
+    #[test]
+    fn const_u64_wide() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let context = Context::<DummyDependency>::new(&llvm, "const_u64", optimizer, None, vec![]);
+
+        assert_eq!(context.const_u64(context.field_const(42)), Some(42));
+        assert_eq!(
+            context.const_u64(context.field_const_str_hex("10000000000000000")),
+            None
+        );
+        assert_eq!(
+            context.const_u64(context.field_const_str_hex("10000000000000005")),
+            None
+        );
+        assert!(!context.field_const_str_hex("10000000000000000").is_null());
+    }
+
+    #[test]
+    fn revert_string_code_page() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "revert_string", optimizer, None, vec![]);
+
+        context.add_function("revert_string", context.function_type(0, vec![]), None);
+        let function = context.functions["revert_string"].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        context.build_revert_error_string("Ownable: caller is not the owner");
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let global = context.declare_revert_string("Ownable: caller is not the owner");
+        assert_eq!(
+            global.as_pointer_value().get_type().get_address_space(),
+            AddressSpace::Code.into()
+        );
+        assert!(global.is_constant());
+        assert!(global.get_initializer().is_some());
+        assert_eq!(
+            global
+                .as_pointer_value()
+                .get_type()
+                .get_element_type()
+                .into_array_type()
+                .len(),
+            4
+        );
+
+        let printed = context
+            .print_function("revert_string")
+            .expect("Always exists");
+        assert!(printed.contains("load i256, i256 addrspace(4)*"));
+        assert!(!printed.contains("store i256 %revert_string_word, i256 addrspace(4)*"));
+    }
+
+    #[test]
+    fn fat_ptr_field() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "fat_ptr_field", optimizer, None, vec![]);
+
+        let pointer_type = context
+            .integer_type(compiler_common::BITLENGTH_BYTE)
+            .ptr_type(AddressSpace::Generic.into());
+        context.add_function(
+            "fat_ptr_field",
+            context.function_type(0, vec![pointer_type.into()]),
+            None,
+        );
+        let function = context.functions["fat_ptr_field"].clone();
+        let pointer = function
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_pointer_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        context.write_abi_calldata(pointer);
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let printed = context
+            .print_function("fat_ptr_field")
+            .expect("Always exists");
+        let getter = IntrinsicFunction::PointerLength.name();
+        if context
+            .try_get_intrinsic_function(IntrinsicFunction::PointerLength)
+            .is_ok()
+        {
+            assert!(printed.contains(format!("call i256 @{}", getter).as_str()));
+            assert!(!printed.contains("lshr"));
+        } else {
+            assert!(printed.contains("lshr i256 %fat_pointer_value_abi_length_value, 96"));
+            assert!(printed.contains("trunc i256"));
+        }
+    }
+
+    #[test]
+    fn fat_ptr_int_round_trip() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(
+            &llvm,
+            "fat_ptr_int_round_trip",
+            optimizer,
+            None,
+            vec![],
+        );
+
+        context.add_function(
+            "fat_ptr_int_round_trip",
+            context.function_type(0, vec![context.field_type().into()]),
+            None,
+        );
+        let function = context.functions["fat_ptr_int_round_trip"].clone();
+        let value = function
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_int_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let pointer = context.int_to_fat_ptr(value, "pointer");
+        assert_eq!(
+            pointer.get_type().get_address_space(),
+            AddressSpace::Generic.into()
+        );
+        let value = context.fat_ptr_to_int(pointer, "value");
+        assert_eq!(value.get_type(), context.field_type());
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let printed = context
+            .print_function("fat_ptr_int_round_trip")
+            .expect("Always exists");
+        for (intrinsic, instruction) in [
+            (IntrinsicFunction::IntToPointer, "inttoptr"),
+            (IntrinsicFunction::PointerToInt, "ptrtoint"),
+        ] {
+            if context.try_get_intrinsic_function(intrinsic).is_ok() {
+                assert!(printed.contains(format!("@{}", intrinsic.name()).as_str()));
+            } else {
+                assert!(printed.contains(format!("{} i", instruction).as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn callee_check() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "callee_check", optimizer, None, vec![]);
+
+        context.enter_contract("Library");
+        context.add_function("library_function", context.function_type(0, vec![]), None);
+        let library_function = context.functions["library_function"].value;
+        context.enter_contract("Contract");
+        context.add_function("caller", context.function_type(0, vec![]), None);
+        context.add_function("declared", context.function_type(0, vec![]), None);
+        let declared = context.functions["declared"].value;
+        let undeclared = context.module().add_function(
+            "Contract.undeclared",
+            context.function_type(0, vec![]),
+            None,
+        );
+        let caller = context.functions["caller"].clone();
+        context.set_function(caller);
+        context.set_basic_block(context.function().entry_block);
+
+        assert!(context
+            .try_build_call(declared, &[], "declared_call")
+            .is_ok());
+        assert!(context
+            .try_build_call(library_function, &[], "library_function_call")
+            .is_ok());
+        assert!(context
+            .try_build_call(
+                context.get_intrinsic_function(IntrinsicFunction::CodeSource),
+                &[],
+                "code_source_call",
+            )
+            .is_ok());
+        let error = context
+            .try_build_invoke(undeclared, &[], "undeclared_call")
+            .expect_err("Always invalid");
+        assert!(error.to_string().contains("Contract.undeclared"));
+    }
+
+    #[test]
+    fn hooks_code_stages() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "hooks_code_stages", optimizer, None, vec![]);
+
+        let calls = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        for stage in [HookStage::BeforeTranslation, HookStage::AfterTranslation] {
+            let calls = calls.clone();
+            context.add_hook(stage, move |context, subject| {
+                if stage == HookStage::BeforeTranslation {
+                    context.write_llvm(DummyLLVMWritable::default())?;
+                }
+                calls
+                    .borrow_mut()
+                    .push((stage, context.code_type(), subject.to_owned()));
+                Ok(())
+            });
+        }
+
+        let mut deploy_code = DeployCode::new(DummyLLVMWritable::default());
+        let mut runtime_code = RuntimeCode::new(DummyLLVMWritable::default());
+        deploy_code.declare(&mut context).expect("Always valid");
+        runtime_code.declare(&mut context).expect("Always valid");
+        deploy_code.into_llvm(&mut context).expect("Always valid");
+        runtime_code.into_llvm(&mut context).expect("Always valid");
+        assert!(context.verify().is_ok());
+
+        let subject = std::any::type_name::<DummyLLVMWritable>().to_owned();
+        assert_eq!(
+            calls.take(),
+            vec![
+                (
+                    HookStage::BeforeTranslation,
+                    CodeType::Deploy,
+                    subject.clone()
+                ),
+                (
+                    HookStage::AfterTranslation,
+                    CodeType::Deploy,
+                    subject.clone()
+                ),
+                (
+                    HookStage::BeforeTranslation,
+                    CodeType::Runtime,
+                    subject.clone()
+                ),
+                (HookStage::AfterTranslation, CodeType::Runtime, subject),
+            ]
+        );
+    }
+
+    #[test]
+    fn kernel_only_allocas() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "kernel_only_allocas", optimizer, None, vec![]);
+
+        context.add_function(
+            "kernel_only_allocas",
+            context.function_type(0, vec![]),
+            None,
+        );
+        let function = context.functions["kernel_only_allocas"].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let before = context.build_alloca(context.field_type(), "before");
+        context
+            .set_function_kernel_only("kernel_only_allocas")
+            .expect("Always valid");
+        let after = context.build_alloca(context.field_type(), "after");
+        context.build_store(before, context.field_const(1));
+        context.build_store(after, context.field_const(2));
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let function = context.functions["kernel_only_allocas"].value;
+        let first_block = function.get_first_basic_block().expect("Always exists");
+        assert_eq!(
+            first_block.get_name().to_string_lossy(),
+            "kernel_check_block"
+        );
+        for block in function.get_basic_blocks() {
+            let mut instruction = block.get_first_instruction();
+            while let Some(current) = instruction {
+                if current.get_opcode() == inkwell::values::InstructionOpcode::Alloca {
+                    assert_eq!(current.get_parent(), Some(first_block));
+                }
+                instruction = current.get_next_instruction();
+            }
+        }
+        for pointer in [before, after] {
+            assert_eq!(
+                pointer
+                    .as_instruction_value()
+                    .and_then(|instruction| instruction.get_parent()),
+                Some(first_block)
+            );
+        }
+    }
+
+    #[test]
+    fn discard_value_names() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let is_discarded =
+            || unsafe { llvm_sys::core::LLVMContextShouldDiscardValueNames(llvm.raw()) } != 0;
+
+        let mut settings = OptimizerSettings::none();
+        settings.discard_value_names = true;
+        let optimizer = Optimizer::new(settings).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "discard_value_names", optimizer, None, vec![]);
+        assert!(is_discarded());
+        context.set_names_discarded(false);
+        assert!(!is_discarded());
+        context.set_names_discarded(true);
+        assert!(is_discarded());
+        drop(context);
+        assert!(!is_discarded());
+
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let context =
+            Context::<DummyDependency>::new(&llvm, "keep_value_names", optimizer, None, vec![]);
+        assert!(!is_discarded());
+        drop(context);
+    }
+
+    #[test]
+    fn new_pass_manager_inliner_disabled() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let mut settings = OptimizerSettings::size();
+        settings.is_new_pass_manager_enabled = true;
+        settings.is_inliner_enabled = false;
+        let optimizer = Optimizer::new(settings).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(
+            &llvm,
+            "new_pass_manager_inliner_disabled",
+            optimizer,
+            None,
+            vec![],
+        );
+
+        context.add_function("callee", context.function_type(0, vec![]), None);
+        context.add_function("caller", context.function_type(0, vec![]), None);
+        let callee = context.functions["callee"].clone();
+        let caller = context.functions["caller"].clone();
+
+        context.set_function(callee.clone());
+        context.set_basic_block(callee.entry_block);
+        let pointer = context.access_memory(context.field_const(0), AddressSpace::Heap, "pointer");
+        context.build_store(pointer, context.field_const(1));
+        context.build_unconditional_branch(callee.return_block);
+        context.set_basic_block(callee.return_block);
+        context.build_return(None);
+
+        context.set_function(caller.clone());
+        context.set_basic_block(caller.entry_block);
+        context.build_call(callee.value, &[], "callee_call");
+        context.build_unconditional_branch(caller.return_block);
+        context.set_basic_block(caller.return_block);
+        context.build_return(None);
+
+        context.optimize().expect("Always valid");
+        assert!(context.verify().is_ok());
+        assert!(context
+            .print_function("caller")
+            .expect("Always exists")
+            .contains("@callee"));
+        assert!(callee
+            .value
+            .get_enum_attribute(
+                inkwell::attributes::AttributeLoc::Function,
+                Attribute::NoInline as u32,
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn fork_functions() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "fork_functions", optimizer, None, vec![]);
+
+        context.enter_contract("Library");
+        context.add_function("library_function", context.function_type(0, vec![]), None);
+        context.enter_contract("Contract");
+        context.add_function("declared", context.function_type(0, vec![]), None);
+
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let fork = context.fork(optimizer);
+        assert_eq!(fork.contract_namespace(), Some("Contract"));
+
+        let declared = &fork.functions["declared"];
+        assert_eq!(
+            Some(declared.value),
+            fork.module().get_function("Contract.declared")
+        );
+        assert_ne!(declared.value, context.functions["declared"].value);
+        assert_eq!(
+            declared.value.get_first_basic_block(),
+            Some(declared.entry_block)
+        );
+        assert_eq!(
+            declared.value.get_last_basic_block(),
+            Some(declared.return_block)
+        );
+
+        let library_function = &fork.namespaced_functions["Library"]["library_function"];
+        assert_eq!(
+            Some(library_function.value),
+            fork.module().get_function("Library.library_function")
+        );
+        assert!(fork.check_callee(declared.value).is_ok());
+    }
+
+    #[test]
+    fn library_deferred() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "library_deferred", optimizer, None, vec![]);
+
+        context.add_function("library_deferred", context.function_type(0, vec![]), None);
+        let function = context.functions["library_deferred"].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        context.resolve_library_deferred("contracts/Math.sol:Math");
+        context.resolve_library_deferred("contracts/Math.sol:Math");
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let name = format!(
+            "{}{}",
+            crate::r#const::GLOBAL_LINKER_SYMBOL_PREFIX,
+            crate::hashes::keccak256("contracts/Math.sol:Math".as_bytes())
+        );
+        let global = context
+            .module()
+            .get_global(name.as_str())
+            .expect("Always exists");
+        assert!(global.is_declaration());
+        assert!(global.get_initializer().is_none());
+        assert_eq!(global.get_linkage(), inkwell::module::Linkage::External);
+        assert!(global
+            .print_to_string()
+            .to_string()
+            .contains(format!("@{} = external", name).as_str()));
+        assert_eq!(
+            context
+                .linker_symbols
+                .get(name.as_str())
+                .map(String::as_str),
+            Some("contracts/Math.sol:Math")
+        );
+        assert_eq!(context.linker_symbols.len(), 1);
+    }
+
+    #[test]
+    fn selector_table() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let mut settings = OptimizerSettings::none();
+        settings.selector_table_threshold = Some(3);
+        let optimizer = Optimizer::new(settings).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "selector_table", optimizer, None, vec![]);
+
+        context.add_function(
+            "selector_table",
+            context.function_type(0, vec![context.field_type().into()]),
+            None,
+        );
+        let function = context.functions["selector_table"].clone();
+        let selector = function
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_int_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let selectors = [0xa9059cbb_u32, 0x095ea7b3, 0x70a08231];
+        let cases: Vec<_> = selectors
+            .iter()
+            .map(|selector| {
+                let block = context.append_basic_block(format!("case_{:08x}", selector).as_str());
+                (*selector, block)
+            })
+            .collect();
+        context
+            .build_selector_dispatch(selector, context.function().return_block, cases.as_slice())
+            .expect("Always valid");
+        for (_selector, block) in cases.iter() {
+            context.set_basic_block(*block);
+            context.build_unconditional_branch(context.function().return_block);
+        }
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        let table = std::iter::successors(context.module().get_first_global(), |global| {
+            global.get_next_global()
+        })
+        .find(|global| {
+            global
+                .get_name()
+                .to_string_lossy()
+                .starts_with(crate::r#const::GLOBAL_SELECTOR_TABLE_PREFIX)
+        })
+        .expect("Always exists");
+        assert_eq!(
+            table.as_pointer_value().get_type().get_address_space(),
+            AddressSpace::Code.into()
+        );
+        let table = table.print_to_string().to_string();
+        let positions: Vec<usize> = [0x095ea7b3_u64, 0x70a08231, 0xa9059cbb]
+            .iter()
+            .map(|selector| {
+                table
+                    .find(format!("i256 {}", selector).as_str())
+                    .expect("Always exists")
+            })
+            .collect();
+        assert!(positions.windows(2).all(|window| window[0] < window[1]));
+
+        let printed = context
+            .print_function("selector_table")
+            .expect("Always exists");
+        assert!(printed.contains("%selector_table_middle = lshr i256 %selector_table_sum, 1"));
+        assert!(printed.contains("%selector_table_entry = load i256, i256 addrspace(4)*"));
+        assert!(printed.contains("switch i256 %selector_table_middle"));
+        assert!(!printed.contains("switch i256 %0"));
+    }
+
+    #[test]
+    fn jump_table_terminated() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "jump_table", optimizer, None, vec![]);
+
+        context.add_function("jump_table", context.function_type(0, vec![]), None);
+        let function = context.functions["jump_table"].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let case_block = context.append_basic_block("case_block");
+        let cases = [(context.field_const(1), case_block)];
+        context
+            .build_jump_table(
+                context.field_const(0),
+                context.function().return_block,
+                &cases,
+                false,
+            )
+            .expect("Always valid");
+        assert!(context
+            .build_jump_table(
+                context.field_const(0),
+                context.function().return_block,
+                &cases,
+                false,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn selector_table_duplicate() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "selector_duplicate", optimizer, None, vec![]);
+
+        context.add_function("selector_duplicate", context.function_type(0, vec![]), None);
+        let function = context.functions["selector_duplicate"].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let block = context.append_basic_block("case");
+        assert!(context
+            .build_selector_dispatch(
+                context.field_const(0),
+                context.function().return_block,
+                &[(1, block), (1, block)],
+            )
+            .is_err());
+    }
 }
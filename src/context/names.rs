@@ -0,0 +1,157 @@
+//!
+//! The LLVM IR value and block names interner.
+//!
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+///
+/// The LLVM IR value and block names interner.
+///
+/// The names which are composed at runtime are formatted only once and reused afterwards.
+/// In the discarding mode all names are replaced with empty strings, and the LLVM context is
+/// told to discard the names of the values, so LLVM does not have to store and uniquify them.
+///
+/// The LLVM context flag is restored when the interner is dropped, so the interners sharing
+/// an LLVM context must be dropped in the reverse order.
+///
+#[derive(Debug)]
+pub struct Names {
+    /// The LLVM context the names are discarded in.
+    llvm: llvm_sys::prelude::LLVMContextRef,
+    /// The LLVM context names discarding flag set before.
+    was_discarded: bool,
+    /// Whether the names are replaced with empty strings.
+    is_discarded: bool,
+    /// The interned names with a numeric suffix.
+    indexed: RefCell<HashMap<&'static str, HashMap<usize, Rc<str>>>>,
+    /// The interned names with a string suffix.
+    suffixed: RefCell<HashMap<&'static str, HashMap<String, Rc<str>>>>,
+    /// The empty name, returned in the discarding mode.
+    empty: Rc<str>,
+    /// The next unique identifier.
+    next_id: Cell<usize>,
+}
+
+impl Names {
+    ///
+    /// A shortcut constructor.
+    ///
+    /// Sets the discarding mode of the `llvm` context, saving the previous one.
+    ///
+    pub fn new(llvm: &inkwell::context::Context, is_discarded: bool) -> Self {
+        let llvm = llvm.raw();
+        let was_discarded =
+            unsafe { llvm_sys::core::LLVMContextShouldDiscardValueNames(llvm) } != 0;
+        let mut names = Self {
+            llvm,
+            was_discarded,
+            is_discarded,
+            indexed: RefCell::new(HashMap::new()),
+            suffixed: RefCell::new(HashMap::new()),
+            empty: Rc::from(""),
+            next_id: Cell::new(0),
+        };
+        names.set_discarded(is_discarded);
+        names
+    }
+
+    ///
+    /// Whether the names are replaced with empty strings.
+    ///
+    pub fn is_discarded(&self) -> bool {
+        self.is_discarded
+    }
+
+    ///
+    /// Sets the discarding mode, both for the interner and the LLVM context.
+    ///
+    pub fn set_discarded(&mut self, is_discarded: bool) {
+        self.is_discarded = is_discarded;
+        unsafe {
+            llvm_sys::core::LLVMContextSetDiscardValueNames(
+                self.llvm,
+                is_discarded as llvm_sys::prelude::LLVMBool,
+            );
+        }
+    }
+
+    ///
+    /// Returns the static `name`, or an empty string in the discarding mode.
+    ///
+    pub fn get<'a>(&self, name: &'a str) -> &'a str {
+        if self.is_discarded {
+            ""
+        } else {
+            name
+        }
+    }
+
+    ///
+    /// Returns the interned `{prefix}_{index}` name.
+    ///
+    pub fn indexed(&self, prefix: &'static str, index: usize) -> Rc<str> {
+        if self.is_discarded {
+            return self.empty.clone();
+        }
+
+        self.indexed
+            .borrow_mut()
+            .entry(prefix)
+            .or_default()
+            .entry(index)
+            .or_insert_with(|| Rc::from(format!("{}_{}", prefix, index)))
+            .clone()
+    }
+
+    ///
+    /// Returns the next unique identifier.
+    ///
+    /// The identifiers are allocated sequentially, so they are deterministic for the same
+    /// translation order. They are allocated in the discarding mode as well.
+    ///
+    pub fn next_id(&self) -> usize {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    ///
+    /// Returns the `{prefix}_{id}` name with the next unique identifier.
+    ///
+    pub fn unique(&self, prefix: &'static str) -> Rc<str> {
+        let id = self.next_id();
+        self.indexed(prefix, id)
+    }
+
+    ///
+    /// Returns the interned `{prefix}_{suffix}` name.
+    ///
+    pub fn suffixed(&self, prefix: &'static str, suffix: &str) -> Rc<str> {
+        if self.is_discarded {
+            return self.empty.clone();
+        }
+
+        let mut suffixed = self.suffixed.borrow_mut();
+        let names = suffixed.entry(prefix).or_default();
+        if let Some(name) = names.get(suffix) {
+            return name.clone();
+        }
+        let name: Rc<str> = Rc::from(format!("{}_{}", prefix, suffix));
+        names.insert(suffix.to_owned(), name.clone());
+        name
+    }
+}
+
+impl Drop for Names {
+    fn drop(&mut self) {
+        unsafe {
+            llvm_sys::core::LLVMContextSetDiscardValueNames(
+                self.llvm,
+                self.was_discarded as llvm_sys::prelude::LLVMBool,
+            );
+        }
+    }
+}
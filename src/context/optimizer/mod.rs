@@ -4,6 +4,8 @@
 
 pub mod settings;
 
+use crate::context::attribute::Attribute;
+
 use self::settings::Settings;
 
 ///
@@ -32,6 +34,9 @@ impl<'ctx> Optimizer<'ctx> {
     /// The actual production VM name.
     pub const VM_PRODUCTION_NAME: &'static str = "zkEVM";
 
+    /// Whether the loops are unrolled by the middle-end, which is never profitable for size.
+    const IS_LOOP_UNROLLING_ENABLED: bool = false;
+
     ///
     /// A shortcut constructor.
     ///
@@ -70,6 +75,10 @@ impl<'ctx> Optimizer<'ctx> {
         module.set_triple(&self.target_machine.get_triple());
         module.set_data_layout(&self.target_machine.get_target_data().get_data_layout());
 
+        if self.settings.is_new_pass_manager_enabled {
+            return;
+        }
+
         let pass_manager_builder = inkwell::passes::PassManagerBuilder::create();
         let size_level: u32 = self.settings.level_middle_end_size.into();
         if size_level > 0 {
@@ -80,7 +89,7 @@ impl<'ctx> Optimizer<'ctx> {
         if self.settings.is_inliner_enabled {
             pass_manager_builder.set_inliner_with_threshold(i32::MAX as u32);
         }
-        pass_manager_builder.set_disable_unroll_loops(true);
+        pass_manager_builder.set_disable_unroll_loops(!Self::IS_LOOP_UNROLLING_ENABLED);
 
         let pass_manager_module = inkwell::passes::PassManager::create(());
         self.target_machine
@@ -106,13 +115,27 @@ impl<'ctx> Optimizer<'ctx> {
     ///
     /// Runs the optimizations on `module`.
     ///
-    /// Only returns `true` if any of the passes modified the module.
+    /// Only returns `true` if any of the passes modified the module. The new pass manager does
+    /// not report modifications, so `true` is always returned in this mode.
+    ///
+    /// The `skipped` functions are only excluded from the new pass manager pipeline, since the
+    /// legacy one runs the function passes separately with `run_on_function`.
     ///
-    pub fn run_on_module(&self, module: &inkwell::module::Module<'ctx>) -> bool {
-        self.pass_manager_module
+    pub fn run_on_module(
+        &self,
+        module: &inkwell::module::Module<'ctx>,
+        skipped: &[inkwell::values::FunctionValue<'ctx>],
+    ) -> anyhow::Result<bool> {
+        if self.settings.is_new_pass_manager_enabled {
+            self.run_pipeline(module, skipped)?;
+            return Ok(true);
+        }
+
+        Ok(self
+            .pass_manager_module
             .as_ref()
             .expect("The module has not been set")
-            .run_on(module)
+            .run_on(module))
     }
 
     ///
@@ -120,13 +143,88 @@ impl<'ctx> Optimizer<'ctx> {
     ///
     /// Only returns `true` if any of the passes modified the function.
     ///
+    /// Does nothing with the new pass manager, since the module pipeline also includes the
+    /// function passes.
+    ///
     pub fn run_on_function(&self, function: inkwell::values::FunctionValue<'ctx>) -> bool {
+        if self.settings.is_new_pass_manager_enabled {
+            return false;
+        }
+
         self.pass_manager_function
             .as_ref()
             .expect("The module has not been set")
             .run_on(&function)
     }
 
+    ///
+    /// Runs the new pass manager pipeline on `module`.
+    ///
+    /// The default pipelines cannot be configured like the legacy pass manager builder, so
+    /// the `skipped` functions are temporarily marked with `optnone`, and the other functions
+    /// are temporarily marked with `noinline` if the inliner is disabled. The functions already
+    /// marked with `alwaysinline` are left as is, since the attributes are incompatible.
+    ///
+    fn run_pipeline(
+        &self,
+        module: &inkwell::module::Module<'ctx>,
+        skipped: &[inkwell::values::FunctionValue<'ctx>],
+    ) -> anyhow::Result<()> {
+        let llvm = module.get_context();
+        let mut added = Vec::new();
+        let mut function = module.get_first_function();
+        while let Some(current) = function {
+            function = current.get_next_function();
+
+            let has_attribute = |attribute: Attribute| {
+                current
+                    .get_enum_attribute(
+                        inkwell::attributes::AttributeLoc::Function,
+                        attribute as u32,
+                    )
+                    .is_some()
+            };
+            if current.count_basic_blocks() == 0 || has_attribute(Attribute::AlwaysInline) {
+                continue;
+            }
+
+            let attributes = if skipped.contains(&current) {
+                vec![Attribute::NoInline, Attribute::OptimizeNone]
+            } else if !self.settings.is_inliner_enabled {
+                vec![Attribute::NoInline]
+            } else {
+                vec![]
+            };
+            for attribute in attributes.into_iter() {
+                if has_attribute(attribute) {
+                    continue;
+                }
+                current.add_attribute(
+                    inkwell::attributes::AttributeLoc::Function,
+                    llvm.create_enum_attribute(attribute as u32, 0),
+                );
+                added.push((current.get_name().to_string_lossy().to_string(), attribute));
+            }
+        }
+
+        let pipeline = self.settings.pipeline();
+        let options = inkwell::passes::PassBuilderOptions::create();
+        options.set_loop_unrolling(Self::IS_LOOP_UNROLLING_ENABLED);
+        let result = module
+            .run_passes(pipeline.as_str(), &self.target_machine, options)
+            .map_err(|error| anyhow::anyhow!("The LLVM pipeline `{}` error: {}", pipeline, error));
+
+        for (name, attribute) in added.into_iter() {
+            if let Some(function) = module.get_function(name.as_str()) {
+                function.remove_enum_attribute(
+                    inkwell::attributes::AttributeLoc::Function,
+                    attribute as u32,
+                );
+            }
+        }
+        result
+    }
+
     ///
     /// Returns the target machine reference.
     ///
@@ -0,0 +1,22 @@
+//!
+//! The legacy EVM instructions compatibility mode.
+//!
+
+///
+/// The legacy EVM instructions compatibility mode.
+///
+/// Defines how the EVM instructions without a zkEVM counterpart, e.g. `pc`, are translated.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatibilityMode {
+    /// The instructions are rejected with a translation error.
+    Strict,
+    /// The instructions are translated to a constant zero.
+    Permissive,
+}
+
+impl Default for CompatibilityMode {
+    fn default() -> Self {
+        Self::Strict
+    }
+}
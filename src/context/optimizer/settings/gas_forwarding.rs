@@ -0,0 +1,26 @@
+//!
+//! The far call gas forwarding policy.
+//!
+
+///
+/// The far call gas forwarding policy.
+///
+/// Defines how the gas requested by the EVM `call(gas, ...)` family is mapped to the ergs
+/// forwarded in the far call ABI data.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasForwarding {
+    /// The requested gas is forwarded as is.
+    Requested,
+    /// The requested gas is capped with all but one 64th of the remaining ergs, like in EVM.
+    AllButOne64th,
+    /// The specified stipend is forwarded regardless of the requested gas.
+    /// Also overrides the EVM stipend of the Ether transfers, which may allow the reentrancy.
+    Stipend(u32),
+}
+
+impl Default for GasForwarding {
+    fn default() -> Self {
+        Self::Requested
+    }
+}
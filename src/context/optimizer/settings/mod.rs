@@ -2,10 +2,19 @@
 //! The LLVM optimizer settings.
 //!
 
+pub mod compatibility_mode;
+pub mod gas_forwarding;
+pub mod register_pressure;
 pub mod size_level;
 
 use itertools::Itertools;
 
+use crate::context::attribute::Attribute;
+use crate::context::coverage::CoverageMode;
+
+use self::compatibility_mode::CompatibilityMode;
+use self::gas_forwarding::GasForwarding;
+use self::register_pressure::RegisterPressure;
 use self::size_level::SizeLevel;
 
 ///
@@ -21,9 +30,92 @@ pub struct Settings {
     pub is_inliner_enabled: bool,
     /// The back-end optimization level.
     pub level_back_end: inkwell::OptimizationLevel,
+    /// Whether to cache the system context getter results in globals.
+    /// Disabled by default to preserve the strict semantics of each request.
+    pub is_system_request_memoization_enabled: bool,
+    /// Whether to discard the LLVM IR value and block names.
+    /// Speeds up the compilation of large contracts, but makes the IR dumps less readable.
+    pub discard_value_names: bool,
+    /// Whether to collect the LLVM optimization remarks into the build.
+    /// The remarks emission is enabled via the process-wide LLVM options, which must not be set
+    /// while other threads are running LLVM passes, so the first context with the remarks enabled
+    /// must be created before the parallel compilation starts. Once enabled, the remarks are
+    /// emitted in every LLVM context, and the ones without a handler registered by a `Context`,
+    /// e.g. created by other crates, print them to the standard error. The `Context`s sharing an
+    /// LLVM context must be dropped in the reverse order of their creation, since each of them
+    /// restores the diagnostic handler registered before it.
+    pub is_remarks_enabled: bool,
+    /// Whether to check the front-end assertions at runtime.
+    /// Only enabled in the debug profile, otherwise the assertions are passed to the optimizer
+    /// as assumptions.
+    pub is_assertions_enabled: bool,
+    /// The attributes applied to every function declared by the front-end.
+    pub function_attributes: Vec<Attribute>,
+    /// The default far call gas forwarding policy.
+    pub gas_forwarding: GasForwarding,
+    /// Whether to warn about the heap accesses with unvalidated offsets derived from calldata.
+    pub is_memory_growth_analysis_enabled: bool,
+    /// The code coverage instrumentation mode, if enabled.
+    pub coverage: Option<CoverageMode>,
+    /// Whether to redirect the far calls to the `__test_dispatch` runtime function declared by
+    /// the front-end, so the external calls can be mocked in a test harness.
+    pub is_far_call_mocking_enabled: bool,
+    /// Whether to use the LLVM new pass manager with the default pipelines instead of the legacy
+    /// pass manager builder. The inliner is disabled by marking the functions with `noinline`
+    /// for the duration of the pipeline, and its threshold cannot be configured in this mode.
+    pub is_new_pass_manager_enabled: bool,
+    /// Whether to rewrite the returns of the data just copied from a far call result to the
+    /// returns forwarding the result pointer after the optimization.
+    pub is_return_forwarding_enabled: bool,
+    /// Whether the calls to the simulation addresses are translated to the EraVM-specific
+    /// instructions. If disabled, such calls are rejected to keep the contracts portable.
+    pub are_eravm_extensions_enabled: bool,
+    /// The translation mode of the legacy EVM instructions without a zkEVM counterpart.
+    pub compatibility_mode: CompatibilityMode,
+    /// Whether the library addresses are left as the assembly symbols to be linked after
+    /// the compilation, instead of being resolved by the dependency manager.
+    pub is_deferred_linking_enabled: bool,
+    /// Whether to revert if the upper 96 bits of an address operand are not zero, instead of
+    /// silently clearing them like EVM does.
+    pub is_address_check_enabled: bool,
+    /// The heap size the words accessed by the `mload`, `mstore`, and `mstore8` instructions
+    /// must fit into, if set.
+    /// Catches the unbounded offsets in testing builds, which would otherwise silently pay for
+    /// an enormous memory growth.
+    pub heap_offset_limit: Option<u64>,
+    /// Whether to generate the EVM bytecode stub embedding the zkEVM bytecode hash.
+    pub is_evm_stub_enabled: bool,
+    /// The number of the selector dispatch cases starting from which a sorted selector table
+    /// with a binary search is emitted instead of a switch, if set.
+    /// The table is smaller for the contracts with many external functions.
+    pub selector_table_threshold: Option<usize>,
+    /// Whether to remove the unused parameters of the front-end functions before
+    /// the optimization, since the LLVM dead argument elimination is not enabled.
+    pub is_dead_argument_elimination_enabled: bool,
+    /// Whether to retain the unoptimized and optimized textual LLVM IR in the build, so
+    /// the outputs embedding the IR do not require another compilation with the dump flags.
+    pub is_llvm_ir_retained: bool,
+    /// The back-end register pressure policy.
+    pub register_pressure: RegisterPressure,
+    /// The string attributes applied to every function declared by the front-end, e.g.
+    /// the back-end options not covered by the register pressure policy.
+    pub function_string_attributes: Vec<(String, String)>,
+    /// Whether to compute the `keccak256` hashes of the heap regions filled with constants in
+    /// the same basic block at compile time, instead of calling the hashing system contract.
+    pub is_constant_keccak_folding_enabled: bool,
+    /// Whether to merge the identical exit blocks, such as the reverts emitted in many branches,
+    /// after the optimization.
+    pub is_tail_merging_enabled: bool,
 }
 
 impl Settings {
+    /// The default attributes applied to every function declared by the front-end.
+    pub const DEFAULT_FUNCTION_ATTRIBUTES: [Attribute; 3] = [
+        Attribute::NoFree,
+        Attribute::Cold,
+        Attribute::NullPointerIsValid,
+    ];
+
     ///
     /// A shortcut constructor.
     ///
@@ -38,6 +130,30 @@ impl Settings {
             level_middle_end_size,
             is_inliner_enabled,
             level_back_end,
+            is_system_request_memoization_enabled: false,
+            discard_value_names: false,
+            is_remarks_enabled: false,
+            is_assertions_enabled: false,
+            function_attributes: Self::DEFAULT_FUNCTION_ATTRIBUTES.to_vec(),
+            gas_forwarding: GasForwarding::default(),
+            is_memory_growth_analysis_enabled: false,
+            coverage: None,
+            is_far_call_mocking_enabled: false,
+            is_new_pass_manager_enabled: false,
+            is_return_forwarding_enabled: false,
+            are_eravm_extensions_enabled: true,
+            compatibility_mode: CompatibilityMode::default(),
+            is_deferred_linking_enabled: false,
+            is_address_check_enabled: false,
+            heap_offset_limit: None,
+            is_evm_stub_enabled: false,
+            selector_table_threshold: None,
+            is_dead_argument_elimination_enabled: false,
+            is_llvm_ir_retained: false,
+            register_pressure: RegisterPressure::default(),
+            function_string_attributes: Vec::new(),
+            is_constant_keccak_folding_enabled: false,
+            is_tail_merging_enabled: false,
         }
     }
 
@@ -45,12 +161,14 @@ impl Settings {
     /// Returns the settings without optimizations.
     ///
     pub fn none() -> Self {
-        Self::new(
+        let mut settings = Self::new(
             inkwell::OptimizationLevel::None,
             SizeLevel::Zero,
             false,
             inkwell::OptimizationLevel::None,
-        )
+        );
+        settings.is_assertions_enabled = true;
+        settings
     }
 
     ///
@@ -117,6 +235,20 @@ impl Settings {
     }
 }
 
+impl Settings {
+    ///
+    /// Returns the LLVM new pass manager middle-end pipeline, e.g. `default<O3>` or `default<Oz>`.
+    ///
+    pub fn pipeline(&self) -> String {
+        let level = match self.level_middle_end_size {
+            SizeLevel::Zero => (self.level_middle_end as u8).to_string(),
+            SizeLevel::S => 's'.to_string(),
+            SizeLevel::Z => 'z'.to_string(),
+        };
+        format!("default<O{}>", level)
+    }
+}
+
 impl std::fmt::Display for Settings {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -0,0 +1,49 @@
+//!
+//! The back-end register pressure policy.
+//!
+
+use crate::context::attribute::Attribute;
+
+///
+/// The back-end register pressure policy.
+///
+/// Is a mitigation for the register allocation pathologies in huge functions, such as
+/// the excessive spilling or the allocator running out of the compile time budget.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterPressure {
+    /// The back-end defaults.
+    Default,
+    /// The front-end functions are not inlined into each other, so the live ranges do not
+    /// grow beyond the front-end function bodies, and the jump tables are disabled, since
+    /// their address computations extend the live ranges around the large switches.
+    Reduced,
+}
+
+impl RegisterPressure {
+    ///
+    /// Returns the enum attributes applied to every function declared by the front-end.
+    ///
+    pub fn enum_attributes(&self) -> &'static [Attribute] {
+        match self {
+            Self::Default => &[],
+            Self::Reduced => &[Attribute::NoInline],
+        }
+    }
+
+    ///
+    /// Returns the string attributes applied to every function declared by the front-end.
+    ///
+    pub fn string_attributes(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Default => &[],
+            Self::Reduced => &[("no-jump-tables", "true")],
+        }
+    }
+}
+
+impl Default for RegisterPressure {
+    fn default() -> Self {
+        Self::Default
+    }
+}
@@ -0,0 +1,31 @@
+//!
+//! The LLVM module build output format.
+//!
+
+///
+/// The LLVM module build output format.
+///
+/// The assembly is always generated, since the bytecode is assembled from it, whereas the other
+/// formats are only emitted into the build if passed to `Context::finalize`, e.g. for the build
+/// systems caching the intermediate artifacts or linking them later.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// The optimized LLVM bitcode.
+    Bitcode,
+    /// The object file generated by the back-end.
+    /// Is experimental, since the object emission of the back-end has not been verified yet.
+    Object,
+    /// The unoptimized and optimized textual LLVM IR.
+    LLVMIR,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bitcode => write!(f, "bitcode"),
+            Self::Object => write!(f, "object"),
+            Self::LLVMIR => write!(f, "llvm-ir"),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//!
+//! The contract path sanitizer.
+//!
+
+use std::collections::BTreeMap;
+
+///
+/// The contract path sanitizer.
+///
+/// The module names are derived from the contract paths, which may contain characters rejected
+/// by the assembler in symbols. Such characters are replaced, and the sanitized names are mapped
+/// back to the original paths, so the paths reported to the front-end are not changed.
+/// Since the replacement is lossy, the different paths sanitized to the same name are rejected.
+///
+#[derive(Debug, Default, Clone)]
+pub struct PathSanitizer {
+    /// The sanitized-name-to-original-path mapping.
+    originals: BTreeMap<String, String>,
+}
+
+impl PathSanitizer {
+    /// The replacement of the characters rejected in symbols.
+    pub const REPLACEMENT: char = '_';
+
+    ///
+    /// Whether `character` is allowed in the assembly symbols.
+    ///
+    pub fn is_allowed(character: char) -> bool {
+        character.is_ascii_alphanumeric() || ['_', '.'].contains(&character)
+    }
+
+    ///
+    /// Returns the sanitized `path` without registering it.
+    ///
+    pub fn sanitized(path: &str) -> String {
+        path.chars()
+            .map(|character| {
+                if Self::is_allowed(character) {
+                    character
+                } else {
+                    Self::REPLACEMENT
+                }
+            })
+            .collect()
+    }
+
+    ///
+    /// Sanitizes and registers `path`, returning the sanitized name.
+    ///
+    /// Returns an error if another path has been sanitized to the same name.
+    ///
+    pub fn sanitize(&mut self, path: &str) -> anyhow::Result<String> {
+        let name = Self::sanitized(path);
+        match self.originals.get(name.as_str()) {
+            Some(original) if original != path => anyhow::bail!(
+                "The contract paths `{}` and `{}` collide as `{}`",
+                original,
+                path,
+                name
+            ),
+            Some(_) => {}
+            None => {
+                self.originals.insert(name.clone(), path.to_owned());
+            }
+        }
+        Ok(name)
+    }
+
+    ///
+    /// Returns the original path of the sanitized `name`, if it has been registered.
+    ///
+    pub fn original(&self, name: &str) -> Option<&str> {
+        self.originals.get(name).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathSanitizer;
+
+    #[test]
+    fn reversible() {
+        let mut sanitizer = PathSanitizer::default();
+        let path = "contracts/Token.sol:Token";
+
+        let name = sanitizer.sanitize(path).expect("Always valid");
+        assert_eq!(name, "contracts_Token.sol_Token");
+        assert_eq!(sanitizer.original(name.as_str()), Some(path));
+    }
+
+    #[test]
+    fn idempotent() {
+        let mut sanitizer = PathSanitizer::default();
+
+        let first = sanitizer.sanitize("a/B.sol:B").expect("Always valid");
+        let second = sanitizer.sanitize("a/B.sol:B").expect("Always valid");
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn error_collision() {
+        let mut sanitizer = PathSanitizer::default();
+
+        sanitizer.sanitize("a/B.sol:B").expect("Always valid");
+        assert!(sanitizer.sanitize("a:B.sol/B").is_err());
+    }
+}
@@ -0,0 +1,451 @@
+//!
+//! The post-optimization LLVM IR peephole rewrites.
+//!
+
+use inkwell::values::AsValueRef;
+use inkwell::values::BasicValue;
+
+use crate::context::address_space::AddressSpace;
+use crate::context::fat_pointer_field::FatPointerField;
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// Rewrites the returns of the data just copied from a generic page, that is, the
+/// `memcpy(heap[offset], source, length)` immediately followed by `return(offset, length)`,
+/// to the return forwarding the `source` fat pointer, which eliminates the copy.
+///
+/// The forwarding is only possible if the `source` pointer has at least `length` bytes left,
+/// so the check is made at runtime, and the original copying return is kept as the fallback.
+///
+/// Only the ABI data shapes produced by `Context::build_exit` and their usual optimizations are
+/// recognized. Returns the number of the rewritten returns.
+///
+pub fn forward_return_data<'ctx, D>(context: &Context<'ctx, D>) -> usize
+where
+    D: Dependency,
+{
+    let mut candidates = Vec::new();
+    let mut function = context.module().get_first_function();
+    while let Some(current_function) = function {
+        function = current_function.get_next_function();
+
+        for block in current_function.get_basic_blocks().into_iter() {
+            if let Some(candidate) = match_block(context, block) {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    let count = candidates.len();
+    for (memcpy, source, length) in candidates.into_iter() {
+        rewrite(context, memcpy, source, length);
+    }
+    count
+}
+
+///
+/// Matches the memory copy followed by the return at the end of `block`.
+///
+/// Returns the memory copy instruction, the copy source, and the copy length.
+///
+fn match_block<'ctx, D>(
+    context: &Context<'ctx, D>,
+    block: inkwell::basic_block::BasicBlock<'ctx>,
+) -> Option<(
+    inkwell::values::InstructionValue<'ctx>,
+    inkwell::values::PointerValue<'ctx>,
+    inkwell::values::IntValue<'ctx>,
+)>
+where
+    D: Dependency,
+{
+    let terminator = block.get_terminator()?;
+    if terminator.get_opcode() != inkwell::values::InstructionOpcode::Unreachable {
+        return None;
+    }
+    let r#return = terminator.get_previous_instruction()?;
+    if callee_name(r#return)? != IntrinsicFunction::Return.name() {
+        return None;
+    }
+
+    let mut memcpy = r#return.get_previous_instruction()?;
+    while memcpy.get_opcode() != inkwell::values::InstructionOpcode::Call {
+        if matches!(
+            memcpy.get_opcode(),
+            inkwell::values::InstructionOpcode::Load | inkwell::values::InstructionOpcode::Store
+        ) {
+            return None;
+        }
+        memcpy = memcpy.get_previous_instruction()?;
+    }
+    if !callee_name(memcpy)?.starts_with(IntrinsicFunction::MemoryCopyFromGeneric.name()) {
+        return None;
+    }
+
+    let destination = memcpy.get_operand(0)?.left()?.into_pointer_value();
+    let source = memcpy.get_operand(1)?.left()?.into_pointer_value();
+    let length = memcpy.get_operand(2)?.left()?.into_int_value();
+    if destination.get_type().get_address_space() != AddressSpace::Heap.into()
+        || source.get_type().get_address_space() != AddressSpace::Generic.into()
+    {
+        return None;
+    }
+
+    let abi_data = r#return.get_operand(0)?.left()?.into_int_value();
+    let (return_offset, return_length) = match_abi_data(context, abi_data)?;
+    if heap_offset(context, destination)? != return_offset
+        || strip_mask(context, length).as_value_ref() != return_length
+    {
+        return None;
+    }
+
+    Some((memcpy, source, length))
+}
+
+///
+/// Decomposes the return ABI data into the offset and length values.
+///
+fn match_abi_data<'ctx, D>(
+    context: &Context<'ctx, D>,
+    abi_data: inkwell::values::IntValue<'ctx>,
+) -> Option<(
+    llvm_sys::prelude::LLVMValueRef,
+    llvm_sys::prelude::LLVMValueRef,
+)>
+where
+    D: Dependency,
+{
+    let offset_shift = (compiler_common::BITLENGTH_X32 * 2) as u64;
+    let length_shift = (compiler_common::BITLENGTH_X32 * 3) as u64;
+
+    if let Some(length) = shifted_operand(context, abi_data, length_shift) {
+        return Some((context.field_const(0).as_value_ref(), length.as_value_ref()));
+    }
+
+    let instruction = abi_data.as_instruction_value()?;
+    if !matches!(
+        instruction.get_opcode(),
+        inkwell::values::InstructionOpcode::Add | inkwell::values::InstructionOpcode::Or
+    ) {
+        return None;
+    }
+    let operand_1 = instruction.get_operand(0)?.left()?.into_int_value();
+    let operand_2 = instruction.get_operand(1)?.left()?.into_int_value();
+    for (offset, length) in [(operand_1, operand_2), (operand_2, operand_1)].into_iter() {
+        if let (Some(offset), Some(length)) = (
+            shifted_operand(context, offset, offset_shift),
+            shifted_operand(context, length, length_shift),
+        ) {
+            return Some((offset.as_value_ref(), length.as_value_ref()));
+        }
+    }
+    None
+}
+
+///
+/// Returns the heap offset `pointer` has been converted from.
+///
+fn heap_offset<'ctx, D>(
+    context: &Context<'ctx, D>,
+    pointer: inkwell::values::PointerValue<'ctx>,
+) -> Option<llvm_sys::prelude::LLVMValueRef>
+where
+    D: Dependency,
+{
+    if pointer.is_null() {
+        return Some(context.field_const(0).as_value_ref());
+    }
+
+    if let Some(instruction) = pointer.as_instruction_value() {
+        if instruction.get_opcode() != inkwell::values::InstructionOpcode::IntToPtr {
+            return None;
+        }
+        let offset = instruction.get_operand(0)?.left()?.into_int_value();
+        return Some(strip_mask(context, offset).as_value_ref());
+    }
+
+    unsafe {
+        let pointer = pointer.as_value_ref();
+        if llvm_sys::core::LLVMIsAConstantExpr(pointer).is_null()
+            || llvm_sys::core::LLVMGetConstOpcode(pointer) != llvm_sys::LLVMOpcode::LLVMIntToPtr
+        {
+            return None;
+        }
+        Some(llvm_sys::core::LLVMGetOperand(pointer, 0))
+    }
+}
+
+///
+/// Returns the unmasked operand of `value` if it is shifted left by `shift` bits.
+///
+fn shifted_operand<'ctx, D>(
+    context: &Context<'ctx, D>,
+    value: inkwell::values::IntValue<'ctx>,
+    shift: u64,
+) -> Option<inkwell::values::IntValue<'ctx>>
+where
+    D: Dependency,
+{
+    let instruction = value.as_instruction_value()?;
+    if instruction.get_opcode() != inkwell::values::InstructionOpcode::Shl {
+        return None;
+    }
+    let amount = instruction.get_operand(1)?.left()?.into_int_value();
+    if context.const_u64(amount) != Some(shift) {
+        return None;
+    }
+    let operand = instruction.get_operand(0)?.left()?.into_int_value();
+    Some(strip_mask(context, operand))
+}
+
+///
+/// Strips the 32-bit truncating mask applied by `Context::build_exit`.
+///
+fn strip_mask<'ctx, D>(
+    context: &Context<'ctx, D>,
+    value: inkwell::values::IntValue<'ctx>,
+) -> inkwell::values::IntValue<'ctx>
+where
+    D: Dependency,
+{
+    let instruction = match value.as_instruction_value() {
+        Some(instruction) => instruction,
+        None => return value,
+    };
+    if instruction.get_opcode() != inkwell::values::InstructionOpcode::And {
+        return value;
+    }
+    match (
+        instruction
+            .get_operand(0)
+            .and_then(|operand| operand.left()),
+        instruction
+            .get_operand(1)
+            .and_then(|operand| operand.left()),
+    ) {
+        (Some(operand), Some(mask))
+            if context.const_u64(mask.into_int_value()) == Some(u32::MAX as u64) =>
+        {
+            operand.into_int_value()
+        }
+        _ => value,
+    }
+}
+
+///
+/// Returns the name of the function called by `instruction`, if it is a direct call.
+///
+fn callee_name(instruction: inkwell::values::InstructionValue) -> Option<String> {
+    if instruction.get_opcode() != inkwell::values::InstructionOpcode::Call {
+        return None;
+    }
+    let callee = instruction
+        .get_operand(instruction.get_num_operands() - 1)?
+        .left()?
+        .into_pointer_value();
+    Some(callee.get_name().to_string_lossy().into_owned())
+}
+
+///
+/// Moves the memory copy and the return to a fallback block, and branches to the forwarding
+/// return if the `source` pointer has at least `length` bytes left.
+///
+fn rewrite<'ctx, D>(
+    context: &Context<'ctx, D>,
+    memcpy: inkwell::values::InstructionValue<'ctx>,
+    source: inkwell::values::PointerValue<'ctx>,
+    length: inkwell::values::IntValue<'ctx>,
+) where
+    D: Dependency,
+{
+    let block = memcpy.get_parent().expect("Always exists");
+    let forwarding_block = context
+        .llvm
+        .insert_basic_block_after(block, "return_forwarding_block");
+    let copying_block = context
+        .llvm
+        .insert_basic_block_after(forwarding_block, "return_copying_block");
+
+    let mut instructions = Vec::new();
+    let mut instruction = Some(memcpy);
+    while let Some(current) = instruction {
+        instruction = current.get_next_instruction();
+        instructions.push(current);
+    }
+    context.builder().position_at_end(copying_block);
+    for instruction in instructions.into_iter() {
+        instruction.remove_from_basic_block();
+        context.builder().insert_instruction(&instruction, None);
+    }
+
+    context.builder().position_at_end(block);
+    let source = context.builder().build_pointer_cast(
+        source,
+        context
+            .integer_type(compiler_common::BITLENGTH_BYTE)
+            .ptr_type(AddressSpace::Generic.into()),
+        "return_forwarding_source",
+    );
+    let source_offset = context.fat_ptr_field(
+        source,
+        FatPointerField::Offset,
+        "return_forwarding_source_offset",
+    );
+    let source_length = context.fat_ptr_field(
+        source,
+        FatPointerField::Length,
+        "return_forwarding_source_length",
+    );
+    let source_left = context.builder().build_int_sub(
+        source_length,
+        source_offset,
+        "return_forwarding_source_left",
+    );
+    let is_offset_in_bounds = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        source_offset,
+        source_length,
+        "return_forwarding_is_offset_in_bounds",
+    );
+    let is_length_in_bounds = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        length,
+        source_left,
+        "return_forwarding_is_length_in_bounds",
+    );
+    let is_forwardable = context.builder().build_and(
+        is_offset_in_bounds,
+        is_length_in_bounds,
+        "return_forwarding_is_forwardable",
+    );
+    context.build_conditional_branch(is_forwardable, forwarding_block, copying_block);
+
+    context.builder().position_at_end(forwarding_block);
+    let shrink =
+        context
+            .builder()
+            .build_int_sub(source_left, length, "return_forwarding_source_shrink");
+    let source_shrunk = context
+        .build_call(
+            context.get_intrinsic_function(IntrinsicFunction::PointerShrink),
+            &[source.as_basic_value_enum(), shrink.as_basic_value_enum()],
+            "return_forwarding_source_shrunk",
+        )
+        .expect("Always returns a pointer")
+        .into_pointer_value();
+    let abi_data = context.fat_ptr_to_int(source_shrunk, "return_forwarding_abi_data_pointer");
+    let forwarding_marker_shifted = context.builder().build_left_shift(
+        context.field_const(zkevm_opcode_defs::RetForwardPageType::ForwardFatPointer as u64),
+        context.field_const((compiler_common::BITLENGTH_X32 * 7) as u64),
+        "return_forwarding_marker_shifted",
+    );
+    let abi_data = context.builder().build_or(
+        abi_data,
+        forwarding_marker_shifted,
+        "return_forwarding_abi_data",
+    );
+    context.build_call(
+        context.get_intrinsic_function(IntrinsicFunction::Return),
+        &[abi_data.as_basic_value_enum()],
+        "return_forwarding_return",
+    );
+    context.builder().build_unreachable();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::address_space::AddressSpace;
+    use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn build_copying_return(context: &mut Context<DummyDependency>, is_clobbered: bool) {
+        context.add_function(
+            "copying_return",
+            context.function_type(
+                0,
+                vec![
+                    context
+                        .field_type()
+                        .ptr_type(AddressSpace::Generic.into())
+                        .into(),
+                    context.field_type().into(),
+                    context.field_type().into(),
+                ],
+            ),
+            None,
+        );
+        let function = context.functions["copying_return"].clone();
+        let parameters = function.value.get_params();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+
+        let source = parameters[0].into_pointer_value();
+        let offset = parameters[1].into_int_value();
+        let length = parameters[2].into_int_value();
+        let destination = context.access_memory(offset, AddressSpace::Heap, "destination");
+        context.build_memcpy(
+            IntrinsicFunction::MemoryCopyFromGeneric,
+            destination,
+            source,
+            length,
+            "memcpy",
+        );
+        if is_clobbered {
+            let pointer =
+                context.access_memory(context.field_const(0), AddressSpace::Heap, "clobbered");
+            context.build_store(pointer, context.field_const(1));
+        }
+        context.build_exit(IntrinsicFunction::Return, offset, length);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+    }
+
+    #[test]
+    fn forward_return_data() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "peephole", optimizer, None, vec![]);
+        build_copying_return(&mut context, false);
+
+        let before = context
+            .print_function("copying_return")
+            .expect("Always exists");
+        assert!(!before.contains("return_forwarding_block"));
+
+        assert_eq!(super::forward_return_data(&context), 1);
+        let after = context
+            .print_function("copying_return")
+            .expect("Always exists");
+        assert!(after.contains("return_forwarding_block:"));
+        assert!(after.contains("return_copying_block:"));
+        assert!(after.contains("%return_forwarding_abi_data ="));
+        assert_eq!(after.matches("call void @llvm.memcpy").count(), 1);
+        assert!(context.verify().is_ok());
+    }
+
+    #[test]
+    fn forward_return_data_clobbered() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "peephole", optimizer, None, vec![]);
+        build_copying_return(&mut context, true);
+
+        let before = context
+            .print_function("copying_return")
+            .expect("Always exists");
+        assert_eq!(super::forward_return_data(&context), 0);
+        let after = context
+            .print_function("copying_return")
+            .expect("Always exists");
+        assert_eq!(before, after);
+    }
+}
@@ -0,0 +1,174 @@
+//!
+//! The LLVM optimization remarks collector.
+//!
+
+use std::cell::RefCell;
+use std::sync::Once;
+
+///
+/// The LLVM optimization remarks collector.
+///
+/// Registers itself as the LLVM context diagnostic handler, so the collector must stay boxed
+/// while the handler is active. The handler registered before is restored when the collector
+/// is dropped, so the collectors of the contexts sharing an LLVM context must be dropped in
+/// the reverse order.
+///
+/// Unlike the LLVM default handler, a custom one does not abort the compilation on errors, so
+/// the errors are collected as well and must be checked with `take_errors`.
+///
+#[derive(Debug)]
+pub struct Remarks {
+    /// The LLVM context the handler is registered in.
+    llvm: llvm_sys::prelude::LLVMContextRef,
+    /// Whether the remarks are collected, or discarded otherwise.
+    is_collecting: bool,
+    /// The collected remarks in the emission order.
+    messages: RefCell<Vec<String>>,
+    /// The collected errors in the emission order.
+    errors: RefCell<Vec<String>>,
+    /// The handler registered in the LLVM context before.
+    previous_handler: llvm_sys::LLVMDiagnosticHandler,
+    /// The context of the handler registered in the LLVM context before.
+    previous_context: *mut std::os::raw::c_void,
+}
+
+///
+/// The LLVM command line options enabling the applied, missed, and analysis remarks.
+///
+/// The remark filters can only be set process-wide via the LLVM C API, so once set, the remarks
+/// are emitted in every LLVM context and must be discarded by the contexts not collecting them.
+///
+static REMARKS_OPTIONS: Once = Once::new();
+
+impl Remarks {
+    ///
+    /// Enables the remarks emission and registers the collector in the `llvm` context.
+    ///
+    pub fn enable(llvm: &inkwell::context::Context) -> Box<Self> {
+        REMARKS_OPTIONS.call_once(|| {
+            let arguments = [
+                "\0",
+                "-pass-remarks=.*\0",
+                "-pass-remarks-missed=.*\0",
+                "-pass-remarks-analysis=.*\0",
+            ];
+            let arguments: Vec<*const std::os::raw::c_char> = arguments
+                .iter()
+                .map(|argument| argument.as_ptr() as *const std::os::raw::c_char)
+                .collect();
+            unsafe {
+                llvm_sys::support::LLVMParseCommandLineOptions(
+                    arguments.len() as i32,
+                    arguments.as_ptr(),
+                    std::ptr::null(),
+                );
+            }
+        });
+
+        Self::register(llvm, true)
+    }
+
+    ///
+    /// Registers the handler discarding the remarks in the `llvm` context, if the remarks
+    /// emission has been enabled for another context. Otherwise, the LLVM default handler
+    /// is left in place.
+    ///
+    pub fn discard(llvm: &inkwell::context::Context) -> Option<Box<Self>> {
+        if !REMARKS_OPTIONS.is_completed() {
+            return None;
+        }
+
+        Some(Self::register(llvm, false))
+    }
+
+    ///
+    /// Takes the remarks collected so far.
+    ///
+    pub fn take(&self) -> Vec<String> {
+        self.messages.take()
+    }
+
+    ///
+    /// Takes the errors collected so far.
+    ///
+    pub fn take_errors(&self) -> Vec<String> {
+        self.errors.take()
+    }
+
+    ///
+    /// Registers the handler in the `llvm` context, saving the previous one.
+    ///
+    fn register(llvm: &inkwell::context::Context, is_collecting: bool) -> Box<Self> {
+        let llvm = llvm.raw();
+        let (previous_handler, previous_context) = unsafe {
+            (
+                llvm_sys::core::LLVMContextGetDiagnosticHandler(llvm),
+                llvm_sys::core::LLVMContextGetDiagnosticContext(llvm),
+            )
+        };
+        let remarks = Box::new(Self {
+            llvm,
+            is_collecting,
+            messages: RefCell::new(Vec::new()),
+            errors: RefCell::new(Vec::new()),
+            previous_handler,
+            previous_context,
+        });
+        unsafe {
+            llvm_sys::core::LLVMContextSetDiagnosticHandler(
+                remarks.llvm,
+                Some(Self::handle),
+                remarks.as_ref() as *const Self as *mut std::os::raw::c_void,
+            );
+        }
+        remarks
+    }
+
+    ///
+    /// The LLVM diagnostic handler.
+    ///
+    /// Collects or discards the remarks, and collects the errors, since LLVM does not abort
+    /// the compilation on them anymore once a custom handler is registered. The notes and
+    /// warnings are forwarded to the `log` facade, since they are not printed by LLVM either.
+    ///
+    extern "C" fn handle(
+        info: llvm_sys::prelude::LLVMDiagnosticInfoRef,
+        context: *mut std::os::raw::c_void,
+    ) {
+        let remarks = unsafe { &*(context as *const Self) };
+
+        let (severity, message) = unsafe {
+            let severity = llvm_sys::core::LLVMGetDiagInfoSeverity(info);
+            let description = llvm_sys::core::LLVMGetDiagInfoDescription(info);
+            let message = std::ffi::CStr::from_ptr(description)
+                .to_string_lossy()
+                .into_owned();
+            llvm_sys::core::LLVMDisposeMessage(description);
+            (severity, message)
+        };
+
+        match severity {
+            llvm_sys::LLVMDiagnosticSeverity::LLVMDSRemark if remarks.is_collecting => {
+                remarks.messages.borrow_mut().push(message)
+            }
+            llvm_sys::LLVMDiagnosticSeverity::LLVMDSRemark => {}
+            llvm_sys::LLVMDiagnosticSeverity::LLVMDSNote => log::info!("{}", message),
+            llvm_sys::LLVMDiagnosticSeverity::LLVMDSWarning => log::warn!("{}", message),
+            llvm_sys::LLVMDiagnosticSeverity::LLVMDSError => {
+                remarks.errors.borrow_mut().push(message)
+            }
+        }
+    }
+}
+
+impl Drop for Remarks {
+    fn drop(&mut self) {
+        unsafe {
+            llvm_sys::core::LLVMContextSetDiagnosticHandler(
+                self.llvm,
+                self.previous_handler,
+                self.previous_context,
+            );
+        }
+    }
+}
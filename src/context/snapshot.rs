@@ -0,0 +1,354 @@
+//!
+//! The LLVM generator context snapshot.
+//!
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use inkwell::values::AsValueRef;
+
+use crate::context::function::Function;
+use crate::context::r#loop::Loop;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The LLVM generator context snapshot.
+///
+/// Is taken with `Context::snapshot` before a speculative translation, e.g. an attempt to fold
+/// an expression, and passed to `Context::rollback` to discard the IR emitted since then.
+///
+/// Only the IR emitted into the current function, and the functions and globals declared
+/// since the snapshot, are discarded. The IR emitted into other existing functions is kept,
+/// so it must not refer to the discarded IR.
+///
+#[derive(Debug)]
+pub struct Snapshot<'ctx> {
+    /// The current function.
+    function: Option<Function<'ctx>>,
+    /// The current basic block.
+    block: Option<inkwell::basic_block::BasicBlock<'ctx>>,
+    /// The loop context stack.
+    loop_stack: Vec<Loop<'ctx>>,
+    /// The basic blocks of the current function.
+    blocks: HashSet<llvm_sys::prelude::LLVMBasicBlockRef>,
+    /// The instructions of the current function.
+    instructions: HashSet<llvm_sys::prelude::LLVMValueRef>,
+    /// The names of the declared functions.
+    functions: HashSet<String>,
+    /// The module globals.
+    globals: HashSet<llvm_sys::prelude::LLVMValueRef>,
+    /// The linker symbols.
+    linker_symbols: BTreeMap<String, String>,
+}
+
+impl<'ctx> Snapshot<'ctx> {
+    ///
+    /// Captures the state of `context`.
+    ///
+    pub fn new<D>(context: &Context<'ctx, D>) -> Self
+    where
+        D: Dependency,
+    {
+        let mut blocks = HashSet::new();
+        let mut instructions = HashSet::new();
+        if let Some(function) = context.function.as_ref() {
+            for block in function.value.get_basic_blocks().into_iter() {
+                blocks.insert(block.as_mut_ptr());
+                let mut instruction = block.get_first_instruction();
+                while let Some(current) = instruction {
+                    instructions.insert(current.as_value_ref());
+                    instruction = current.get_next_instruction();
+                }
+            }
+        }
+
+        let mut globals = HashSet::new();
+        let mut global = context.module().get_first_global();
+        while let Some(current) = global {
+            globals.insert(current.as_value_ref());
+            global = current.get_next_global();
+        }
+
+        Self {
+            function: context.function.clone(),
+            block: context.builder().get_insert_block(),
+            loop_stack: context.loop_stack.clone(),
+            blocks,
+            instructions,
+            functions: context.functions.keys().cloned().collect(),
+            globals,
+            linker_symbols: context.linker_symbols.clone(),
+        }
+    }
+
+    ///
+    /// Discards the IR emitted into `context` since the snapshot, and restores the state.
+    ///
+    /// Returns an error and leaves the IR intact if the kept IR refers to the discarded one,
+    /// that is, a `phi` node existing at the snapshot has got an incoming value from a new basic
+    /// block, or a new function is used outside the discarded IR.
+    ///
+    pub fn restore<D>(self, context: &mut Context<'ctx, D>) -> anyhow::Result<()>
+    where
+        D: Dependency,
+    {
+        let mut new_blocks = Vec::new();
+        let mut new_instructions = Vec::new();
+        if let Some(function) = self.function.as_ref() {
+            for block in function.value.get_basic_blocks().into_iter() {
+                let is_new_block = !self.blocks.contains(&block.as_mut_ptr());
+                if is_new_block {
+                    new_blocks.push(block);
+                }
+                let mut instruction = block.get_first_instruction();
+                while let Some(current) = instruction {
+                    instruction = current.get_next_instruction();
+                    if is_new_block || !self.instructions.contains(&current.as_value_ref()) {
+                        new_instructions.push(current);
+                        continue;
+                    }
+                    if let Some(incoming) = self.new_incoming_block(current) {
+                        anyhow::bail!(
+                            "Function `{}` phi node has an incoming value from the block `{}` emitted since the snapshot",
+                            function.name,
+                            incoming,
+                        );
+                    }
+                }
+            }
+        }
+
+        let new_functions: Vec<String> = context
+            .functions
+            .keys()
+            .filter(|name| !self.functions.contains(name.as_str()))
+            .cloned()
+            .collect();
+        let new_function_values: HashSet<llvm_sys::prelude::LLVMValueRef> = new_functions
+            .iter()
+            .map(|name| context.functions[name.as_str()].value.as_value_ref())
+            .collect();
+        let new_instruction_values: HashSet<llvm_sys::prelude::LLVMValueRef> = new_instructions
+            .iter()
+            .map(|instruction| instruction.as_value_ref())
+            .collect();
+        for name in new_functions.iter() {
+            let function = context.functions[name.as_str()].value.as_value_ref();
+            let is_used = unsafe {
+                Self::is_used_outside(function, &new_instruction_values, &new_function_values)
+            };
+            if is_used {
+                anyhow::bail!(
+                    "Function `{}` declared since the snapshot is used outside the discarded IR",
+                    name
+                );
+            }
+        }
+
+        unsafe {
+            for instruction in new_instructions.iter() {
+                Self::replace_uses_with_undef(instruction.as_value_ref());
+            }
+        }
+        for instruction in new_instructions.into_iter().rev() {
+            instruction.erase_from_basic_block();
+        }
+        for block in new_blocks.into_iter() {
+            unsafe { llvm_sys::core::LLVMDeleteBasicBlock(block.as_mut_ptr()) };
+        }
+        if let Some(function) = self.function.as_ref() {
+            context
+                .functions
+                .insert(function.name.clone(), function.clone());
+        }
+
+        for name in new_functions.into_iter() {
+            let function = context
+                .functions
+                .remove(name.as_str())
+                .expect("Always exists");
+            unsafe {
+                Self::replace_uses_with_undef(function.value.as_value_ref());
+                llvm_sys::core::LLVMDeleteFunction(function.value.as_value_ref());
+            }
+        }
+
+        let mut global = context.module().get_first_global();
+        while let Some(current) = global {
+            global = current.get_next_global();
+            let is_unused =
+                unsafe { llvm_sys::core::LLVMGetFirstUse(current.as_value_ref()).is_null() };
+            if !self.globals.contains(&current.as_value_ref()) && is_unused {
+                unsafe { current.delete() };
+            }
+        }
+
+        context.function = self.function;
+        context.loop_stack = self.loop_stack;
+        context.linker_symbols = self.linker_symbols;
+        context.memory_store_log = Default::default();
+        if let Some(block) = self.block {
+            context.set_basic_block(block);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the name of the basic block emitted since the snapshot, which `instruction` has
+    /// an incoming value from, if it is a `phi` node.
+    ///
+    fn new_incoming_block(&self, instruction: inkwell::values::InstructionValue) -> Option<String> {
+        if instruction.get_opcode() != inkwell::values::InstructionOpcode::Phi {
+            return None;
+        }
+
+        let value = instruction.as_value_ref();
+        unsafe {
+            (0..llvm_sys::core::LLVMCountIncoming(value))
+                .map(|index| llvm_sys::core::LLVMGetIncomingBlock(value, index))
+                .find(|block| !self.blocks.contains(block))
+                .map(|block| {
+                    std::ffi::CStr::from_ptr(llvm_sys::core::LLVMGetBasicBlockName(block))
+                        .to_string_lossy()
+                        .into_owned()
+                })
+        }
+    }
+
+    ///
+    /// Checks whether `value` is used by anything other than the `discarded_instructions` and
+    /// the instructions of the `discarded_functions`.
+    ///
+    /// # Safety
+    /// `value` must be a valid LLVM value.
+    ///
+    unsafe fn is_used_outside(
+        value: llvm_sys::prelude::LLVMValueRef,
+        discarded_instructions: &HashSet<llvm_sys::prelude::LLVMValueRef>,
+        discarded_functions: &HashSet<llvm_sys::prelude::LLVMValueRef>,
+    ) -> bool {
+        let mut r#use = llvm_sys::core::LLVMGetFirstUse(value);
+        while !r#use.is_null() {
+            let user = llvm_sys::core::LLVMGetUser(r#use);
+            let is_discarded = discarded_instructions.contains(&user)
+                || (!llvm_sys::core::LLVMIsAInstruction(user).is_null()
+                    && discarded_functions.contains(&llvm_sys::core::LLVMGetBasicBlockParent(
+                        llvm_sys::core::LLVMGetInstructionParent(user),
+                    )));
+            if !is_discarded {
+                return true;
+            }
+            r#use = llvm_sys::core::LLVMGetNextUse(r#use);
+        }
+        false
+    }
+
+    ///
+    /// Replaces the uses of `value` with the undefined value of its type.
+    ///
+    /// # Safety
+    /// `value` must be a valid LLVM value.
+    ///
+    unsafe fn replace_uses_with_undef(value: llvm_sys::prelude::LLVMValueRef) {
+        if llvm_sys::core::LLVMGetFirstUse(value).is_null() {
+            return;
+        }
+        let undef = llvm_sys::core::LLVMGetUndef(llvm_sys::core::LLVMTypeOf(value));
+        llvm_sys::core::LLVMReplaceAllUsesWith(value, undef);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn enter_function(context: &mut Context<DummyDependency>, name: &str) {
+        if !context.functions.contains_key(name) {
+            context.add_function(name, context.function_type(0, vec![]), None);
+        }
+        let function = context.functions[name].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+    }
+
+    #[test]
+    fn rollback() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "snapshot", optimizer, None, vec![]);
+
+        enter_function(&mut context, "main");
+        let blocks_count = context.function().value.count_basic_blocks();
+        let snapshot = context.snapshot();
+
+        let block = context.append_basic_block("speculative_block");
+        context.build_unconditional_branch(block);
+        context.set_basic_block(block);
+        context.add_function("helper", context.function_type(0, vec![]), None);
+        let helper = context.functions["helper"].value;
+        context.build_call(helper, &[], "helper_call");
+        context.resolve_library_deferred("library");
+
+        context.rollback(snapshot).expect("Always valid");
+        assert_eq!(context.function().value.count_basic_blocks(), blocks_count);
+        assert!(!context.functions.contains_key("helper"));
+        assert!(context.module().get_function("helper").is_none());
+        assert!(context.linker_symbols.is_empty());
+
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+    }
+
+    #[test]
+    fn rollback_phi_from_new_block() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "snapshot", optimizer, None, vec![]);
+
+        enter_function(&mut context, "main");
+        let entry_block = context.function().entry_block;
+        let join_block = context.append_basic_block("join_block");
+        context.build_unconditional_branch(join_block);
+        context.set_basic_block(join_block);
+        let phi = context.builder().build_phi(context.field_type(), "phi");
+        phi.add_incoming(&[(&context.field_const(0), entry_block)]);
+        let snapshot = context.snapshot();
+
+        let block = context.append_basic_block("speculative_block");
+        phi.add_incoming(&[(&context.field_const(1), block)]);
+
+        assert!(context.rollback(snapshot).is_err());
+        assert!(context.function().value.get_basic_blocks().contains(&block));
+    }
+
+    #[test]
+    fn rollback_new_function_used() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "snapshot", optimizer, None, vec![]);
+
+        enter_function(&mut context, "other");
+        enter_function(&mut context, "main");
+        let snapshot = context.snapshot();
+
+        context.add_function("helper", context.function_type(0, vec![]), None);
+        let helper = context.functions["helper"].value;
+        enter_function(&mut context, "other");
+        context.build_call(helper, &[], "helper_call");
+
+        assert!(context.rollback(snapshot).is_err());
+        assert!(context.module().get_function("helper").is_some());
+    }
+}
@@ -0,0 +1,294 @@
+//!
+//! The tail merging of the identical exit blocks.
+//!
+
+use std::collections::HashMap;
+
+use inkwell::values::AsValueRef;
+
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// Merges the identical exit blocks within each function, redirecting the branches to
+/// the duplicates to the first occurrence.
+///
+/// Targets the return and revert sequences emitted by `Context::build_exit` in many branches,
+/// such as the ABI decoding and overflow checks, which the generic CFG simplification at
+/// the size levels does not always merge, e.g. if the blocks are not adjacent to a common
+/// successor.
+///
+/// Only the blocks without successors, that is, terminated with `unreachable` or `ret`, are
+/// merged, so no `phi` nodes have to be updated. The blocks are identical if their instructions
+/// have the same opcodes, types, flags, and call site attributes, and their operands are either
+/// the same values defined outside the block or the same positions within the block. The values
+/// defined outside dominate both blocks, so they also dominate the merged block.
+///
+/// The blocks with the arithmetic instructions which may have the poison-generating flags, such
+/// as `nuw` or `exact`, are never merged, since the flags are not accessible via the LLVM C API.
+///
+/// Returns the number of the removed blocks.
+///
+pub fn merge<'ctx, D>(context: &Context<'ctx, D>) -> usize
+where
+    D: Dependency,
+{
+    let mut count = 0;
+    let mut function = context.module().get_first_function();
+    while let Some(current_function) = function {
+        function = current_function.get_next_function();
+
+        let mut originals: HashMap<Vec<InstructionKey>, inkwell::basic_block::BasicBlock<'ctx>> =
+            HashMap::new();
+        let mut duplicates = Vec::new();
+        for block in current_function.get_basic_blocks().into_iter().skip(1) {
+            let key = match block_key(block) {
+                Some(key) => key,
+                None => continue,
+            };
+            match originals.get(&key) {
+                Some(original) => duplicates.push((block, *original)),
+                None => {
+                    originals.insert(key, block);
+                }
+            }
+        }
+
+        for (duplicate, original) in duplicates.into_iter() {
+            unsafe {
+                llvm_sys::core::LLVMReplaceAllUsesWith(
+                    llvm_sys::core::LLVMBasicBlockAsValue(duplicate.as_mut_ptr()),
+                    llvm_sys::core::LLVMBasicBlockAsValue(original.as_mut_ptr()),
+                );
+                llvm_sys::core::LLVMDeleteBasicBlock(duplicate.as_mut_ptr());
+            }
+            count += 1;
+        }
+    }
+    count
+}
+
+///
+/// The structural key of an instruction.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct InstructionKey {
+    /// The opcode.
+    opcode: u32,
+    /// The result type.
+    r#type: llvm_sys::prelude::LLVMTypeRef,
+    /// The opcode-specific flags, such as the comparison predicate or the alignment.
+    flags: Vec<u32>,
+    /// The call site attributes by the attribute index.
+    attributes: Vec<(
+        llvm_sys::LLVMAttributeIndex,
+        llvm_sys::prelude::LLVMAttributeRef,
+    )>,
+    /// The operands.
+    operands: Vec<Operand>,
+}
+
+///
+/// The instruction operand.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Operand {
+    /// The value defined outside the block, including the constants and the arguments.
+    Value(llvm_sys::prelude::LLVMValueRef),
+    /// The result of the instruction at the position within the block.
+    Local(usize),
+}
+
+///
+/// Returns the structural key of `block`, if it is an exit block eligible for merging.
+///
+fn block_key(block: inkwell::basic_block::BasicBlock) -> Option<Vec<InstructionKey>> {
+    let terminator = block.get_terminator()?;
+    if !matches!(
+        terminator.get_opcode(),
+        inkwell::values::InstructionOpcode::Unreachable
+            | inkwell::values::InstructionOpcode::Return
+    ) {
+        return None;
+    }
+
+    let mut positions = HashMap::new();
+    let mut key = Vec::new();
+    let mut instruction = block.get_first_instruction();
+    while let Some(current) = instruction {
+        instruction = current.get_next_instruction();
+
+        if matches!(
+            current.get_opcode(),
+            inkwell::values::InstructionOpcode::Phi
+                | inkwell::values::InstructionOpcode::Alloca
+                | inkwell::values::InstructionOpcode::LandingPad
+                | inkwell::values::InstructionOpcode::Add
+                | inkwell::values::InstructionOpcode::Sub
+                | inkwell::values::InstructionOpcode::Mul
+                | inkwell::values::InstructionOpcode::Shl
+                | inkwell::values::InstructionOpcode::UDiv
+                | inkwell::values::InstructionOpcode::SDiv
+                | inkwell::values::InstructionOpcode::LShr
+                | inkwell::values::InstructionOpcode::AShr
+        ) {
+            return None;
+        }
+
+        let value = current.as_value_ref();
+        let mut operands = Vec::with_capacity(current.get_num_operands() as usize);
+        for index in 0..current.get_num_operands() {
+            let operand = unsafe { llvm_sys::core::LLVMGetOperand(value, index) };
+            let is_local = unsafe {
+                !llvm_sys::core::LLVMIsAInstruction(operand).is_null()
+                    && llvm_sys::core::LLVMGetInstructionParent(operand) == block.as_mut_ptr()
+            };
+            operands.push(if is_local {
+                Operand::Local(*positions.get(&operand)?)
+            } else {
+                Operand::Value(operand)
+            });
+        }
+
+        key.push(InstructionKey {
+            opcode: unsafe { llvm_sys::core::LLVMGetInstructionOpcode(value) } as u32,
+            r#type: unsafe { llvm_sys::core::LLVMTypeOf(value) },
+            flags: instruction_flags(current),
+            attributes: call_site_attributes(current),
+            operands,
+        });
+        positions.insert(value, positions.len());
+    }
+    Some(key)
+}
+
+///
+/// Returns the opcode-specific flags of `instruction` affecting its semantics.
+///
+fn instruction_flags(instruction: inkwell::values::InstructionValue) -> Vec<u32> {
+    let value = instruction.as_value_ref();
+    unsafe {
+        match instruction.get_opcode() {
+            inkwell::values::InstructionOpcode::ICmp => {
+                vec![llvm_sys::core::LLVMGetICmpPredicate(value) as u32]
+            }
+            inkwell::values::InstructionOpcode::Load
+            | inkwell::values::InstructionOpcode::Store => {
+                vec![
+                    llvm_sys::core::LLVMGetAlignment(value),
+                    llvm_sys::core::LLVMGetVolatile(value) as u32,
+                ]
+            }
+            inkwell::values::InstructionOpcode::Call => {
+                vec![
+                    llvm_sys::core::LLVMGetInstructionCallConv(value),
+                    llvm_sys::core::LLVMIsTailCall(value) as u32,
+                ]
+            }
+            inkwell::values::InstructionOpcode::GetElementPtr => {
+                vec![llvm_sys::core::LLVMIsInBounds(value) as u32]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+///
+/// Returns the call site attributes of `instruction`, if it is a call.
+///
+fn call_site_attributes(
+    instruction: inkwell::values::InstructionValue,
+) -> Vec<(
+    llvm_sys::LLVMAttributeIndex,
+    llvm_sys::prelude::LLVMAttributeRef,
+)> {
+    if instruction.get_opcode() != inkwell::values::InstructionOpcode::Call {
+        return Vec::new();
+    }
+
+    let value = instruction.as_value_ref();
+    let arguments_count = unsafe { llvm_sys::core::LLVMGetNumArgOperands(value) };
+    let mut attributes = Vec::new();
+    for index in [
+        llvm_sys::LLVMAttributeFunctionIndex,
+        llvm_sys::LLVMAttributeReturnIndex,
+    ]
+    .into_iter()
+    .chain(1..=arguments_count)
+    {
+        unsafe {
+            let count = llvm_sys::core::LLVMGetCallSiteAttributeCount(value, index);
+            let mut index_attributes = vec![std::ptr::null_mut(); count as usize];
+            llvm_sys::core::LLVMGetCallSiteAttributes(value, index, index_attributes.as_mut_ptr());
+            attributes.extend(
+                index_attributes
+                    .into_iter()
+                    .map(|attribute| (index, attribute)),
+            );
+        }
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn merged_count(name: &str, lengths: [u64; 2]) -> usize {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(&llvm, name, optimizer, None, vec![]);
+
+        context.add_function(
+            name,
+            context.function_type(0, vec![context.field_type().into()]),
+            None,
+        );
+        let function = context.functions[name].clone();
+        let condition = function
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_int_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let condition = context.builder().build_int_compare(
+            inkwell::IntPredicate::EQ,
+            condition,
+            context.field_const(0),
+            "condition",
+        );
+        let first_block = context.append_basic_block("first_revert_block");
+        let second_block = context.append_basic_block("second_revert_block");
+        context.build_conditional_branch(condition, first_block, second_block);
+        for (block, length) in [first_block, second_block].into_iter().zip(lengths) {
+            context.set_basic_block(block);
+            context.build_exit(
+                IntrinsicFunction::Revert,
+                context.field_const(0),
+                context.field_const(length),
+            );
+        }
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+
+        let count = super::merge(&context);
+        assert!(context.verify().is_ok());
+        count
+    }
+
+    #[test]
+    fn identical() {
+        assert_eq!(merged_count("identical", [32, 32]), 1);
+    }
+
+    #[test]
+    fn different() {
+        assert_eq!(merged_count("different", [32, 64]), 0);
+    }
+}
@@ -0,0 +1,211 @@
+//!
+//! The LLVM IR value range analysis.
+//!
+
+use inkwell::values::AsValueRef;
+
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The maximum depth of the value definitions walked by the analysis.
+///
+const DEPTH_MAX: usize = 4;
+
+///
+/// Checks whether `value` is known to fit into 32 bits, so its truncation is redundant.
+///
+/// The analysis is structural and conservative: it recognizes the constants, the values already
+/// masked to 32 bits, the values extended from narrower types or shifted right far enough, and
+/// the loads of the calldata size, which is always extracted from the calldata fat pointer.
+/// The return data size is not recognized, since the deployer return data stores a whole word
+/// read from the heap there.
+/// The bitwise and selection instructions are walked up to a small depth.
+///
+pub fn fits_x32<'ctx, D>(context: &Context<'ctx, D>, value: inkwell::values::IntValue<'ctx>) -> bool
+where
+    D: Dependency,
+{
+    fits_x32_recursive(context, value, 0)
+}
+
+///
+/// The `fits_x32` implementation with the walked depth.
+///
+fn fits_x32_recursive<'ctx, D>(
+    context: &Context<'ctx, D>,
+    value: inkwell::values::IntValue<'ctx>,
+    depth: usize,
+) -> bool
+where
+    D: Dependency,
+{
+    if value.get_type().get_bit_width() <= compiler_common::BITLENGTH_X32 as u32 {
+        return true;
+    }
+    if value.is_const() {
+        return context
+            .const_u64(value)
+            .map_or(false, |constant| constant <= u32::MAX as u64);
+    }
+    if depth >= DEPTH_MAX {
+        return false;
+    }
+
+    let instruction = match value.as_instruction_value() {
+        Some(instruction) => instruction,
+        None => return false,
+    };
+    let operand = |index: u32| {
+        instruction
+            .get_operand(index)
+            .and_then(|operand| operand.left())
+    };
+    match instruction.get_opcode() {
+        inkwell::values::InstructionOpcode::ZExt => operand(0).map_or(false, |operand| {
+            operand.into_int_value().get_type().get_bit_width()
+                <= compiler_common::BITLENGTH_X32 as u32
+        }),
+        inkwell::values::InstructionOpcode::LShr => {
+            operand(1).map_or(false, |amount| {
+                context
+                    .const_u64(amount.into_int_value())
+                    .map_or(false, |amount| {
+                        amount.saturating_add(compiler_common::BITLENGTH_X32 as u64)
+                            >= value.get_type().get_bit_width() as u64
+                    })
+            }) || operand(0).map_or(false, |operand| {
+                fits_x32_recursive(context, operand.into_int_value(), depth + 1)
+            })
+        }
+        inkwell::values::InstructionOpcode::And => [0, 1].into_iter().any(|index| {
+            operand(index).map_or(false, |operand| {
+                fits_x32_recursive(context, operand.into_int_value(), depth + 1)
+            })
+        }),
+        inkwell::values::InstructionOpcode::Or | inkwell::values::InstructionOpcode::Xor => {
+            [0, 1].into_iter().all(|index| {
+                operand(index).map_or(false, |operand| {
+                    fits_x32_recursive(context, operand.into_int_value(), depth + 1)
+                })
+            })
+        }
+        inkwell::values::InstructionOpcode::Select => [1, 2].into_iter().all(|index| {
+            operand(index).map_or(false, |operand| {
+                fits_x32_recursive(context, operand.into_int_value(), depth + 1)
+            })
+        }),
+        inkwell::values::InstructionOpcode::Load => operand(0).map_or(false, |pointer| {
+            is_calldata_size_global(context, pointer.into_pointer_value())
+        }),
+        _ => false,
+    }
+}
+
+///
+/// Checks whether `pointer` is the calldata size global.
+///
+fn is_calldata_size_global<'ctx, D>(
+    context: &Context<'ctx, D>,
+    pointer: inkwell::values::PointerValue<'ctx>,
+) -> bool
+where
+    D: Dependency,
+{
+    context
+        .get_global_ptr(crate::r#const::GLOBAL_CALLDATA_SIZE)
+        .map_or(false, |global| {
+            global.as_value_ref() == pointer.as_value_ref()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    #[test]
+    fn constants() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let context =
+            Context::<DummyDependency>::new(&llvm, "value_range", optimizer, None, vec![]);
+
+        assert!(super::fits_x32(
+            &context,
+            context.field_const(u32::MAX as u64)
+        ));
+        assert!(!super::fits_x32(
+            &context,
+            context.field_const(u32::MAX as u64 + 1)
+        ));
+        let wide = context
+            .field_const(1)
+            .const_shl(context.field_const(compiler_common::BITLENGTH_X64 as u64));
+        assert!(!super::fits_x32(&context, wide));
+    }
+
+    #[test]
+    fn instructions() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "value_range", optimizer, None, vec![]);
+
+        context.add_function(
+            "value_range",
+            context.function_type(0, vec![context.field_type().into()]),
+            None,
+        );
+        let function = context.functions["value_range"].clone();
+        let argument = function
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_int_value();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+
+        assert!(!super::fits_x32(&context, argument));
+        let masked =
+            context
+                .builder()
+                .build_and(argument, context.field_const(u32::MAX as u64), "masked");
+        assert!(super::fits_x32(&context, masked));
+        let shifted = context.builder().build_right_shift(
+            argument,
+            context.field_const(
+                (compiler_common::BITLENGTH_FIELD - compiler_common::BITLENGTH_X32) as u64,
+            ),
+            false,
+            "shifted",
+        );
+        assert!(super::fits_x32(&context, shifted));
+        let combined = context.builder().build_or(masked, argument, "combined");
+        assert!(!super::fits_x32(&context, combined));
+
+        context.set_global(crate::r#const::GLOBAL_CALLDATA_SIZE, argument);
+        context.set_global(crate::r#const::GLOBAL_RETURN_DATA_SIZE, argument);
+        let calldata_size = context.build_load(
+            context
+                .get_global_ptr(crate::r#const::GLOBAL_CALLDATA_SIZE)
+                .expect("Always exists"),
+            "calldata_size",
+        );
+        assert!(super::fits_x32(&context, calldata_size.into_int_value()));
+        let return_data_size = context.build_load(
+            context
+                .get_global_ptr(crate::r#const::GLOBAL_RETURN_DATA_SIZE)
+                .expect("Always exists"),
+            "return_data_size",
+        );
+        assert!(!super::fits_x32(
+            &context,
+            return_data_size.into_int_value()
+        ));
+    }
+}
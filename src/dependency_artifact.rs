@@ -0,0 +1,35 @@
+//!
+//! The compiled project dependency artifact.
+//!
+
+use std::collections::BTreeMap;
+
+///
+/// The compiled project dependency artifact, returned by the dependency manager.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyArtifact {
+    /// The zkEVM bytecode hash.
+    pub hash: String,
+    /// The zkEVM bytecode length in bytes.
+    pub bytecode_length: usize,
+    /// The hash-to-path mapping of the dependency's own factory dependencies.
+    pub factory_dependencies: BTreeMap<String, String>,
+}
+
+impl DependencyArtifact {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(
+        hash: String,
+        bytecode_length: usize,
+        factory_dependencies: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            hash,
+            bytecode_length,
+            factory_dependencies,
+        }
+    }
+}
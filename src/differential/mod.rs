@@ -0,0 +1,259 @@
+//!
+//! The differential testing of the translation helpers.
+//!
+//! The helpers are evaluated on random operands both with the EVM reference implementation and
+//! with the LLVM interpreter executing the generated IR, so the semantic differences between
+//! the EVM and LLVM IR, e.g. the division by zero or the overlong shifts, are caught.
+//!
+
+pub mod operation;
+pub mod random;
+
+use num::BigUint;
+use num::Zero;
+
+use crate::context::optimizer::settings::Settings as OptimizerSettings;
+use crate::context::optimizer::Optimizer;
+use crate::context::Context;
+use crate::Dependency;
+
+use self::operation::Operation;
+use self::random::Random;
+
+/// The number of 64-bit limbs in the field element.
+const LIMBS: usize = compiler_common::BITLENGTH_FIELD / compiler_common::BITLENGTH_X64;
+
+///
+/// Evaluates `operation` on `samples` random operand pairs generated from `seed`.
+///
+/// Returns an error describing the first mismatch between the reference and generated IR.
+/// The target must have been initialized with `crate::initialize_target`.
+///
+pub fn check<D>(operation: Operation, samples: usize, seed: u64) -> anyhow::Result<()>
+where
+    D: Dependency,
+{
+    let llvm = inkwell::context::Context::create();
+    let optimizer = Optimizer::new(OptimizerSettings::none())?;
+    let mut context = Context::<D>::new(
+        &llvm,
+        format!("differential_{}", operation.name()).as_str(),
+        optimizer,
+        None,
+        vec![],
+    );
+    define_sign_extend(&context);
+    let function = build_wrapper(&mut context, operation)?;
+    context.verify()?;
+
+    let limb_type = context.integer_type(compiler_common::BITLENGTH_X64);
+    let engine = context
+        .module()
+        .create_interpreter_execution_engine()
+        .map_err(|error| anyhow::anyhow!("The LLVM interpreter creating error: {}", error))?;
+
+    let mut random = Random::new(seed);
+    for _ in 0..samples {
+        let operand_1 = random.word();
+        let operand_2 = random.word();
+
+        let mut arguments = Vec::with_capacity(LIMBS * 2 + 1);
+        for operand in [&operand_1, &operand_2].into_iter() {
+            let mut digits = operand.to_u64_digits();
+            digits.resize(LIMBS, 0);
+            arguments.extend(
+                digits
+                    .into_iter()
+                    .map(|digit| limb_type.create_generic_value(digit, false)),
+            );
+        }
+
+        let mut actual = BigUint::zero();
+        for limb in 0..LIMBS {
+            let limb_index = limb_type.create_generic_value(limb as u64, false);
+            let arguments: Vec<_> = arguments
+                .iter()
+                .chain(std::iter::once(&limb_index))
+                .collect();
+            let value = unsafe { engine.run_function(function, arguments.as_slice()) };
+            actual |= BigUint::from(value.as_int(false)) << (limb * compiler_common::BITLENGTH_X64);
+        }
+
+        let expected = operation.reference(&operand_1, &operand_2);
+        if actual != expected {
+            anyhow::bail!(
+                "Operation `{}` with operands {:#x} and {:#x}: expected {:#x}, found {:#x}",
+                operation.name(),
+                operand_1,
+                operand_2,
+                expected,
+                actual
+            );
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Builds the wrapper function taking the operands split into 64-bit limbs and the index of
+/// the result limb to return, since the interpreter arguments and results are limited to
+/// 64 bits.
+///
+fn build_wrapper<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    operation: Operation,
+) -> anyhow::Result<inkwell::values::FunctionValue<'ctx>>
+where
+    D: Dependency,
+{
+    let name = format!("differential_{}", operation.name());
+    let limb_type = context.integer_type(compiler_common::BITLENGTH_X64);
+    let r#type = limb_type.fn_type(vec![limb_type.into(); LIMBS * 2 + 1].as_slice(), false);
+    context.add_function(
+        name.as_str(),
+        r#type,
+        Some(inkwell::module::Linkage::External),
+    );
+    let function = context
+        .functions
+        .get(name.as_str())
+        .cloned()
+        .expect("Always exists");
+    context.set_function(function.clone());
+    context.set_basic_block(function.entry_block);
+
+    let mut operands = Vec::with_capacity(2);
+    for operand_index in 0..2 {
+        let mut operand = context.field_const(0);
+        for limb in 0..LIMBS {
+            let parameter = function
+                .value
+                .get_nth_param((operand_index * LIMBS + limb) as u32)
+                .expect("Always exists")
+                .into_int_value();
+            let extended = context.builder().build_int_z_extend(
+                parameter,
+                context.field_type(),
+                "differential_limb_extended",
+            );
+            let shifted = context.builder().build_left_shift(
+                extended,
+                context.field_const((limb * compiler_common::BITLENGTH_X64) as u64),
+                "differential_limb_shifted",
+            );
+            operand = context
+                .builder()
+                .build_or(operand, shifted, "differential_operand");
+        }
+        operands.push(operand);
+    }
+
+    let result = operation.translate(context, operands[0], operands[1])?;
+
+    let limb_index = function
+        .value
+        .get_nth_param((LIMBS * 2) as u32)
+        .expect("Always exists")
+        .into_int_value();
+    let limb_index = context.builder().build_int_z_extend(
+        limb_index,
+        context.field_type(),
+        "differential_limb_index",
+    );
+    let limb_shift = context.builder().build_int_mul(
+        limb_index,
+        context.field_const(compiler_common::BITLENGTH_X64 as u64),
+        "differential_limb_shift",
+    );
+    let result_shifted = context.builder().build_right_shift(
+        result,
+        limb_shift,
+        false,
+        "differential_result_shifted",
+    );
+    let result_limb =
+        context
+            .builder()
+            .build_int_truncate(result_shifted, limb_type, "differential_result_limb");
+    context.build_unconditional_branch(function.return_block);
+    context.set_basic_block(function.return_block);
+    context.builder().build_return(Some(&result_limb));
+
+    Ok(function.value)
+}
+
+///
+/// Defines the `signextend` runtime function, which is implemented in the LLVM back-end, with
+/// the equivalent IR, so the interpreter can execute its calls.
+///
+fn define_sign_extend<D>(context: &Context<D>)
+where
+    D: Dependency,
+{
+    let function = context.runtime.sign_extend;
+    let block = context
+        .module()
+        .get_context()
+        .append_basic_block(function, "entry");
+    context.builder().position_at_end(block);
+
+    let bytes = function
+        .get_nth_param(0)
+        .expect("Always exists")
+        .into_int_value();
+    let value = function
+        .get_nth_param(1)
+        .expect("Always exists")
+        .into_int_value();
+    let is_extended = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULT,
+        bytes,
+        context.field_const((compiler_common::SIZE_FIELD - 1) as u64),
+        "sign_extend_is_extended",
+    );
+    let bits = context.builder().build_int_mul(
+        bytes,
+        context.field_const(compiler_common::BITLENGTH_BYTE as u64),
+        "sign_extend_bits",
+    );
+    let shift = context.builder().build_int_sub(
+        context.field_const(
+            (compiler_common::BITLENGTH_FIELD - compiler_common::BITLENGTH_BYTE) as u64,
+        ),
+        bits,
+        "sign_extend_shift",
+    );
+    let shift = context
+        .builder()
+        .build_select(
+            is_extended,
+            shift,
+            context.field_const(0),
+            "sign_extend_shift_checked",
+        )
+        .into_int_value();
+    let shifted = context
+        .builder()
+        .build_left_shift(value, shift, "sign_extend_shifted");
+    let result = context
+        .builder()
+        .build_right_shift(shifted, shift, true, "sign_extend_result");
+    context.builder().build_return(Some(&result));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DummyDependency;
+
+    use super::operation::Operation;
+
+    #[test]
+    fn all_operations() {
+        crate::initialize_target();
+        for operation in Operation::ALL.into_iter() {
+            super::check::<DummyDependency>(operation, 64, 0x5eed)
+                .expect("The semantics have drifted");
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//!
+//! The differential testing operation.
+//!
+
+use num::BigInt;
+use num::BigUint;
+use num::One;
+use num::Zero;
+
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// The differential testing operation.
+///
+/// Only the pure translation helpers are covered, since their results only depend on
+/// the operands. The `signextend` instruction is translated into a call of the runtime function
+/// implemented in the LLVM back-end, so only the call is covered, with the runtime function
+/// defined by the harness.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// The `add` instruction.
+    Addition,
+    /// The `sub` instruction.
+    Subtraction,
+    /// The `mul` instruction.
+    Multiplication,
+    /// The `div` instruction.
+    Division,
+    /// The `mod` instruction.
+    Remainder,
+    /// The `sdiv` instruction.
+    DivisionSigned,
+    /// The `smod` instruction.
+    RemainderSigned,
+    /// The `exp` instruction.
+    Exponent,
+    /// The `signextend` instruction.
+    SignExtend,
+    /// The `and` instruction.
+    And,
+    /// The `or` instruction.
+    Or,
+    /// The `xor` instruction.
+    Xor,
+    /// The `shl` instruction.
+    ShiftLeft,
+    /// The `shr` instruction.
+    ShiftRight,
+    /// The `sar` instruction.
+    ShiftRightArithmetic,
+    /// The `eq` instruction.
+    Equal,
+    /// The `lt` instruction.
+    LessThan,
+    /// The `gt` instruction.
+    GreaterThan,
+    /// The `slt` instruction.
+    LessThanSigned,
+    /// The `sgt` instruction.
+    GreaterThanSigned,
+}
+
+impl Operation {
+    /// All the covered operations.
+    pub const ALL: [Self; 20] = [
+        Self::Addition,
+        Self::Subtraction,
+        Self::Multiplication,
+        Self::Division,
+        Self::Remainder,
+        Self::DivisionSigned,
+        Self::RemainderSigned,
+        Self::Exponent,
+        Self::SignExtend,
+        Self::And,
+        Self::Or,
+        Self::Xor,
+        Self::ShiftLeft,
+        Self::ShiftRight,
+        Self::ShiftRightArithmetic,
+        Self::Equal,
+        Self::LessThan,
+        Self::GreaterThan,
+        Self::LessThanSigned,
+        Self::GreaterThanSigned,
+    ];
+
+    ///
+    /// Returns the EVM instruction name.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Addition => "add",
+            Self::Subtraction => "sub",
+            Self::Multiplication => "mul",
+            Self::Division => "div",
+            Self::Remainder => "mod",
+            Self::DivisionSigned => "sdiv",
+            Self::RemainderSigned => "smod",
+            Self::Exponent => "exp",
+            Self::SignExtend => "signextend",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Xor => "xor",
+            Self::ShiftLeft => "shl",
+            Self::ShiftRight => "shr",
+            Self::ShiftRightArithmetic => "sar",
+            Self::Equal => "eq",
+            Self::LessThan => "lt",
+            Self::GreaterThan => "gt",
+            Self::LessThanSigned => "slt",
+            Self::GreaterThanSigned => "sgt",
+        }
+    }
+
+    ///
+    /// Translates the operation with the crate helper.
+    ///
+    pub fn translate<'ctx, D>(
+        &self,
+        context: &mut Context<'ctx, D>,
+        operand_1: inkwell::values::IntValue<'ctx>,
+        operand_2: inkwell::values::IntValue<'ctx>,
+    ) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
+    where
+        D: Dependency,
+    {
+        let result = match self {
+            Self::Addition => crate::evm::arithmetic::addition(context, operand_1, operand_2),
+            Self::Subtraction => crate::evm::arithmetic::subtraction(context, operand_1, operand_2),
+            Self::Multiplication => {
+                crate::evm::arithmetic::multiplication(context, operand_1, operand_2)
+            }
+            Self::Division => crate::evm::arithmetic::division(context, operand_1, operand_2),
+            Self::Remainder => crate::evm::arithmetic::remainder(context, operand_1, operand_2),
+            Self::DivisionSigned => {
+                crate::evm::arithmetic::division_signed(context, operand_1, operand_2)
+            }
+            Self::RemainderSigned => {
+                crate::evm::arithmetic::remainder_signed(context, operand_1, operand_2)
+            }
+            Self::Exponent => crate::evm::math::exponent(context, operand_1, operand_2),
+            Self::SignExtend => crate::evm::math::sign_extend(context, operand_1, operand_2),
+            Self::And => crate::evm::bitwise::and(context, operand_1, operand_2),
+            Self::Or => crate::evm::bitwise::or(context, operand_1, operand_2),
+            Self::Xor => crate::evm::bitwise::xor(context, operand_1, operand_2),
+            Self::ShiftLeft => crate::evm::bitwise::shift_left(context, operand_1, operand_2),
+            Self::ShiftRight => crate::evm::bitwise::shift_right(context, operand_1, operand_2),
+            Self::ShiftRightArithmetic => {
+                crate::evm::bitwise::shift_right_arithmetic(context, operand_1, operand_2)
+            }
+            Self::Equal => crate::evm::comparison::compare(
+                context,
+                operand_1,
+                operand_2,
+                inkwell::IntPredicate::EQ,
+            ),
+            Self::LessThan => crate::evm::comparison::compare(
+                context,
+                operand_1,
+                operand_2,
+                inkwell::IntPredicate::ULT,
+            ),
+            Self::GreaterThan => crate::evm::comparison::compare(
+                context,
+                operand_1,
+                operand_2,
+                inkwell::IntPredicate::UGT,
+            ),
+            Self::LessThanSigned => crate::evm::comparison::compare(
+                context,
+                operand_1,
+                operand_2,
+                inkwell::IntPredicate::SLT,
+            ),
+            Self::GreaterThanSigned => crate::evm::comparison::compare(
+                context,
+                operand_1,
+                operand_2,
+                inkwell::IntPredicate::SGT,
+            ),
+        }?;
+        Ok(result.expect("Always returns a value").into_int_value())
+    }
+
+    ///
+    /// Evaluates the operation according to the EVM semantics.
+    ///
+    pub fn reference(&self, operand_1: &BigUint, operand_2: &BigUint) -> BigUint {
+        let modulus = BigUint::one() << compiler_common::BITLENGTH_FIELD;
+        let all_ones = &modulus - BigUint::one();
+        let boolean = |value: bool| {
+            if value {
+                BigUint::one()
+            } else {
+                BigUint::zero()
+            }
+        };
+
+        match self {
+            Self::Addition => (operand_1 + operand_2) % &modulus,
+            Self::Subtraction => (operand_1 + &modulus - operand_2) % &modulus,
+            Self::Multiplication => (operand_1 * operand_2) % &modulus,
+            Self::Division if operand_2.is_zero() => BigUint::zero(),
+            Self::Division => operand_1 / operand_2,
+            Self::Remainder if operand_2.is_zero() => BigUint::zero(),
+            Self::Remainder => operand_1 % operand_2,
+            Self::DivisionSigned if operand_2.is_zero() => BigUint::zero(),
+            Self::DivisionSigned => from_signed(to_signed(operand_1) / to_signed(operand_2)),
+            Self::RemainderSigned if operand_2.is_zero() => BigUint::zero(),
+            Self::RemainderSigned => from_signed(to_signed(operand_1) % to_signed(operand_2)),
+            Self::Exponent => operand_1.modpow(operand_2, &modulus),
+            Self::SignExtend => match shift_amount(operand_1) {
+                Some(bytes) if bytes < compiler_common::SIZE_FIELD - 1 => {
+                    let bits = (bytes + 1) * compiler_common::BITLENGTH_BYTE;
+                    let value = operand_2 % (BigUint::one() << bits);
+                    if value.bit((bits - 1) as u64) {
+                        value + (&all_ones - ((BigUint::one() << bits) - BigUint::one()))
+                    } else {
+                        value
+                    }
+                }
+                _ => operand_2.to_owned(),
+            },
+            Self::And => operand_1 & operand_2,
+            Self::Or => operand_1 | operand_2,
+            Self::Xor => operand_1 ^ operand_2,
+            Self::ShiftLeft => match shift_amount(operand_1) {
+                Some(shift) => (operand_2 << shift) % &modulus,
+                None => BigUint::zero(),
+            },
+            Self::ShiftRight => match shift_amount(operand_1) {
+                Some(shift) => operand_2 >> shift,
+                None => BigUint::zero(),
+            },
+            Self::ShiftRightArithmetic => {
+                let is_negative = is_negative(operand_2);
+                match (shift_amount(operand_1), is_negative) {
+                    (Some(shift), false) => operand_2 >> shift,
+                    (Some(shift), true) => &all_ones - ((&all_ones - operand_2) >> shift),
+                    (None, false) => BigUint::zero(),
+                    (None, true) => all_ones,
+                }
+            }
+            Self::Equal => boolean(operand_1 == operand_2),
+            Self::LessThan => boolean(operand_1 < operand_2),
+            Self::GreaterThan => boolean(operand_1 > operand_2),
+            Self::LessThanSigned => boolean(to_signed(operand_1) < to_signed(operand_2)),
+            Self::GreaterThanSigned => boolean(to_signed(operand_1) > to_signed(operand_2)),
+        }
+    }
+}
+
+///
+/// Returns the shift amount, or `None` if the whole word is shifted out.
+///
+fn shift_amount(value: &BigUint) -> Option<usize> {
+    if value < &BigUint::from(compiler_common::BITLENGTH_FIELD) {
+        value
+            .to_u64_digits()
+            .first()
+            .map_or(Some(0), |shift| Some(*shift as usize))
+    } else {
+        None
+    }
+}
+
+///
+/// Checks whether the sign bit of the two's complement `value` is set.
+///
+fn is_negative(value: &BigUint) -> bool {
+    value >= &(BigUint::one() << (compiler_common::BITLENGTH_FIELD - 1))
+}
+
+///
+/// Converts the two's complement `value` to a signed integer.
+///
+fn to_signed(value: &BigUint) -> BigInt {
+    if is_negative(value) {
+        BigInt::from(value.to_owned()) - (BigInt::one() << compiler_common::BITLENGTH_FIELD)
+    } else {
+        BigInt::from(value.to_owned())
+    }
+}
+
+///
+/// Converts the signed `value` to its two's complement representation.
+///
+fn from_signed(value: BigInt) -> BigUint {
+    let modulus = BigInt::one() << compiler_common::BITLENGTH_FIELD;
+    (((value % &modulus) + &modulus) % &modulus)
+        .to_biguint()
+        .expect("Always non-negative")
+}
@@ -0,0 +1,65 @@
+//!
+//! The differential testing operand generator.
+//!
+
+use num::BigUint;
+use num::One;
+
+///
+/// The deterministic operand generator.
+///
+/// Mixes the uniformly random words with the boundary values and small numbers, since the most
+/// of the semantic differences are found at the boundaries, e.g. at zero or the sign bit.
+///
+#[derive(Debug)]
+pub struct Random {
+    /// The xorshift generator state.
+    state: u64,
+}
+
+impl Random {
+    ///
+    /// A shortcut constructor.
+    ///
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    ///
+    /// Returns the next random 64-bit number.
+    ///
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    ///
+    /// Returns the next random field element.
+    ///
+    pub fn word(&mut self) -> BigUint {
+        let modulus = BigUint::one() << compiler_common::BITLENGTH_FIELD;
+        let sign_bit = BigUint::one() << (compiler_common::BITLENGTH_FIELD - 1);
+
+        match self.next_u64() % 4 {
+            0 => match self.next_u64() % 6 {
+                0 => BigUint::from(0u8),
+                1 => BigUint::one(),
+                2 => &modulus - BigUint::one(),
+                3 => sign_bit,
+                4 => &sign_bit - BigUint::one(),
+                _ => BigUint::from(compiler_common::BITLENGTH_FIELD),
+            },
+            1 => BigUint::from(self.next_u64() % (compiler_common::BITLENGTH_FIELD as u64 + 8)),
+            _ => {
+                let mut word = BigUint::from(0u8);
+                for limb in 0..compiler_common::BITLENGTH_FIELD / compiler_common::BITLENGTH_X64 {
+                    word |=
+                        BigUint::from(self.next_u64()) << (limb * compiler_common::BITLENGTH_X64);
+                }
+                word
+            }
+        }
+    }
+}
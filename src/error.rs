@@ -0,0 +1,117 @@
+//!
+//! The LLVM context error.
+//!
+
+///
+/// The LLVM context error.
+///
+/// Is wrapped into `anyhow::Error` by the fallible context methods, so the front-ends can
+/// downcast it to map the failure to their diagnostics instead of parsing the message.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The LLVM IR module verification failure.
+    Verification {
+        /// The contract path.
+        contract_path: String,
+        /// The pipeline stage, e.g. `unoptimized` or `optimized`, if known.
+        stage: Option<&'static str>,
+        /// The first function failing the verification, if it has been found.
+        function: Option<String>,
+        /// The LLVM verifier message.
+        message: String,
+        /// The LLVM IR dump of the module failing the verification, if it has been requested.
+        llvm_ir: Option<String>,
+    },
+    /// The generated assembly parsing failure.
+    AssemblyParsing {
+        /// The contract path.
+        contract_path: String,
+        /// The assembler message.
+        message: String,
+        /// The assembly text failing the parsing.
+        assembly: String,
+    },
+    /// The dependency which cannot be resolved or compiled.
+    MissingDependency {
+        /// The contract path.
+        contract_path: String,
+        /// The dependency identifier, path, or library path.
+        dependency: String,
+        /// The reason the dependency is missing.
+        message: String,
+    },
+    /// The intrinsic function unknown to LLVM.
+    MissingIntrinsic {
+        /// The contract path.
+        contract_path: String,
+        /// The intrinsic function name.
+        name: String,
+    },
+}
+
+impl Error {
+    ///
+    /// Returns the path of the contract the error has occurred in.
+    ///
+    pub fn contract_path(&self) -> &str {
+        match self {
+            Self::Verification { contract_path, .. } => contract_path.as_str(),
+            Self::AssemblyParsing { contract_path, .. } => contract_path.as_str(),
+            Self::MissingDependency { contract_path, .. } => contract_path.as_str(),
+            Self::MissingIntrinsic { contract_path, .. } => contract_path.as_str(),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Verification {
+                contract_path,
+                stage,
+                function,
+                message,
+                ..
+            } => {
+                write!(f, "The contract `{}`", contract_path)?;
+                if let Some(stage) = stage {
+                    write!(f, " {}", stage)?;
+                }
+                write!(f, " LLVM IR verification error")?;
+                if let Some(function) = function {
+                    write!(f, " in function `{}`", function)?;
+                }
+                write!(f, ": {}", message)
+            }
+            Self::AssemblyParsing {
+                contract_path,
+                message,
+                ..
+            } => write!(
+                f,
+                "The contract `{}` assembly parsing error: {}",
+                contract_path, message
+            ),
+            Self::MissingDependency {
+                contract_path,
+                dependency,
+                message,
+            } => write!(
+                f,
+                "The contract `{}` dependency `{}` is missing: {}",
+                contract_path, dependency, message
+            ),
+            Self::MissingIntrinsic {
+                contract_path,
+                name,
+            } => write!(
+                f,
+                "Contract `{}` intrinsic function `{}`: does not exist",
+                contract_path, name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
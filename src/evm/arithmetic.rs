@@ -195,7 +195,9 @@ where
     let condition_is_divided_int_min = context.builder().build_int_compare(
         inkwell::IntPredicate::EQ,
         operand_1,
-        context.field_const_str("8000000000000000000000000000000000000000000000000000000000000000"),
+        context.field_const_str_hex(
+            "8000000000000000000000000000000000000000000000000000000000000000",
+        ),
         "division_signed_is_divided_int_min",
     );
     let condition_is_divider_minus_one = context.builder().build_int_compare(
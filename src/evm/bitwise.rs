@@ -260,3 +260,17 @@ where
             .build_and(value_shifted, context.field_const(0xff), "byte_result");
     Ok(Some(byte_result.as_basic_value_enum()))
 }
+
+///
+/// Translates the byte order reversal, converting the value between the big-endian and
+/// little-endian representations.
+///
+pub fn byte_swap<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    operand: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    Ok(Some(context.build_byte_swap(operand).as_basic_value_enum()))
+}
@@ -3,6 +3,7 @@
 //!
 
 use crate::context::address_space::AddressSpace;
+use crate::context::calldata_slice::CalldataSlice;
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 use crate::context::Context;
 use crate::Dependency;
@@ -17,20 +18,8 @@ pub fn load<'ctx, D>(
 where
     D: Dependency,
 {
-    let calldata_pointer = context.get_global(crate::r#const::GLOBAL_CALLDATA_POINTER)?;
-    let calldata_pointer = unsafe {
-        context.builder().build_gep(
-            calldata_pointer.into_pointer_value(),
-            &[offset],
-            "calldata_pointer_with_offset",
-        )
-    };
-    let calldata_pointer_casted = context.builder().build_pointer_cast(
-        calldata_pointer,
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "calldata_pointer_casted",
-    );
-    let value = context.build_load(calldata_pointer_casted, "calldata_value");
+    let calldata_pointer = context.calldata_word_pointer(offset)?;
+    let value = context.build_load(calldata_pointer, "calldata_value");
 
     Ok(Some(value))
 }
@@ -44,9 +33,9 @@ pub fn size<'ctx, D>(
 where
     D: Dependency,
 {
-    let value = context.get_global(crate::r#const::GLOBAL_CALLDATA_SIZE)?;
+    let value = context.calldata_size()?;
 
-    Ok(Some(value))
+    Ok(Some(value.into()))
 }
 
 ///
@@ -93,3 +82,147 @@ where
 
     Ok(None)
 }
+
+///
+/// Translates the checked decoding of the dynamic ABI type, whose head is at `head_offset`.
+///
+/// The head holds the tail offset relative to `base_offset`, that is, the beginning of the
+/// enclosing tuple encoding. The tail starts with the number of elements of `element_size` bytes,
+/// followed by the elements. Like in the Solidity ABI decoder, reverts with empty data if
+/// the head, the tail, or the elements are not within the calldata.
+///
+/// The bounds are checked without overflowing additions, so any offsets may be passed.
+///
+pub fn slice<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    base_offset: inkwell::values::IntValue<'ctx>,
+    head_offset: inkwell::values::IntValue<'ctx>,
+    element_size: u64,
+) -> anyhow::Result<CalldataSlice<'ctx>>
+where
+    D: Dependency,
+{
+    let calldata_size = context.calldata_size()?;
+    let failure_block = context.append_basic_block("calldata_slice_failure_block");
+
+    build_bounds_check(
+        context,
+        calldata_size,
+        head_offset,
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+        failure_block,
+    );
+    let relative_offset = self::load(context, head_offset)?
+        .expect("Always exists")
+        .into_int_value();
+
+    build_bounds_check(
+        context,
+        calldata_size,
+        base_offset,
+        relative_offset,
+        failure_block,
+    );
+    let tail_offset =
+        context
+            .builder()
+            .build_int_add(base_offset, relative_offset, "calldata_slice_tail_offset");
+    build_bounds_check(
+        context,
+        calldata_size,
+        tail_offset,
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+        failure_block,
+    );
+    let length = self::load(context, tail_offset)?
+        .expect("Always exists")
+        .into_int_value();
+    let offset = context.builder().build_int_add(
+        tail_offset,
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+        "calldata_slice_offset",
+    );
+
+    let length_valid_block = context.append_basic_block("calldata_slice_length_valid_block");
+    let is_length_valid = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        length,
+        calldata_size,
+        "calldata_slice_is_length_valid",
+    );
+    context.build_conditional_branch_weighted(
+        is_length_valid,
+        length_valid_block,
+        failure_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
+    );
+    context.set_basic_block(length_valid_block);
+    let slice = CalldataSlice::new(offset, length, element_size);
+    build_bounds_check(
+        context,
+        calldata_size,
+        offset,
+        slice.size(context),
+        failure_block,
+    );
+    let success_block = context.basic_block();
+
+    context.set_basic_block(failure_block);
+    context.build_exit(
+        IntrinsicFunction::Revert,
+        context.field_const(0),
+        context.field_const(0),
+    );
+
+    context.set_basic_block(success_block);
+    Ok(slice)
+}
+
+///
+/// Checks that `size` bytes at `offset` are within the calldata of `calldata_size` bytes,
+/// jumping to `failure_block` otherwise, and continues in a new block.
+///
+/// The check is `offset <= calldata_size && size <= calldata_size - offset`, which does not
+/// overflow, unlike `offset + size <= calldata_size`.
+///
+fn build_bounds_check<'ctx, D>(
+    context: &Context<'ctx, D>,
+    calldata_size: inkwell::values::IntValue<'ctx>,
+    offset: inkwell::values::IntValue<'ctx>,
+    size: inkwell::values::IntValue<'ctx>,
+    failure_block: inkwell::basic_block::BasicBlock<'ctx>,
+) where
+    D: Dependency,
+{
+    let is_offset_valid = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        offset,
+        calldata_size,
+        "calldata_bounds_is_offset_valid",
+    );
+    let size_left =
+        context
+            .builder()
+            .build_int_sub(calldata_size, offset, "calldata_bounds_size_left");
+    let is_size_valid = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        size,
+        size_left,
+        "calldata_bounds_is_size_valid",
+    );
+    let is_valid =
+        context
+            .builder()
+            .build_and(is_offset_valid, is_size_valid, "calldata_bounds_is_valid");
+
+    let success_block = context.append_basic_block("calldata_bounds_valid_block");
+    context.build_conditional_branch_weighted(
+        is_valid,
+        success_block,
+        failure_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
+    );
+    context.set_basic_block(success_block);
+}
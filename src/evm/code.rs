@@ -0,0 +1,39 @@
+//!
+//! Translates the code page operations.
+//!
+
+use crate::context::address_space::AddressSpace;
+use crate::context::Context;
+use crate::Dependency;
+
+///
+/// Translates the constant load from the code page of the executed contract.
+///
+/// The code page is read-only and consists of 32-byte cells, so `offset` is the byte offset of
+/// a cell, e.g. of a constant placed in the data section after the instructions.
+/// A constant `offset` not aligned to the cell size is rejected.
+///
+pub fn load<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    offset: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    if let Some(offset) = context.const_u64(offset) {
+        if offset % (compiler_common::SIZE_FIELD as u64) != 0 {
+            return Err(context.error(
+                "code load",
+                format!(
+                    "the offset {} is not aligned to the code page cell size {}",
+                    offset,
+                    compiler_common::SIZE_FIELD
+                ),
+            ));
+        }
+    }
+
+    let pointer = context.access_memory(offset, AddressSpace::Code, "code_load_pointer");
+    let value = context.build_load(pointer, "code_load_value");
+    Ok(Some(value))
+}
@@ -2,6 +2,10 @@
 //! Translates the context getter instructions.
 //!
 
+use inkwell::values::BasicValue;
+
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::optimizer::settings::compatibility_mode::CompatibilityMode;
 use crate::context::Context;
 use crate::Dependency;
 
@@ -14,13 +18,7 @@ pub fn gas_limit<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "blockErgsLimit()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "blockErgsLimit()").map(Some)
 }
 
 ///
@@ -32,13 +30,7 @@ pub fn gas_price<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "ergsPrice()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "ergsPrice()").map(Some)
 }
 
 ///
@@ -50,13 +42,7 @@ pub fn origin<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "origin()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "origin()").map(Some)
 }
 
 ///
@@ -68,13 +54,7 @@ pub fn chain_id<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "chainId()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "chainId()").map(Some)
 }
 
 ///
@@ -86,13 +66,7 @@ pub fn block_number<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "getBlockNumber()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "getBlockNumber()").map(Some)
 }
 
 ///
@@ -104,13 +78,7 @@ pub fn block_timestamp<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "getBlockTimestamp()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "getBlockTimestamp()").map(Some)
 }
 
 ///
@@ -141,13 +109,7 @@ pub fn difficulty<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "difficulty()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "difficulty()").map(Some)
 }
 
 ///
@@ -159,13 +121,7 @@ pub fn coinbase<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "coinbase()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "coinbase()").map(Some)
 }
 
 ///
@@ -177,29 +133,136 @@ pub fn basefee<'ctx, D>(
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
-        context,
-        context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "baseFee()",
-        vec![],
-    )
-    .map(Some)
+    request_cached(context, "baseFee()").map(Some)
 }
 
 ///
 /// Translates the `memory_size` instruction.
 ///
+/// The heap size is only known to the VM, so it is extracted from the `meta` intrinsic result,
+/// where it occupies the second 64-bit slot. The system context cannot know the heap size of
+/// its caller, so it must not be requested from there.
+///
 pub fn msize<'ctx, D>(
     context: &mut Context<'ctx, D>,
 ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
 where
     D: Dependency,
 {
-    crate::evm::contract::request::request(
+    let meta = context
+        .build_call(
+            context.get_intrinsic_function(IntrinsicFunction::Meta),
+            &[],
+            "msize_meta",
+        )
+        .expect("Always exists");
+    let meta_shifted = context.builder().build_right_shift(
+        meta.into_int_value(),
+        context.field_const((compiler_common::BITLENGTH_X32 * 2) as u64),
+        false,
+        "msize_meta_shifted",
+    );
+    let heap_size = context.builder().build_and(
+        meta_shifted,
+        context.field_const(u32::MAX as u64),
+        "msize_heap_size",
+    );
+    Ok(Some(heap_size.as_basic_value_enum()))
+}
+
+///
+/// Translates the `pc` instruction.
+///
+/// The zkEVM program counter is not related to the EVM bytecode offsets, so the instruction is
+/// either rejected or translated to a constant zero, depending on the compatibility mode.
+///
+pub fn pc<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    match context.optimizer_settings().compatibility_mode {
+        CompatibilityMode::Strict => Err(context.error(
+            "pc",
+            "is not supported, since the zkEVM program counter has no EVM equivalent",
+        )),
+        CompatibilityMode::Permissive => Ok(Some(context.field_const(0).as_basic_value_enum())),
+    }
+}
+
+///
+/// Generates a system context request, caching the result in a global variable if the
+/// memoization is enabled in the optimizer settings.
+///
+/// The cached values are constant within a single contract call, so only the first executed
+/// request is performed, and the subsequent ones read the global variable.
+///
+fn request_cached<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    signature: &'static str,
+) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+where
+    D: Dependency,
+{
+    if !context
+        .optimizer_settings()
+        .is_system_request_memoization_enabled
+    {
+        return crate::evm::contract::request::request(
+            context,
+            context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
+            signature,
+            vec![],
+        );
+    }
+
+    let name = signature.trim_end_matches("()");
+    let value_pointer = context.declare_global(
+        format!(
+            "{}{}",
+            crate::r#const::GLOBAL_SYSTEM_REQUEST_CACHE_PREFIX,
+            name
+        )
+        .as_str(),
+        context.field_type(),
+    );
+    let is_cached_pointer = context.declare_global(
+        format!(
+            "{}{}_is_cached",
+            crate::r#const::GLOBAL_SYSTEM_REQUEST_CACHE_PREFIX,
+            name
+        )
+        .as_str(),
+        context.field_type(),
+    );
+
+    let request_block = context.append_basic_block("system_request_cache_miss_block");
+    let join_block = context.append_basic_block("system_request_cache_join_block");
+
+    let is_cached_value = context
+        .build_load(is_cached_pointer, "system_request_is_cached_value")
+        .into_int_value();
+    let is_cached = context.builder().build_int_compare(
+        inkwell::IntPredicate::NE,
+        is_cached_value,
+        context.field_const(0),
+        "system_request_is_cached",
+    );
+    context.build_conditional_branch(is_cached, join_block, request_block);
+
+    context.set_basic_block(request_block);
+    let value = crate::evm::contract::request::request(
         context,
         context.field_const(compiler_common::ADDRESS_SYSTEM_CONTEXT.into()),
-        "msize()",
+        signature,
         vec![],
-    )
-    .map(Some)
+    )?;
+    context.build_store(value_pointer, value);
+    context.build_store(is_cached_pointer, context.field_const(1));
+    context.build_unconditional_branch(join_block);
+
+    context.set_basic_block(join_block);
+    let value = context.build_load(value_pointer, "system_request_cached_value");
+    Ok(value)
 }
@@ -9,15 +9,22 @@ use inkwell::values::BasicValue;
 
 use crate::context::address_space::AddressSpace;
 use crate::context::argument::Argument;
+use crate::context::far_call_result::FarCallResult;
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::function::runtime::far_call::Kind as FarCallKind;
+use crate::context::function::runtime::Runtime;
+use crate::context::optimizer::settings::gas_forwarding::GasForwarding;
 use crate::context::Context;
 use crate::Dependency;
 
+use self::simulation::Simulation;
+
 ///
 /// Translates a contract call.
 ///
 /// If the `simulation_address` is specified, the call is substituted with another instruction
-/// according to the specification.
+/// according to the specification. The substitution is an error if the EraVM extensions are
+/// disabled.
 ///
 #[allow(clippy::too_many_arguments)]
 pub fn call<'ctx, D>(
@@ -35,33 +42,46 @@ pub fn call<'ctx, D>(
 where
     D: Dependency,
 {
-    match simulation_address {
-        Some(compiler_common::ADDRESS_TO_L1) => {
+    let simulation = simulation_address.and_then(Simulation::from_address);
+    if let (Some(simulation_address), Some(_)) = (simulation_address, simulation) {
+        if !context.optimizer_settings().are_eravm_extensions_enabled {
+            return Err(context.error(
+                "call",
+                format!(
+                    "the simulation address {:#x} requires the EraVM extensions to be enabled",
+                    simulation_address
+                ),
+            ));
+        }
+    }
+
+    match simulation {
+        Some(Simulation::ToL1) => {
             let is_first = gas;
             let in_0 = value.unwrap_or_else(|| context.field_const(0));
             let in_1 = input_offset;
             return simulation::to_l1(context, is_first, in_0, in_1).map(Some);
         }
-        Some(compiler_common::ADDRESS_CODE_ADDRESS) => {
+        Some(Simulation::CodeAddress) => {
             return simulation::code_source(context).map(Some);
         }
-        Some(compiler_common::ADDRESS_PRECOMPILE) => {
+        Some(Simulation::Precompile) => {
             let in_0 = gas;
             let ergs_left = input_offset;
 
             return simulation::precompile(context, in_0, ergs_left).map(Some);
         }
-        Some(compiler_common::ADDRESS_META) => {
+        Some(Simulation::Meta) => {
             return simulation::meta(context).map(Some);
         }
-        Some(compiler_common::ADDRESS_MIMIC_CALL) => {
+        Some(Simulation::MimicCall) => {
             let address = gas;
             let mimic = value.unwrap_or_else(|| context.field_const(0));
             let abi_data = input_offset;
 
             return simulation::mimic_call(
                 context,
-                context.runtime.system_mimic_call,
+                context.runtime.modify(FarCallKind::Mimic, false, true),
                 address,
                 mimic,
                 abi_data.as_basic_value_enum(),
@@ -69,7 +89,7 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_SYSTEM_MIMIC_CALL) => {
+        Some(Simulation::SystemMimicCall) => {
             let address = gas;
             let mimic = value.unwrap_or_else(|| context.field_const(0));
             let abi_data = input_offset;
@@ -78,7 +98,7 @@ where
 
             return simulation::mimic_call(
                 context,
-                context.runtime.system_mimic_call,
+                context.runtime.modify(FarCallKind::Mimic, false, true),
                 address,
                 mimic,
                 abi_data.as_basic_value_enum(),
@@ -86,14 +106,14 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_MIMIC_CALL_BYREF) => {
+        Some(Simulation::MimicCallByRef) => {
             let address = gas;
             let mimic = value.unwrap_or_else(|| context.field_const(0));
             let abi_data = context.get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?;
 
             return simulation::mimic_call(
                 context,
-                context.runtime.system_mimic_call_byref,
+                context.runtime.modify(FarCallKind::Mimic, true, true),
                 address,
                 mimic,
                 abi_data.as_basic_value_enum(),
@@ -101,7 +121,7 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_SYSTEM_MIMIC_CALL_BYREF) => {
+        Some(Simulation::SystemMimicCallByRef) => {
             let address = gas;
             let mimic = value.unwrap_or_else(|| context.field_const(0));
             let abi_data = context.get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?;
@@ -110,7 +130,7 @@ where
 
             return simulation::mimic_call(
                 context,
-                context.runtime.system_mimic_call_byref,
+                context.runtime.modify(FarCallKind::Mimic, true, true),
                 address,
                 mimic,
                 abi_data,
@@ -118,13 +138,15 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_RAW_FAR_CALL) => {
+        Some(Simulation::RawFarCall) => {
             let address = gas;
             let abi_data = input_offset;
 
             return simulation::raw_far_call(
                 context,
-                context.runtime.modify(function, false, false)?,
+                context
+                    .runtime
+                    .modify(Runtime::far_call_kind(function)?, false, false),
                 address,
                 abi_data.as_basic_value_enum(),
                 output_offset,
@@ -132,13 +154,15 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_RAW_FAR_CALL_BYREF) => {
+        Some(Simulation::RawFarCallByRef) => {
             let address = gas;
             let abi_data = context.get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?;
 
             return simulation::raw_far_call(
                 context,
-                context.runtime.modify(function, true, false)?,
+                context
+                    .runtime
+                    .modify(Runtime::far_call_kind(function)?, true, false),
                 address,
                 abi_data,
                 output_offset,
@@ -146,7 +170,7 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_SYSTEM_CALL) => {
+        Some(Simulation::SystemCall) => {
             let address = gas;
             let abi_data = input_offset;
             let extra_value_1 = value.unwrap_or_else(|| context.field_const(0));
@@ -154,7 +178,9 @@ where
 
             return simulation::system_call(
                 context,
-                context.runtime.modify(function, false, true)?,
+                context
+                    .runtime
+                    .modify(Runtime::far_call_kind(function)?, false, true),
                 address,
                 abi_data.as_basic_value_enum(),
                 output_offset,
@@ -164,7 +190,7 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_SYSTEM_CALL_BYREF) => {
+        Some(Simulation::SystemCallByRef) => {
             let address = gas;
             let abi_data = context.get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?;
             let extra_value_1 = value.unwrap_or_else(|| context.field_const(0));
@@ -172,7 +198,9 @@ where
 
             return simulation::system_call(
                 context,
-                context.runtime.modify(function, true, true)?,
+                context
+                    .runtime
+                    .modify(Runtime::far_call_kind(function)?, true, true),
                 address,
                 abi_data,
                 output_offset,
@@ -182,63 +210,65 @@ where
             )
             .map(Some);
         }
-        Some(compiler_common::ADDRESS_SET_CONTEXT_VALUE_CALL) => {
+        Some(Simulation::SetContextValue) => {
             let value = value.unwrap_or_else(|| context.field_const(0));
 
             return simulation::set_context_value(context, value).map(Some);
         }
-        Some(compiler_common::ADDRESS_SET_PUBDATA_PRICE) => {
+        Some(Simulation::SetPubdataPrice) => {
             let price = gas;
 
             return simulation::set_pubdata_price(context, price).map(Some);
         }
-        Some(compiler_common::ADDRESS_INCREMENT_TX_COUNTER) => {
+        Some(Simulation::IncrementTxCounter) => {
             return simulation::increment_tx_counter(context).map(Some);
         }
-        Some(compiler_common::ADDRESS_GET_GLOBAL_PTR_CALLDATA) => {
+        Some(Simulation::GetGlobalPtrCalldata) => {
             return simulation::get_global(context, crate::r#const::GLOBAL_INDEX_CALLDATA_ABI)
                 .map(Some);
         }
-        Some(compiler_common::ADDRESS_GET_GLOBAL_CALL_FLAGS) => {
+        Some(Simulation::GetGlobalCallFlags) => {
             return simulation::get_global(context, crate::r#const::GLOBAL_INDEX_CALL_FLAGS)
                 .map(Some);
         }
-        Some(compiler_common::ADDRESS_GET_GLOBAL_EXTRA_ABI_DATA_1) => {
+        Some(Simulation::GetGlobalExtraAbiData1) => {
             return simulation::get_global(context, crate::r#const::GLOBAL_INDEX_EXTRA_ABI_DATA_1)
                 .map(Some);
         }
-        Some(compiler_common::ADDRESS_GET_GLOBAL_EXTRA_ABI_DATA_2) => {
+        Some(Simulation::GetGlobalExtraAbiData2) => {
             return simulation::get_global(context, crate::r#const::GLOBAL_INDEX_EXTRA_ABI_DATA_2)
                 .map(Some);
         }
-        Some(compiler_common::ADDRESS_GET_GLOBAL_PTR_RETURN_DATA) => {
+        Some(Simulation::GetGlobalPtrReturnData) => {
             return simulation::get_global(context, crate::r#const::GLOBAL_INDEX_RETURN_DATA_ABI)
                 .map(Some);
         }
-        Some(compiler_common::ADDRESS_ACTIVE_PTR_LOAD_CALLDATA) => {
+        Some(Simulation::ActivePtrLoadCalldata) => {
             return simulation::calldata_ptr_to_active(context).map(Some);
         }
-        Some(compiler_common::ADDRESS_ACTIVE_PTR_LOAD_RETURN_DATA) => {
+        Some(Simulation::ActivePtrLoadReturnData) => {
             return simulation::return_data_ptr_to_active(context).map(Some);
         }
-        Some(compiler_common::ADDRESS_ACTIVE_PTR_ADD) => {
+        Some(Simulation::ActivePtrAdd) => {
             let offset = gas;
 
             return simulation::active_ptr_add_assign(context, offset).map(Some);
         }
-        Some(compiler_common::ADDRESS_ACTIVE_PTR_SHRINK) => {
+        Some(Simulation::ActivePtrShrink) => {
             let offset = gas;
 
             return simulation::active_ptr_shrink_assign(context, offset).map(Some);
         }
-        Some(compiler_common::ADDRESS_ACTIVE_PTR_PACK) => {
+        Some(Simulation::ActivePtrPack) => {
             let data = gas;
 
             return simulation::active_ptr_pack_assign(context, data).map(Some);
         }
-        _ => {}
+        None => {}
     }
 
+    let address = context.to_address(address);
+
     let identity_block = context.append_basic_block("contract_call_identity_block");
     let ordinary_block = context.append_basic_block("contract_call_ordinary_block");
     let join_block = context.append_basic_block("contract_call_join_block");
@@ -246,14 +276,14 @@ where
     let result_pointer = context.build_alloca(context.field_type(), "contract_call_result_pointer");
     context.build_store(result_pointer, context.field_const(0));
 
-    context.builder().build_switch(
+    context.build_switch(
         address,
         ordinary_block,
         &[(
             context.field_const(compiler_common::ADDRESS_IDENTITY.into()),
             identity_block,
         )],
-    );
+    )?;
 
     {
         context.set_basic_block(identity_block);
@@ -263,6 +293,7 @@ where
     }
 
     context.set_basic_block(ordinary_block);
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
     let result = if let Some(value) = value {
         call_default_wrapped(
             context,
@@ -297,194 +328,272 @@ where
 }
 
 ///
-/// Translates the Yul `linkersymbol` instruction.
+/// Translates a contract call, returning the raw `i1` success flag.
 ///
-pub fn linker_symbol<'ctx, D>(
+/// Unlike `call`, the flag is neither extended to the field type nor passed through a stack
+/// slot, which produces tighter IR for the front-ends branching on the call success right away.
+/// The simulation addresses are not supported, since their results are not success flags.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn call_status<'ctx, D>(
     context: &mut Context<'ctx, D>,
-    mut arguments: [Argument<'ctx>; 1],
-) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    value: Option<inkwell::values::IntValue<'ctx>>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
 where
     D: Dependency,
 {
-    let path = arguments[0]
-        .original
-        .take()
-        .ok_or_else(|| anyhow::anyhow!("Linker symbol literal is missing"))?;
+    let address = context.to_address(address);
 
-    Ok(Some(
-        context
-            .resolve_library(path.as_str())?
-            .as_basic_value_enum(),
-    ))
+    let identity_block = context.append_basic_block("contract_call_identity_block");
+    let ordinary_block = context.append_basic_block("contract_call_ordinary_block");
+    let join_block = context.append_basic_block("contract_call_join_block");
+
+    context.build_switch(
+        address,
+        ordinary_block,
+        &[(
+            context.field_const(compiler_common::ADDRESS_IDENTITY.into()),
+            identity_block,
+        )],
+    )?;
+
+    context.set_basic_block(identity_block);
+    call_identity(context, output_offset, input_offset, output_length)?;
+    let identity_status_block = context.basic_block();
+    context.build_unconditional_branch(join_block);
+
+    context.set_basic_block(ordinary_block);
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
+    let status = if let Some(value) = value {
+        call_default_wrapped_status(
+            context,
+            function,
+            gas,
+            value,
+            address,
+            input_offset,
+            input_length,
+            output_offset,
+            output_length,
+        )
+    } else {
+        call_default_status(
+            context,
+            function,
+            gas,
+            address,
+            input_offset,
+            input_length,
+            output_offset,
+            output_length,
+        )
+    }?;
+    let ordinary_status_block = context.basic_block();
+    context.build_unconditional_branch(join_block);
+
+    context.set_basic_block(join_block);
+    let status_phi = context
+        .builder()
+        .build_phi(context.bool_type(), "contract_call_status");
+    status_phi.add_incoming(&[
+        (&context.bool_const(true), identity_status_block),
+        (&status, ordinary_status_block),
+    ]);
+    Ok(status_phi.as_basic_value().into_int_value())
 }
 
 ///
-/// Generates an ABI data for a default call.
+/// Generates the Ether `value` transfer to `address` with empty calldata and return data.
 ///
-pub fn abi_data<'ctx, D>(
+/// The `gas` is forwarded as is, so it must have been mapped by the gas forwarding policy.
+/// The non-zero values are redirected through the `msg.value` simulator, whereas the constant
+/// zero value skips the value check altogether.
+///
+pub fn transfer<'ctx, D>(
     context: &mut Context<'ctx, D>,
-    input_offset: inkwell::values::IntValue<'ctx>,
-    input_length: inkwell::values::IntValue<'ctx>,
     gas: inkwell::values::IntValue<'ctx>,
-    address_space: AddressSpace,
-    is_system_call: bool,
+    address: inkwell::values::IntValue<'ctx>,
+    value: inkwell::values::IntValue<'ctx>,
 ) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
 where
     D: Dependency,
 {
-    let input_offset_truncated = context.builder().build_and(
-        input_offset,
-        context.field_const(u32::MAX as u64),
-        "abi_data_input_offset_truncated",
-    );
-    let input_length_truncated = context.builder().build_and(
-        input_length,
-        context.field_const(u32::MAX as u64),
-        "abi_data_input_length_truncated",
-    );
-    let gas_truncated = context.builder().build_and(
-        gas,
-        context.field_const(u32::MAX as u64),
-        "abi_data_gas_truncated",
-    );
-
-    let input_offset_shifted = context.builder().build_left_shift(
-        input_offset_truncated,
-        context.field_const((compiler_common::BITLENGTH_X32 * 2) as u64),
-        "abi_data_input_offset_shifted",
-    );
-    let input_length_shifted = context.builder().build_left_shift(
-        input_length_truncated,
-        context.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
-        "abi_data_input_length_shifted",
-    );
-    let gas_shifted = context.builder().build_left_shift(
-        gas_truncated,
-        context.field_const((compiler_common::BITLENGTH_X32 * 6) as u64),
-        "abi_data_gas_shifted",
-    );
+    let function = context.runtime.modify(FarCallKind::Far, false, false);
+    let address = context.to_address(address);
+    let empty = context.field_const(0);
 
-    let mut abi_data = context.builder().build_int_add(
-        input_offset_shifted,
-        input_length_shifted,
-        "abi_data_offset_and_length",
-    );
-    abi_data = context
-        .builder()
-        .build_int_add(abi_data, gas_shifted, "abi_data_add_gas");
-    if let AddressSpace::HeapAuxiliary = address_space {
-        let auxiliary_heap_marker_shifted = context.builder().build_left_shift(
-            context.field_const(zkevm_opcode_defs::FarCallForwardPageType::UseAuxHeap as u64),
-            context.field_const(
-                (compiler_common::BITLENGTH_X32 * 7 + compiler_common::BITLENGTH_BYTE) as u64,
-            ),
-            "abi_data_auxiliary_heap_marker_shifted",
-        );
-        abi_data = context.builder().build_int_add(
-            abi_data,
-            auxiliary_heap_marker_shifted,
-            "abi_data_add_heap_auxiliary_marker",
-        );
-    }
-    if is_system_call {
-        let auxiliary_heap_marker_shifted = context.builder().build_left_shift(
-            context.field_const(zkevm_opcode_defs::FarCallForwardPageType::UseAuxHeap as u64),
-            context.field_const(
-                ((compiler_common::BITLENGTH_X32 * 7) + (compiler_common::BITLENGTH_BYTE * 3))
-                    as u64,
-            ),
-            "abi_data_system_call_marker_shifted",
-        );
-        abi_data = context.builder().build_int_add(
-            abi_data,
-            auxiliary_heap_marker_shifted,
-            "abi_data_add_system_call_marker",
-        );
+    if value.is_constant_int() && value.is_null() {
+        call_default(context, function, gas, address, empty, empty, empty, empty)
+    } else {
+        call_default_wrapped(
+            context, function, gas, value, address, empty, empty, empty, empty,
+        )
     }
-
-    Ok(abi_data.as_basic_value_enum())
 }
 
 ///
-/// The default call wrapper, which redirects the call to the `msg.value` simulator if `msg.value`
-/// is not zero.
+/// Translates the `delegatecall` instruction.
+///
+/// The callee code is executed in the context of the current contract, so `msg.sender`,
+/// `msg.value`, and the storage are preserved. Thus, in the callee the `address` instruction
+/// returns the current contract address, whereas the code address intrinsic returns the callee
+/// address, which must be used to tell whether the code is being delegate-called.
+///
+/// The value cannot be transferred by a delegate call, so a `value` known to be non-zero is
+/// rejected, and a runtime one is checked to be zero, reverting with empty data otherwise.
+///
+/// If `is_byref` is set, the input is passed as the active fat pointer, and `input_offset` and
+/// `input_length` are ignored.
 ///
 #[allow(clippy::too_many_arguments)]
-fn call_default_wrapped<'ctx, D>(
+pub fn delegatecall<'ctx, D>(
     context: &mut Context<'ctx, D>,
-    function: inkwell::values::FunctionValue<'ctx>,
     gas: inkwell::values::IntValue<'ctx>,
-    value: inkwell::values::IntValue<'ctx>,
     address: inkwell::values::IntValue<'ctx>,
+    value: Option<inkwell::values::IntValue<'ctx>>,
     input_offset: inkwell::values::IntValue<'ctx>,
     input_length: inkwell::values::IntValue<'ctx>,
     output_offset: inkwell::values::IntValue<'ctx>,
     output_length: inkwell::values::IntValue<'ctx>,
-) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+    is_byref: bool,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
 where
     D: Dependency,
 {
-    let value_zero_block = context.append_basic_block("contract_call_value_zero_block");
-    let value_non_zero_block = context.append_basic_block("contract_call_value_non_zero_block");
-    let value_join_block = context.append_basic_block("contract_call_value_join_block");
+    if let Some(value) = value {
+        if value.is_constant_int() {
+            if !value.is_null() {
+                return Err(context.error(
+                    "delegatecall",
+                    "the value cannot be transferred by a delegate call",
+                ));
+            }
+        } else {
+            let value_zero_block = context.append_basic_block("delegate_call_value_zero_block");
+            let value_non_zero_block =
+                context.append_basic_block("delegate_call_value_non_zero_block");
+            let is_value_zero =
+                crate::evm::ether_gas::is_value_zero(context, value, "delegate_call_is_value_zero");
+            context.build_conditional_branch_weighted(
+                is_value_zero,
+                value_zero_block,
+                value_non_zero_block,
+                Context::<D>::BRANCH_WEIGHT_LIKELY,
+                Context::<D>::BRANCH_WEIGHT_UNLIKELY,
+            );
+
+            context.set_basic_block(value_non_zero_block);
+            context.build_exit(
+                IntrinsicFunction::Revert,
+                context.field_const(0),
+                context.field_const(0),
+            );
 
-    let result_pointer =
-        context.build_alloca(context.field_type(), "contract_call_address_result_pointer");
-    context.build_store(result_pointer, context.field_const(0));
-    let is_value_zero = context.builder().build_int_compare(
-        inkwell::IntPredicate::EQ,
-        value,
-        context.field_const(0),
-        "contract_call_is_value_zero",
-    );
-    context.build_conditional_branch(is_value_zero, value_zero_block, value_non_zero_block);
+            context.set_basic_block(value_zero_block);
+        }
+    }
 
-    context.set_basic_block(value_non_zero_block);
-    let abi_data = abi_data(
+    let address = context.to_address(address);
+    if is_byref {
+        let abi_data = context.get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?;
+        return call_far_raw(
+            context,
+            context.runtime.modify(FarCallKind::Delegate, true, false),
+            address,
+            abi_data,
+            output_offset,
+            output_length,
+        )
+        .map(Some);
+    }
+
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
+    call_default_with_output(
         context,
+        context.runtime.modify(FarCallKind::Delegate, false, false),
+        gas,
+        address,
         input_offset,
         input_length,
-        gas,
         AddressSpace::Heap,
-        true,
-    )?;
-    let result = call_system(
-        context,
-        context.runtime.modify(function, false, true)?,
-        context.field_const(compiler_common::ADDRESS_MSG_VALUE.into()),
-        abi_data,
         output_offset,
         output_length,
-        value,
-        address,
-    )?;
-    context.build_store(result_pointer, result);
-    context.build_unconditional_branch(value_join_block);
+    )
+    .map(Some)
+}
 
-    context.set_basic_block(value_zero_block);
-    let result = call_default(
+///
+/// Translates the `staticcall` instruction.
+///
+/// The write protection is enforced by the VM, so any state modification in the callee,
+/// including the events and the calls with a non-zero value, makes the call fail. Like with
+/// the other calls, the first `output_length` bytes of the returned data are copied to the heap
+/// at `output_offset`, and the whole returned data becomes available via the return data
+/// instructions.
+///
+/// If `is_byref` is set, the input is passed as the active fat pointer, and `input_offset` and
+/// `input_length` are ignored.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn staticcall<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+    is_byref: bool,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let address = context.to_address(address);
+    if is_byref {
+        let abi_data = context.get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?;
+        return call_far_raw(
+            context,
+            context.runtime.modify(FarCallKind::Static, true, false),
+            address,
+            abi_data,
+            output_offset,
+            output_length,
+        )
+        .map(Some);
+    }
+
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
+    call_default_with_output(
         context,
-        function,
+        context.runtime.modify(FarCallKind::Static, false, false),
         gas,
         address,
         input_offset,
         input_length,
+        AddressSpace::Heap,
         output_offset,
         output_length,
-    )?;
-    context.build_store(result_pointer, result);
-    context.build_unconditional_branch(value_join_block);
-
-    context.set_basic_block(value_join_block);
-    let address = context.build_load(result_pointer, "contract_call_address_result");
-    Ok(address)
+    )
+    .map(Some)
 }
 
 ///
-/// Generates a default contract call, if the `msg.value` is zero.
+/// Generates a contract call expecting at least `expected_length` bytes of the returned data,
+/// e.g. a single word to be decoded.
+///
+/// The call failure is bubbled up. If the call has succeeded, but returned less data than
+/// expected, reverts with empty data before copying, so the short data is never decoded.
+/// Otherwise, the first `expected_length` bytes are copied to the heap at `output_offset`.
 ///
 #[allow(clippy::too_many_arguments)]
-fn call_default<'ctx, D>(
+pub fn call_and_copy_exact<'ctx, D>(
     context: &mut Context<'ctx, D>,
     function: inkwell::values::FunctionValue<'ctx>,
     gas: inkwell::values::IntValue<'ctx>,
@@ -492,19 +601,13 @@ fn call_default<'ctx, D>(
     input_offset: inkwell::values::IntValue<'ctx>,
     input_length: inkwell::values::IntValue<'ctx>,
     output_offset: inkwell::values::IntValue<'ctx>,
-    output_length: inkwell::values::IntValue<'ctx>,
-) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+    expected_length: u64,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
 where
     D: Dependency,
 {
-    let join_block = context.append_basic_block("contract_call_join_block");
-
-    let status_code_result_pointer = context.build_alloca(
-        context.field_type(),
-        "contract_call_result_status_code_pointer",
-    );
-    context.build_store(status_code_result_pointer, context.field_const(0));
-
+    let address = context.to_address(address);
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
     let abi_data = abi_data(
         context,
         input_offset,
@@ -512,88 +615,588 @@ where
         gas,
         AddressSpace::Heap,
         false,
-    )?
-    .into_int_value();
-
-    let result_pointer = context
-        .build_invoke_far_call(
-            function,
-            vec![
-                abi_data.as_basic_value_enum(),
-                address.as_basic_value_enum(),
-            ],
-            "contract_call_external",
-        )
-        .expect("IntrinsicFunction always returns a flag");
+    )?;
 
-    let result_abi_data_pointer = unsafe {
-        context.builder().build_gep(
-            result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_zero(),
-            ],
-            "contract_call_external_result_abi_data_pointer",
-        )
-    };
-    let result_abi_data = context.build_load(
-        result_abi_data_pointer,
-        "contract_call_external_result_abi_data",
-    );
-    let result_abi_data_casted = context.builder().build_pointer_cast(
-        result_abi_data.into_pointer_value(),
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "contract_call_external_result_abi_data_casted",
+    let result = context.build_invoke_far_call(
+        function,
+        vec![abi_data, address.as_basic_value_enum()],
+        "contract_call_exact_external",
     );
-
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "contract_call_external_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context.build_load(
-        result_status_code_pointer,
-        "contract_call_external_result_status_code_boolean",
+    result.bubble_revert(context);
+    context.write_abi_return_data(result.abi_data);
+
+    let length_valid_block = context.append_basic_block("contract_call_exact_length_valid_block");
+    let length_invalid_block =
+        context.append_basic_block("contract_call_exact_length_invalid_block");
+    let expected_length = context.field_const(expected_length);
+    let is_length_valid = context.builder().build_int_compare(
+        inkwell::IntPredicate::UGE,
+        result.data_length(context),
+        expected_length,
+        "contract_call_exact_is_length_valid",
     );
-    let result_status_code = context.builder().build_int_z_extend_or_bit_cast(
-        result_status_code_boolean.into_int_value(),
-        context.field_type(),
-        "contract_call_external_result_status_code",
+    context.build_conditional_branch_weighted(
+        is_length_valid,
+        length_valid_block,
+        length_invalid_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
     );
-    context.build_store(status_code_result_pointer, result_status_code);
 
-    let source = result_abi_data_casted;
-
-    let destination = context.access_memory(
-        output_offset,
-        AddressSpace::Heap,
-        "contract_call_destination",
+    context.set_basic_block(length_invalid_block);
+    context.build_exit(
+        IntrinsicFunction::Revert,
+        context.field_const(0),
+        context.field_const(0),
     );
 
-    context.build_memcpy(
-        IntrinsicFunction::MemoryCopyFromGeneric,
-        destination,
-        source,
-        output_length,
-        "contract_call_memcpy_from_child",
+    context.set_basic_block(length_valid_block);
+    result.copy_to_heap(context, output_offset, expected_length);
+    Ok(None)
+}
+
+///
+/// Generates an ERC-20 token call tolerating the tokens which do not return a value, that is,
+/// the `SafeERC20` pattern.
+///
+/// The call failure is bubbled up. If the call has succeeded, the token is only considered
+/// successful if it has either returned nothing and has code, or returned a word equal to `true`.
+/// Otherwise, reverts with the `Error(string)` reason.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn safe_transfer<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let call_success_block = context.append_basic_block("safe_transfer_call_success_block");
+    let call_failure_block = context.append_basic_block("safe_transfer_call_failure_block");
+    let no_return_block = context.append_basic_block("safe_transfer_no_return_block");
+    let return_block = context.append_basic_block("safe_transfer_return_block");
+    let success_block = context.append_basic_block("safe_transfer_success_block");
+    let failure_block = context.append_basic_block("safe_transfer_failure_block");
+
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
+    let is_call_successful = call_default_status(
+        context,
+        function,
+        gas,
+        address,
+        input_offset,
+        input_length,
+        output_offset,
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+    )?;
+    context.build_conditional_branch_weighted(
+        is_call_successful,
+        call_success_block,
+        call_failure_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
     );
 
-    context.write_abi_return_data(result_abi_data.into_pointer_value());
-    context.build_unconditional_branch(join_block);
+    context.set_basic_block(call_failure_block);
+    let return_data_size = crate::evm::return_data::size(context)?
+        .expect("Always exists")
+        .into_int_value();
+    crate::evm::return_data::copy(
+        context,
+        context.field_const(0),
+        context.field_const(0),
+        return_data_size,
+    )?;
+    context.build_exit(
+        IntrinsicFunction::Revert,
+        context.field_const(0),
+        return_data_size,
+    );
 
-    context.set_basic_block(join_block);
-    let status_code_result =
-        context.build_load(status_code_result_pointer, "contract_call_status_code");
-    Ok(status_code_result)
+    context.set_basic_block(call_success_block);
+    let return_data_size = crate::evm::return_data::size(context)?
+        .expect("Always exists")
+        .into_int_value();
+    let is_return_data_empty = context.builder().build_int_compare(
+        inkwell::IntPredicate::EQ,
+        return_data_size,
+        context.field_const(0),
+        "safe_transfer_is_return_data_empty",
+    );
+    context.build_conditional_branch(is_return_data_empty, no_return_block, return_block);
+
+    context.set_basic_block(no_return_block);
+    let has_code = crate::evm::ext_code::has_code(context, address)?;
+    context.build_conditional_branch(has_code, success_block, failure_block);
+
+    context.set_basic_block(return_block);
+    let is_return_data_word = context.builder().build_int_compare(
+        inkwell::IntPredicate::UGE,
+        return_data_size,
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+        "safe_transfer_is_return_data_word",
+    );
+    let returned = crate::evm::memory::load(context, output_offset)?
+        .expect("Always exists")
+        .into_int_value();
+    let is_returned_true = context.builder().build_int_compare(
+        inkwell::IntPredicate::EQ,
+        returned,
+        context.field_const(1),
+        "safe_transfer_is_returned_true",
+    );
+    let is_successful = context.builder().build_and(
+        is_return_data_word,
+        is_returned_true,
+        "safe_transfer_is_successful",
+    );
+    context.build_conditional_branch(is_successful, success_block, failure_block);
+
+    context.set_basic_block(failure_block);
+    context.build_revert_error_string(crate::r#const::SAFE_TRANSFER_ERROR_MESSAGE);
+
+    context.set_basic_block(success_block);
+    Ok(None)
+}
+
+///
+/// Translates the Yul `linkersymbol` instruction.
+///
+pub fn linker_symbol<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    mut arguments: [Argument<'ctx>; 1],
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let path = arguments[0]
+        .original
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("Linker symbol literal is missing"))?;
+
+    if context.optimizer_settings().is_deferred_linking_enabled {
+        return Ok(Some(
+            context
+                .resolve_library_deferred(path.as_str())
+                .as_basic_value_enum(),
+        ));
+    }
+
+    Ok(Some(
+        context
+            .resolve_library(path.as_str())?
+            .as_basic_value_enum(),
+    ))
+}
+
+///
+/// Computes the ergs to forward to a far call from the `requested` gas according to `policy`.
+///
+pub fn forwarded_gas<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    requested: inkwell::values::IntValue<'ctx>,
+    policy: GasForwarding,
+) -> inkwell::values::IntValue<'ctx>
+where
+    D: Dependency,
+{
+    match policy {
+        GasForwarding::Requested => requested,
+        GasForwarding::AllButOne64th => {
+            let ergs_left = context
+                .build_call(
+                    context.get_intrinsic_function(IntrinsicFunction::ErgsLeft),
+                    &[],
+                    "forwarded_gas_ergs_left",
+                )
+                .expect("Always exists")
+                .into_int_value();
+            let ergs_left_one_64th = context.builder().build_int_unsigned_div(
+                ergs_left,
+                context.field_const(64),
+                "forwarded_gas_ergs_left_one_64th",
+            );
+            let ergs_limit = context.builder().build_int_sub(
+                ergs_left,
+                ergs_left_one_64th,
+                "forwarded_gas_ergs_limit",
+            );
+            let is_requested_less = context.builder().build_int_compare(
+                inkwell::IntPredicate::ULT,
+                requested,
+                ergs_limit,
+                "forwarded_gas_is_requested_less",
+            );
+            context
+                .builder()
+                .build_select(is_requested_less, requested, ergs_limit, "forwarded_gas")
+                .into_int_value()
+        }
+        GasForwarding::Stipend(stipend) => context.field_const(stipend as u64),
+    }
+}
+
+///
+/// Generates an ABI data for a default call.
+///
+pub fn abi_data<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address_space: AddressSpace,
+    is_system_call: bool,
+) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+where
+    D: Dependency,
+{
+    let input_offset_truncated =
+        context.build_truncate_x32(input_offset, "abi_data_input_offset_truncated");
+    let input_length_truncated =
+        context.build_truncate_x32(input_length, "abi_data_input_length_truncated");
+    let gas_truncated = context.build_truncate_x32(gas, "abi_data_gas_truncated");
+
+    let input_offset_shifted = context.builder().build_left_shift(
+        input_offset_truncated,
+        context.field_const((compiler_common::BITLENGTH_X32 * 2) as u64),
+        "abi_data_input_offset_shifted",
+    );
+    let input_length_shifted = context.builder().build_left_shift(
+        input_length_truncated,
+        context.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
+        "abi_data_input_length_shifted",
+    );
+    let gas_shifted = context.builder().build_left_shift(
+        gas_truncated,
+        context.field_const((compiler_common::BITLENGTH_X32 * 6) as u64),
+        "abi_data_gas_shifted",
+    );
+
+    let mut abi_data = context.builder().build_int_add(
+        input_offset_shifted,
+        input_length_shifted,
+        "abi_data_offset_and_length",
+    );
+    abi_data = context
+        .builder()
+        .build_int_add(abi_data, gas_shifted, "abi_data_add_gas");
+    if let AddressSpace::HeapAuxiliary = address_space {
+        let auxiliary_heap_marker_shifted = context.builder().build_left_shift(
+            context.field_const(zkevm_opcode_defs::FarCallForwardPageType::UseAuxHeap as u64),
+            context.field_const(
+                (compiler_common::BITLENGTH_X32 * 7 + compiler_common::BITLENGTH_BYTE) as u64,
+            ),
+            "abi_data_auxiliary_heap_marker_shifted",
+        );
+        abi_data = context.builder().build_int_add(
+            abi_data,
+            auxiliary_heap_marker_shifted,
+            "abi_data_add_heap_auxiliary_marker",
+        );
+    }
+    if is_system_call {
+        let auxiliary_heap_marker_shifted = context.builder().build_left_shift(
+            context.field_const(zkevm_opcode_defs::FarCallForwardPageType::UseAuxHeap as u64),
+            context.field_const(
+                ((compiler_common::BITLENGTH_X32 * 7) + (compiler_common::BITLENGTH_BYTE * 3))
+                    as u64,
+            ),
+            "abi_data_system_call_marker_shifted",
+        );
+        abi_data = context.builder().build_int_add(
+            abi_data,
+            auxiliary_heap_marker_shifted,
+            "abi_data_add_system_call_marker",
+        );
+    }
+
+    Ok(abi_data.as_basic_value_enum())
+}
+
+///
+/// The default call wrapper, which redirects the call to the `msg.value` simulator if `msg.value`
+/// is not zero.
+///
+#[allow(clippy::too_many_arguments)]
+fn call_default_wrapped<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    value: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+where
+    D: Dependency,
+{
+    let status = call_default_wrapped_status(
+        context,
+        function,
+        gas,
+        value,
+        address,
+        input_offset,
+        input_length,
+        output_offset,
+        output_length,
+    )?;
+    Ok(context
+        .builder()
+        .build_int_z_extend_or_bit_cast(status, context.field_type(), "contract_call_status_code")
+        .as_basic_value_enum())
+}
+
+///
+/// The default call wrapper returning the raw `i1` success flag.
+///
+/// See `call_default_wrapped`.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn call_default_wrapped_status<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    value: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
+where
+    D: Dependency,
+{
+    let value_zero_block = context.append_basic_block("contract_call_value_zero_block");
+    let value_non_zero_block = context.append_basic_block("contract_call_value_non_zero_block");
+    let value_join_block = context.append_basic_block("contract_call_value_join_block");
+
+    let is_value_zero =
+        crate::evm::ether_gas::is_value_zero(context, value, "contract_call_is_value_zero");
+    context.build_conditional_branch(is_value_zero, value_zero_block, value_non_zero_block);
+
+    context.set_basic_block(value_non_zero_block);
+    let abi_data = abi_data(
+        context,
+        input_offset,
+        input_length,
+        gas,
+        AddressSpace::Heap,
+        true,
+    )?;
+    let value_non_zero_status = call_system_far(
+        context,
+        context
+            .runtime
+            .modify(Runtime::far_call_kind(function)?, false, true),
+        context.field_const(compiler_common::ADDRESS_MSG_VALUE.into()),
+        abi_data,
+        output_offset,
+        output_length,
+        value,
+        address,
+    )
+    .status;
+    let value_non_zero_status_block = context.basic_block();
+    context.build_unconditional_branch(value_join_block);
+
+    context.set_basic_block(value_zero_block);
+    let value_zero_status = call_default_status(
+        context,
+        function,
+        gas,
+        address,
+        input_offset,
+        input_length,
+        output_offset,
+        output_length,
+    )?;
+    let value_zero_status_block = context.basic_block();
+    context.build_unconditional_branch(value_join_block);
+
+    context.set_basic_block(value_join_block);
+    let status_phi = context
+        .builder()
+        .build_phi(context.bool_type(), "contract_call_value_status");
+    status_phi.add_incoming(&[
+        (&value_non_zero_status, value_non_zero_status_block),
+        (&value_zero_status, value_zero_status_block),
+    ]);
+    Ok(status_phi.as_basic_value().into_int_value())
+}
+
+///
+/// Generates a default contract call, if the `msg.value` is zero.
+///
+#[allow(clippy::too_many_arguments)]
+fn call_default<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+where
+    D: Dependency,
+{
+    call_default_with_output(
+        context,
+        function,
+        gas,
+        address,
+        input_offset,
+        input_length,
+        AddressSpace::Heap,
+        output_offset,
+        output_length,
+    )
+}
+
+///
+/// Generates a default contract call, returning the raw `i1` success flag.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn call_default_status<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
+where
+    D: Dependency,
+{
+    let result = call_default_far(
+        context,
+        function,
+        gas,
+        address,
+        input_offset,
+        input_length,
+        AddressSpace::Heap,
+        output_offset,
+        output_length,
+    )?;
+    Ok(result.status)
+}
+
+///
+/// Generates a contract call with the output written to the auxiliary heap.
+///
+/// Is used by the constructors, where the heap layout is owned by the deploy code ABI packing
+/// and must not be clobbered. The input is still taken from the heap.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn call_aux_heap_output<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let gas = forwarded_gas(context, gas, context.optimizer_settings().gas_forwarding);
+    call_default_with_output(
+        context,
+        function,
+        gas,
+        address,
+        input_offset,
+        input_length,
+        AddressSpace::HeapAuxiliary,
+        output_offset,
+        output_length,
+    )
+    .map(Some)
+}
+
+///
+/// Generates a default contract call with the output written to `output_address_space`.
+///
+#[allow(clippy::too_many_arguments)]
+fn call_default_with_output<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_address_space: AddressSpace,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::BasicValueEnum<'ctx>>
+where
+    D: Dependency,
+{
+    let result = call_default_far(
+        context,
+        function,
+        gas,
+        address,
+        input_offset,
+        input_length,
+        output_address_space,
+        output_offset,
+        output_length,
+    )?;
+    Ok(result.status_code(context).as_basic_value_enum())
+}
+
+///
+/// Generates a default contract call with the output written to `output_address_space`,
+/// returning the far call result.
+///
+#[allow(clippy::too_many_arguments)]
+fn call_default_far<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    gas: inkwell::values::IntValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    output_address_space: AddressSpace,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<FarCallResult<'ctx>>
+where
+    D: Dependency,
+{
+    let abi_data = abi_data(
+        context,
+        input_offset,
+        input_length,
+        gas,
+        AddressSpace::Heap,
+        false,
+    )?;
+
+    let result = context.build_invoke_far_call(
+        function,
+        vec![abi_data, address.as_basic_value_enum()],
+        "contract_call_external",
+    );
+    result.copy_to(context, output_address_space, output_offset, output_length);
+    context.write_abi_return_data(result.abi_data);
+
+    Ok(result)
 }
 
 ///
@@ -644,75 +1247,18 @@ fn call_mimic<'ctx, D>(
 where
     D: Dependency,
 {
-    let join_block = context.append_basic_block("mimic_call_join_block");
-
-    let status_code_result_pointer = context.build_alloca(
-        context.field_type(),
-        "mimic_call_result_status_code_pointer",
-    );
-    context.build_store(status_code_result_pointer, context.field_const(0));
-
-    let mut far_call_arguments = vec![
-        abi_data.as_basic_value_enum(),
-        address.as_basic_value_enum(),
-    ];
+    let mut far_call_arguments = vec![abi_data, address.as_basic_value_enum()];
     far_call_arguments.extend(
         extra_abi_data
             .into_iter()
             .map(|value| value.as_basic_value_enum()),
     );
     far_call_arguments.push(mimic.as_basic_value_enum());
-    let far_call_result_pointer = context
-        .build_invoke_far_call(function, far_call_arguments, "mimic_call_external")
-        .expect("IntrinsicFunction always returns a flag");
-
-    let result_abi_data_pointer = unsafe {
-        context.builder().build_gep(
-            far_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_zero(),
-            ],
-            "mimic_call_external_result_abi_data_pointer",
-        )
-    };
-    let result_abi_data = context.build_load(
-        result_abi_data_pointer,
-        "mimic_call_external_result_abi_data",
-    );
 
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            far_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "mimic_call_external_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context.build_load(
-        result_status_code_pointer,
-        "mimic_call_external_result_status_code_boolean",
-    );
-    let result_status_code = context.builder().build_int_z_extend_or_bit_cast(
-        result_status_code_boolean.into_int_value(),
-        context.field_type(),
-        "mimic_call_external_result_status_code",
-    );
-    context.build_store(status_code_result_pointer, result_status_code);
-
-    context.write_abi_return_data(result_abi_data.into_pointer_value());
-    context.build_unconditional_branch(join_block);
+    let result = context.build_invoke_far_call(function, far_call_arguments, "mimic_call_external");
+    context.write_abi_return_data(result.abi_data);
 
-    context.set_basic_block(join_block);
-    let status_code_result =
-        context.build_load(status_code_result_pointer, "mimic_call_status_code");
-    Ok(status_code_result)
+    Ok(result.status_code(context).as_basic_value_enum())
 }
 
 ///
@@ -733,90 +1279,15 @@ fn call_far_raw<'ctx, D>(
 where
     D: Dependency,
 {
-    let join_block = context.append_basic_block("system_far_call_join_block");
-
-    let status_code_result_pointer = context.build_alloca(
-        context.field_type(),
-        "system_far_call_result_status_code_pointer",
-    );
-    context.build_store(status_code_result_pointer, context.field_const(0));
-
-    let far_call_result_pointer = context
-        .build_invoke_far_call(
-            function,
-            vec![abi_data, address.as_basic_value_enum()],
-            "system_far_call_external",
-        )
-        .expect("IntrinsicFunction always returns a flag");
-
-    let result_abi_data_pointer = unsafe {
-        context.builder().build_gep(
-            far_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_zero(),
-            ],
-            "system_far_call_external_result_abi_data_pointer",
-        )
-    };
-    let result_abi_data = context.build_load(
-        result_abi_data_pointer,
-        "system_far_call_external_result_abi_data",
-    );
-    let result_abi_data_casted = context.builder().build_pointer_cast(
-        result_abi_data.into_pointer_value(),
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "system_far_call_external_result_abi_data_casted",
-    );
-
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            far_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "system_far_call_external_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context.build_load(
-        result_status_code_pointer,
-        "system_far_call_external_result_status_code_boolean",
-    );
-    let result_status_code = context.builder().build_int_z_extend_or_bit_cast(
-        result_status_code_boolean.into_int_value(),
-        context.field_type(),
-        "system_far_call_external_result_status_code",
-    );
-    context.build_store(status_code_result_pointer, result_status_code);
-
-    let source = result_abi_data_casted;
-
-    let destination = context.access_memory(
-        output_offset,
-        AddressSpace::Heap,
-        "system_far_call_destination",
-    );
-
-    context.build_memcpy(
-        IntrinsicFunction::MemoryCopyFromGeneric,
-        destination,
-        source,
-        output_length,
-        "system_far_call_memcpy_from_child",
+    let result = context.build_invoke_far_call(
+        function,
+        vec![abi_data, address.as_basic_value_enum()],
+        "system_far_call_external",
     );
+    result.copy_to_heap(context, output_offset, output_length);
+    context.write_abi_return_data(result.abi_data);
 
-    context.write_abi_return_data(result_abi_data.into_pointer_value());
-    context.build_unconditional_branch(join_block);
-
-    context.set_basic_block(join_block);
-    let status_code_result =
-        context.build_load(status_code_result_pointer, "system_call_status_code");
-    Ok(status_code_result)
+    Ok(result.status_code(context).as_basic_value_enum())
 }
 
 ///
@@ -840,93 +1311,48 @@ fn call_system<'ctx, D>(
 where
     D: Dependency,
 {
-    let join_block = context.append_basic_block("system_far_call_join_block");
-
-    let status_code_result_pointer = context.build_alloca(
-        context.field_type(),
-        "system_far_call_result_status_code_pointer",
-    );
-    context.build_store(status_code_result_pointer, context.field_const(0));
-
-    let far_call_result_pointer = context
-        .build_invoke_far_call(
-            function,
-            vec![
-                abi_data,
-                address.as_basic_value_enum(),
-                extra_value_1.as_basic_value_enum(),
-                extra_value_2.as_basic_value_enum(),
-            ],
-            "system_far_call_external",
-        )
-        .expect("IntrinsicFunction always returns a flag");
-
-    let result_abi_data_pointer = unsafe {
-        context.builder().build_gep(
-            far_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_zero(),
-            ],
-            "system_far_call_external_result_abi_data_pointer",
-        )
-    };
-    let result_abi_data = context.build_load(
-        result_abi_data_pointer,
-        "system_far_call_external_result_abi_data",
-    );
-    let result_abi_data_casted = context.builder().build_pointer_cast(
-        result_abi_data.into_pointer_value(),
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "system_far_call_external_result_abi_data_casted",
-    );
-
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            far_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "system_far_call_external_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context.build_load(
-        result_status_code_pointer,
-        "system_far_call_external_result_status_code_boolean",
-    );
-    let result_status_code = context.builder().build_int_z_extend_or_bit_cast(
-        result_status_code_boolean.into_int_value(),
-        context.field_type(),
-        "system_far_call_external_result_status_code",
-    );
-    context.build_store(status_code_result_pointer, result_status_code);
-
-    let source = result_abi_data_casted;
-
-    let destination = context.access_memory(
+    let result = call_system_far(
+        context,
+        function,
+        address,
+        abi_data,
         output_offset,
-        AddressSpace::Heap,
-        "system_far_call_destination",
-    );
-
-    context.build_memcpy(
-        IntrinsicFunction::MemoryCopyFromGeneric,
-        destination,
-        source,
         output_length,
-        "system_far_call_memcpy_from_child",
+        extra_value_1,
+        extra_value_2,
     );
+    Ok(result.status_code(context).as_basic_value_enum())
+}
 
-    context.write_abi_return_data(result_abi_data.into_pointer_value());
-    context.build_unconditional_branch(join_block);
+///
+/// Generates a system call, returning the far call result.
+///
+#[allow(clippy::too_many_arguments)]
+fn call_system_far<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    function: inkwell::values::FunctionValue<'ctx>,
+    address: inkwell::values::IntValue<'ctx>,
+    abi_data: inkwell::values::BasicValueEnum<'ctx>,
+    output_offset: inkwell::values::IntValue<'ctx>,
+    output_length: inkwell::values::IntValue<'ctx>,
+    extra_value_1: inkwell::values::IntValue<'ctx>,
+    extra_value_2: inkwell::values::IntValue<'ctx>,
+) -> FarCallResult<'ctx>
+where
+    D: Dependency,
+{
+    let result = context.build_invoke_far_call(
+        function,
+        vec![
+            abi_data,
+            address.as_basic_value_enum(),
+            extra_value_1.as_basic_value_enum(),
+            extra_value_2.as_basic_value_enum(),
+        ],
+        "system_far_call_external",
+    );
+    result.copy_to_heap(context, output_offset, output_length);
+    context.write_abi_return_data(result.abi_data);
 
-    context.set_basic_block(join_block);
-    let status_code_result =
-        context.build_load(status_code_result_pointer, "system_call_status_code");
-    Ok(status_code_result)
+    result
 }
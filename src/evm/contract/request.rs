@@ -6,12 +6,15 @@ use inkwell::values::BasicValue;
 
 use crate::context::address_space::AddressSpace;
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::function::runtime::far_call::Kind as FarCallKind;
 use crate::context::Context;
 use crate::Dependency;
 
 ///
 /// Generates a custom request to a system contract.
 ///
+/// Reverts with empty data if the call fails or returns less than a word.
+///
 pub fn request<'ctx, D>(
     context: &mut Context<'ctx, D>,
     address: inkwell::values::IntValue<'ctx>,
@@ -24,7 +27,7 @@ where
     let call_success_block = context.append_basic_block("call_success_block");
     let call_error_block = context.append_basic_block("call_error_block");
 
-    let input_offset = context.field_const(crate::r#const::HEAP_AUX_OFFSET_EXTERNAL_CALL);
+    let input_offset = context.field_const(context.aux_heap_layout().external_call_offset());
     let input_length = context.field_const(
         (compiler_common::SIZE_X32 + (compiler_common::SIZE_FIELD * arguments.len())) as u64,
     );
@@ -43,7 +46,7 @@ where
         AddressSpace::HeapAuxiliary,
         "call_signature_pointer",
     );
-    let signature_value = context.field_const_str(signature_hash.as_str());
+    let signature_value = context.field_const_str_hex(signature_hash.as_str());
     context.build_store(signature_pointer, signature_value);
 
     for (index, argument) in arguments.into_iter().enumerate() {
@@ -52,68 +55,32 @@ where
             context.field_const(
                 (compiler_common::SIZE_X32 + index * compiler_common::SIZE_FIELD) as u64,
             ),
-            format!("call_argument_{}_offset", index).as_str(),
+            context.name_indexed("call_argument_offset", index).as_ref(),
         );
         let arguments_pointer = context.access_memory(
             arguments_offset,
             AddressSpace::HeapAuxiliary,
-            format!("call_argument_{}_pointer", index).as_str(),
+            context
+                .name_indexed("call_argument_pointer", index)
+                .as_ref(),
         );
         context.build_store(arguments_pointer, argument);
     }
 
-    let result_pointer = context
-        .build_invoke_far_call(
-            context.runtime.static_call,
-            vec![
-                abi_data.as_basic_value_enum(),
-                address.as_basic_value_enum(),
-            ],
-            "call",
-        )
-        .expect("Always returns a value");
-
-    let result_abi_data_pointer = unsafe {
-        context.builder().build_gep(
-            result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_zero(),
-            ],
-            "call_result_abi_data_pointer",
-        )
-    };
-    let result_abi_data = context.build_load(result_abi_data_pointer, "call_result_abi_data");
-    let result_abi_data_casted = context.builder().build_pointer_cast(
-        result_abi_data.into_pointer_value(),
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "call_result_abi_data_casted",
-    );
-
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "call_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context.build_load(
-        result_status_code_pointer,
-        "call_result_status_code_boolean",
+    let result = context.build_invoke_far_call(
+        context.runtime.modify(FarCallKind::Static, false, false),
+        vec![
+            abi_data.as_basic_value_enum(),
+            address.as_basic_value_enum(),
+        ],
+        "call",
     );
-    let return_pointer = context.build_alloca(context.field_type(), "call_return_pointer");
-    context.build_store(return_pointer, context.field_const(0));
-    context.build_conditional_branch(
-        result_status_code_boolean.into_int_value(),
+    context.build_conditional_branch_weighted(
+        result.status,
         call_success_block,
         call_error_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
     );
 
     context.set_basic_block(call_error_block);
@@ -124,6 +91,22 @@ where
     );
 
     context.set_basic_block(call_success_block);
-    let child_data_value = context.build_load(result_abi_data_casted, "call_child_address");
+    let length_valid_block = context.append_basic_block("call_length_valid_block");
+    let is_length_valid = context.builder().build_int_compare(
+        inkwell::IntPredicate::UGE,
+        result.data_length(context),
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+        "call_is_length_valid",
+    );
+    context.build_conditional_branch_weighted(
+        is_length_valid,
+        length_valid_block,
+        call_error_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
+    );
+
+    context.set_basic_block(length_valid_block);
+    let child_data_value = context.build_load(result.data_pointer(context), "call_child_address");
     Ok(child_data_value)
 }
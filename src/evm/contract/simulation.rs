@@ -13,6 +13,107 @@ use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 use crate::context::Context;
 use crate::Dependency;
 
+///
+/// The simulated instruction, substituted for the call to its address.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Simulation {
+    /// The message to L1.
+    ToL1,
+    /// The code source address getter.
+    CodeAddress,
+    /// The precompile call.
+    Precompile,
+    /// The VM metadata getter.
+    Meta,
+    /// The mimic call.
+    MimicCall,
+    /// The system mimic call.
+    SystemMimicCall,
+    /// The mimic call with the active pointer.
+    MimicCallByRef,
+    /// The system mimic call with the active pointer.
+    SystemMimicCallByRef,
+    /// The raw far call.
+    RawFarCall,
+    /// The raw far call with the active pointer.
+    RawFarCallByRef,
+    /// The system call.
+    SystemCall,
+    /// The system call with the active pointer.
+    SystemCallByRef,
+    /// The context value setter.
+    SetContextValue,
+    /// The pubdata price setter.
+    SetPubdataPrice,
+    /// The transaction counter increment.
+    IncrementTxCounter,
+    /// The calldata pointer getter.
+    GetGlobalPtrCalldata,
+    /// The call flags getter.
+    GetGlobalCallFlags,
+    /// The first extra ABI data getter.
+    GetGlobalExtraAbiData1,
+    /// The second extra ABI data getter.
+    GetGlobalExtraAbiData2,
+    /// The return data pointer getter.
+    GetGlobalPtrReturnData,
+    /// The calldata pointer load to the active pointer.
+    ActivePtrLoadCalldata,
+    /// The return data pointer load to the active pointer.
+    ActivePtrLoadReturnData,
+    /// The active pointer offset increment.
+    ActivePtrAdd,
+    /// The active pointer length decrement.
+    ActivePtrShrink,
+    /// The active pointer packing.
+    ActivePtrPack,
+}
+
+impl Simulation {
+    ///
+    /// Returns the instruction simulated by the call to `address`, if any.
+    ///
+    pub fn from_address(address: u16) -> Option<Self> {
+        match address {
+            compiler_common::ADDRESS_TO_L1 => Some(Self::ToL1),
+            compiler_common::ADDRESS_CODE_ADDRESS => Some(Self::CodeAddress),
+            compiler_common::ADDRESS_PRECOMPILE => Some(Self::Precompile),
+            compiler_common::ADDRESS_META => Some(Self::Meta),
+            compiler_common::ADDRESS_MIMIC_CALL => Some(Self::MimicCall),
+            compiler_common::ADDRESS_SYSTEM_MIMIC_CALL => Some(Self::SystemMimicCall),
+            compiler_common::ADDRESS_MIMIC_CALL_BYREF => Some(Self::MimicCallByRef),
+            compiler_common::ADDRESS_SYSTEM_MIMIC_CALL_BYREF => Some(Self::SystemMimicCallByRef),
+            compiler_common::ADDRESS_RAW_FAR_CALL => Some(Self::RawFarCall),
+            compiler_common::ADDRESS_RAW_FAR_CALL_BYREF => Some(Self::RawFarCallByRef),
+            compiler_common::ADDRESS_SYSTEM_CALL => Some(Self::SystemCall),
+            compiler_common::ADDRESS_SYSTEM_CALL_BYREF => Some(Self::SystemCallByRef),
+            compiler_common::ADDRESS_SET_CONTEXT_VALUE_CALL => Some(Self::SetContextValue),
+            compiler_common::ADDRESS_SET_PUBDATA_PRICE => Some(Self::SetPubdataPrice),
+            compiler_common::ADDRESS_INCREMENT_TX_COUNTER => Some(Self::IncrementTxCounter),
+            compiler_common::ADDRESS_GET_GLOBAL_PTR_CALLDATA => Some(Self::GetGlobalPtrCalldata),
+            compiler_common::ADDRESS_GET_GLOBAL_CALL_FLAGS => Some(Self::GetGlobalCallFlags),
+            compiler_common::ADDRESS_GET_GLOBAL_EXTRA_ABI_DATA_1 => {
+                Some(Self::GetGlobalExtraAbiData1)
+            }
+            compiler_common::ADDRESS_GET_GLOBAL_EXTRA_ABI_DATA_2 => {
+                Some(Self::GetGlobalExtraAbiData2)
+            }
+            compiler_common::ADDRESS_GET_GLOBAL_PTR_RETURN_DATA => {
+                Some(Self::GetGlobalPtrReturnData)
+            }
+            compiler_common::ADDRESS_ACTIVE_PTR_LOAD_CALLDATA => Some(Self::ActivePtrLoadCalldata),
+            compiler_common::ADDRESS_ACTIVE_PTR_LOAD_RETURN_DATA => {
+                Some(Self::ActivePtrLoadReturnData)
+            }
+            compiler_common::ADDRESS_ACTIVE_PTR_ADD => Some(Self::ActivePtrAdd),
+            compiler_common::ADDRESS_ACTIVE_PTR_SHRINK => Some(Self::ActivePtrShrink),
+            compiler_common::ADDRESS_ACTIVE_PTR_PACK => Some(Self::ActivePtrPack),
+            _ => None,
+        }
+    }
+}
+
 ///
 /// Generates a call to L1.
 ///
@@ -273,11 +374,8 @@ where
     match index {
         crate::r#const::GLOBAL_INDEX_CALLDATA_ABI => {
             let pointer = context.get_global(crate::r#const::GLOBAL_CALLDATA_POINTER)?;
-            let value = context.builder().build_ptr_to_int(
-                pointer.into_pointer_value(),
-                context.field_type(),
-                "calldata_abi_integer",
-            );
+            let value =
+                context.fat_ptr_to_int(pointer.into_pointer_value(), "calldata_abi_integer");
             Ok(value.as_basic_value_enum())
         }
         crate::r#const::GLOBAL_INDEX_CALL_FLAGS => {
@@ -307,11 +405,8 @@ where
         }
         crate::r#const::GLOBAL_INDEX_RETURN_DATA_ABI => {
             let pointer = context.get_global(crate::r#const::GLOBAL_RETURN_DATA_POINTER)?;
-            let value = context.builder().build_ptr_to_int(
-                pointer.into_pointer_value(),
-                context.field_type(),
-                "return_data_abi_integer",
-            );
+            let value =
+                context.fat_ptr_to_int(pointer.into_pointer_value(), "return_data_abi_integer");
             Ok(value.as_basic_value_enum())
         }
         _ => anyhow::bail!(
@@ -417,3 +512,24 @@ where
     context.set_global(crate::r#const::GLOBAL_ACTIVE_POINTER, active_pointer_packed);
     Ok(context.field_const(1).as_basic_value_enum())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Simulation;
+
+    #[test]
+    fn from_address() {
+        assert_eq!(
+            Simulation::from_address(compiler_common::ADDRESS_TO_L1),
+            Some(Simulation::ToL1)
+        );
+        assert_eq!(
+            Simulation::from_address(compiler_common::ADDRESS_ACTIVE_PTR_PACK),
+            Some(Simulation::ActivePtrPack)
+        );
+        assert_eq!(
+            Simulation::from_address(compiler_common::ADDRESS_IDENTITY),
+            None
+        );
+    }
+}
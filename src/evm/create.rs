@@ -2,9 +2,10 @@
 //! Translates the contract creation instructions.
 //!
 
-use inkwell::types::BasicType;
 use inkwell::values::BasicValue;
 
+use crate::context::far_call_result::FarCallResult;
+use crate::context::function::runtime::far_call::Kind as FarCallKind;
 use crate::context::Context;
 use crate::AddressSpace;
 use crate::Dependency;
@@ -72,6 +73,34 @@ where
     Ok(Some(address.as_basic_value_enum()))
 }
 
+///
+/// Translates the contract `create2` instruction with the salt given as a Yul literal.
+///
+/// The salt is parsed at compile time, so if the input offset and length are constants as well,
+/// the whole deployer call header is written with constant stores at constant addresses.
+///
+pub fn create2_literal<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    value: inkwell::values::IntValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    salt: &str,
+    address_space: AddressSpace,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let salt = context.try_field_const_str(salt)?;
+    create2(
+        context,
+        value,
+        input_offset,
+        input_length,
+        Some(salt),
+        address_space,
+    )
+}
+
 ///
 /// Translates the contract hash instruction, which is actually used to set the hash of the contract
 /// being created, or other related auxiliary data.
@@ -85,16 +114,16 @@ pub fn contract_hash<'ctx, D>(
 where
     D: Dependency,
 {
-    let parent = context.module().get_name().to_str().expect("Always valid");
+    let parent = context.contract_path();
 
     let contract_path = context.resolve_path(identifier.as_str())?;
-    if identifier.ends_with("_deployed") || contract_path.as_str() == parent {
+    if identifier.ends_with("_deployed") || contract_path == parent {
         return Ok(Some(context.field_const(0).as_basic_value_enum()));
     }
 
+    let artifact = context.compile_dependency(identifier.as_str(), contract_path.as_str())?;
     let hash_value = context
-        .compile_dependency(identifier.as_str())
-        .map(|hash| context.field_const_str(hash.as_str()))
+        .try_field_const_str_hex(artifact.hash.as_str())
         .map(inkwell::values::BasicValueEnum::IntValue)?;
 
     Ok(Some(hash_value))
@@ -121,10 +150,10 @@ pub fn header_size<'ctx, D>(
 where
     D: Dependency,
 {
-    let parent = context.module().get_name().to_str().expect("Always valid");
+    let parent = context.contract_path();
 
     let contract_path = context.resolve_path(identifier.as_str())?;
-    if identifier.ends_with("_deployed") || contract_path.as_str() == parent {
+    if identifier.ends_with("_deployed") || contract_path == parent {
         return Ok(Some(context.field_const(0).as_basic_value_enum()));
     }
 
@@ -166,105 +195,37 @@ where
         true,
     )?;
 
-    let signature_hash = crate::hashes::keccak256(signature.as_bytes());
-    let signature_pointer = context.access_memory(
-        input_offset,
-        address_space,
-        "deployer_call_signature_pointer",
-    );
-    let signature_value = context.field_const_str(signature_hash.as_str());
-    context.build_store(signature_pointer, signature_value);
-
-    let salt_offset = context.builder().build_int_add(
+    write_header(
+        context,
         input_offset,
-        context.field_const(compiler_common::SIZE_X32 as u64),
-        "deployer_call_salt_offset",
-    );
-    let salt_pointer =
-        context.access_memory(salt_offset, address_space, "deployer_call_salt_pointer");
-    let salt_value = salt.unwrap_or_else(|| context.field_const(0));
-    context.build_store(salt_pointer, salt_value);
-
-    let arguments_offset_offset = context.builder().build_int_add(
-        salt_offset,
-        context.field_const((compiler_common::SIZE_FIELD * 2) as u64),
-        "deployer_call_arguments_offset_offset",
-    );
-    let arguments_offset_pointer = context.access_memory(
-        arguments_offset_offset,
-        address_space,
-        "deployer_call_arguments_offset_pointer",
-    );
-    context.build_store(
-        arguments_offset_pointer,
-        context.field_const(
-            (HEADER_SIZE - (compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD)) as u64,
-        ),
-    );
-
-    let arguments_length_offset = context.builder().build_int_add(
-        arguments_offset_offset,
-        context.field_const(compiler_common::SIZE_FIELD as u64),
-        "deployer_call_arguments_length_offset",
-    );
-    let arguments_length_pointer = context.access_memory(
-        arguments_length_offset,
-        address_space,
-        "deployer_call_arguments_length_pointer",
-    );
-    let arguments_length_value = context.builder().build_int_sub(
         input_length,
-        context.field_const(HEADER_SIZE as u64),
-        "deployer_call_arguments_length",
+        signature,
+        salt,
+        address_space,
     );
-    context.build_store(arguments_length_pointer, arguments_length_value);
 
     let result_pointer = context.build_alloca(context.field_type(), "deployer_call_result_pointer");
     context.build_store(result_pointer, context.field_const(0));
-    let deployer_call_result_pointer_pointer_type = context
-        .structure_type(vec![
-            context
-                .integer_type(compiler_common::BITLENGTH_BYTE)
-                .ptr_type(AddressSpace::Generic.into())
-                .as_basic_type_enum(),
-            context
-                .integer_type(compiler_common::BITLENGTH_BOOLEAN)
-                .as_basic_type_enum(),
-        ])
-        .ptr_type(AddressSpace::Stack.into());
-    let deployer_call_result_pointer_pointer = context.build_alloca(
-        deployer_call_result_pointer_pointer_type,
-        "deployer_call_result_pointer_pointer",
-    );
-    context.build_store(
-        deployer_call_result_pointer_pointer,
-        deployer_call_result_pointer_pointer_type.const_zero(),
-    );
-    let is_value_zero = context.builder().build_int_compare(
-        inkwell::IntPredicate::EQ,
-        value,
-        context.field_const(0),
-        "deployer_call_is_value_zero",
+    let deployer_call_result_pointer = context.build_alloca(
+        FarCallResult::r#type(context),
+        "deployer_call_result_join_pointer",
     );
+    let is_value_zero =
+        crate::evm::ether_gas::is_value_zero(context, value, "deployer_call_is_value_zero");
     context.build_conditional_branch(is_value_zero, value_zero_block, value_non_zero_block);
 
     context.set_basic_block(value_zero_block);
-    let deployer_call_result_pointer = context
-        .build_invoke_far_call(
-            context.runtime.far_call,
-            vec![
-                abi_data.as_basic_value_enum(),
-                context
-                    .field_const(compiler_common::ADDRESS_CONTRACT_DEPLOYER.into())
-                    .as_basic_value_enum(),
-            ],
-            "deployer_call_ordinary",
-        )
-        .expect("Always returns a value");
-    context.build_store(
-        deployer_call_result_pointer_pointer,
-        deployer_call_result_pointer,
+    let deployer_call_result = context.build_invoke_far_call(
+        context.runtime.modify(FarCallKind::Far, false, false),
+        vec![
+            abi_data.as_basic_value_enum(),
+            context
+                .field_const(compiler_common::ADDRESS_CONTRACT_DEPLOYER.into())
+                .as_basic_value_enum(),
+        ],
+        "deployer_call_ordinary",
     );
+    deployer_call_result.store(context, deployer_call_result_pointer);
     context.build_unconditional_branch(value_join_block);
 
     context.set_basic_block(value_non_zero_block);
@@ -278,74 +239,28 @@ where
         system_call_bit,
         "deployer_call_value_and_system_call_bit",
     );
-    let deployer_call_result_pointer = context
-        .build_invoke_far_call(
-            context.runtime.system_far_call,
-            vec![
-                abi_data.as_basic_value_enum(),
-                context
-                    .field_const(compiler_common::ADDRESS_MSG_VALUE.into())
-                    .as_basic_value_enum(),
-                value_and_system_call_bit.as_basic_value_enum(),
-                context
-                    .field_const(compiler_common::ADDRESS_CONTRACT_DEPLOYER.into())
-                    .as_basic_value_enum(),
-            ],
-            "deployer_call_system",
-        )
-        .expect("Always returns a value");
-    context.build_store(
-        deployer_call_result_pointer_pointer,
-        deployer_call_result_pointer,
+    let deployer_call_result = context.build_invoke_far_call(
+        context.runtime.modify(FarCallKind::Far, false, true),
+        vec![
+            abi_data.as_basic_value_enum(),
+            context
+                .field_const(compiler_common::ADDRESS_MSG_VALUE.into())
+                .as_basic_value_enum(),
+            value_and_system_call_bit.as_basic_value_enum(),
+            context
+                .field_const(compiler_common::ADDRESS_CONTRACT_DEPLOYER.into())
+                .as_basic_value_enum(),
+        ],
+        "deployer_call_system",
     );
+    deployer_call_result.store(context, deployer_call_result_pointer);
     context.build_unconditional_branch(value_join_block);
 
     context.set_basic_block(value_join_block);
-    let deployer_call_result_pointer = context.build_load(
-        deployer_call_result_pointer_pointer,
-        "deployer_call_result_pointer_join",
-    );
-    let result_abi_data_pointer = unsafe {
-        context.builder().build_gep(
-            deployer_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_zero(),
-            ],
-            "deployer_call_result_abi_data_pointer",
-        )
-    };
-    let result_abi_data =
-        context.build_load(result_abi_data_pointer, "deployer_call_result_abi_data");
-    let result_abi_data_casted = context.builder().build_pointer_cast(
-        result_abi_data.into_pointer_value(),
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "deployer_call_result_abi_data_casted",
-    );
-
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            deployer_call_result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "contract_call_external_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context
-        .build_load(
-            result_status_code_pointer,
-            "contract_call_external_result_status_code_boolean",
-        )
-        .into_int_value();
+    let deployer_call_result = FarCallResult::load(context, deployer_call_result_pointer);
 
     let address_or_status_code = context.build_load(
-        result_abi_data_casted,
+        deployer_call_result.data_pointer(context),
         "deployer_call_address_or_status_code",
     );
     let is_address_or_status_code_non_zero = context.builder().build_int_compare(
@@ -356,7 +271,7 @@ where
     );
     let is_address_or_status_code_non_zero_with_exception = context.builder().build_and(
         is_address_or_status_code_non_zero,
-        result_status_code_boolean,
+        deployer_call_result.status,
         "deployer_call_is_address_or_status_code_non_zero_with_exception",
     );
     context.build_conditional_branch(
@@ -366,14 +281,84 @@ where
     );
 
     context.set_basic_block(success_block);
-    context.build_store(result_pointer, address_or_status_code);
+    let address = context.to_address(address_or_status_code.into_int_value());
+    context.build_store(result_pointer, address);
     context.build_unconditional_branch(join_block);
 
     context.set_basic_block(error_block);
-    context.write_abi_return_data_deployer(result_abi_data.into_pointer_value());
+    context.write_abi_return_data_deployer(deployer_call_result.abi_data);
     context.build_unconditional_branch(join_block);
 
     context.set_basic_block(join_block);
     let result = context.build_load(result_pointer, "deployer_call_result");
     Ok(result)
 }
+
+///
+/// Writes the deployer call header, except for the bytecode hash written by the front-end.
+///
+/// If the input offset and length are constants, the field offsets and the constructor arguments
+/// length are computed at compile time instead of emitting the address arithmetic.
+///
+fn write_header<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+    signature: &'static str,
+    salt: Option<inkwell::values::IntValue<'ctx>>,
+    address_space: AddressSpace,
+) where
+    D: Dependency,
+{
+    let field_shifts = [
+        0,
+        compiler_common::SIZE_X32,
+        compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD * 2,
+        compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD * 3,
+    ];
+
+    let constant_offset = context
+        .const_u64(input_offset)
+        .filter(|offset| offset.checked_add(HEADER_SIZE as u64).is_some());
+    let constant_length = context
+        .const_u64(input_length)
+        .filter(|length| *length >= HEADER_SIZE as u64);
+
+    let (field_offsets, arguments_length) = match (constant_offset, constant_length) {
+        (Some(offset), Some(length)) => (
+            field_shifts.map(|shift| context.field_const(offset + shift as u64)),
+            context.field_const(length - HEADER_SIZE as u64),
+        ),
+        _ => {
+            let field_offsets = field_shifts.map(|shift| {
+                context.builder().build_int_add(
+                    input_offset,
+                    context.field_const(shift as u64),
+                    "deployer_call_header_field_offset",
+                )
+            });
+            let arguments_length = context.builder().build_int_sub(
+                input_length,
+                context.field_const(HEADER_SIZE as u64),
+                "deployer_call_arguments_length",
+            );
+            (field_offsets, arguments_length)
+        }
+    };
+
+    let signature_hash = crate::hashes::keccak256(signature.as_bytes());
+    let field_values = [
+        context.field_const_str_hex(signature_hash.as_str()),
+        salt.unwrap_or_else(|| context.field_const(0)),
+        context.field_const(
+            (HEADER_SIZE - (compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD)) as u64,
+        ),
+        arguments_length,
+    ];
+
+    for (offset, value) in field_offsets.into_iter().zip(field_values.into_iter()) {
+        let pointer =
+            context.access_memory(offset, address_space, "deployer_call_header_field_pointer");
+        context.build_store(pointer, value);
+    }
+}
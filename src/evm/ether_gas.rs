@@ -2,13 +2,21 @@
 //! Translates the value and balance operations.
 //!
 
+use inkwell::values::BasicValue;
+
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 use crate::context::Context;
 use crate::Dependency;
 
+/// The gas stipend requested by the Ether transfers, as in EVM.
+pub const TRANSFER_STIPEND: u64 = 2300;
+
 ///
 /// Translates the `gas` instruction.
 ///
+/// Returns the remaining ergs, which are not convertible to the EVM gas, so the value must
+/// only be used for the relative comparisons and forwarding to the calls.
+///
 pub fn gas<'ctx, D>(
     context: &mut Context<'ctx, D>,
 ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
@@ -31,11 +39,85 @@ pub fn value<'ctx, D>(
 where
     D: Dependency,
 {
-    Ok(context.build_call(
-        context.get_intrinsic_function(IntrinsicFunction::GetU128),
-        &[],
-        "get_u128_value",
-    ))
+    Ok(Some(callvalue(context).as_basic_value_enum()))
+}
+
+///
+/// Returns the `msg.value` of the current call, which is stored in the abstract `u128` register.
+///
+pub fn callvalue<'ctx, D>(context: &Context<'ctx, D>) -> inkwell::values::IntValue<'ctx>
+where
+    D: Dependency,
+{
+    context
+        .build_call(
+            context.get_intrinsic_function(IntrinsicFunction::GetU128),
+            &[],
+            "get_u128_value",
+        )
+        .expect("Always returns a value")
+        .into_int_value()
+}
+
+///
+/// Checks whether the Ether `value` passed to a call or deployment is zero, in which case
+/// the `msg.value` simulator is not involved.
+///
+pub fn is_value_zero<'ctx, D>(
+    context: &Context<'ctx, D>,
+    value: inkwell::values::IntValue<'ctx>,
+    name: &str,
+) -> inkwell::values::IntValue<'ctx>
+where
+    D: Dependency,
+{
+    context.builder().build_int_compare(
+        inkwell::IntPredicate::EQ,
+        value,
+        context.field_const(0),
+        name,
+    )
+}
+
+///
+/// Translates the Ether `value` transfer to `address`, as done by the Solidity `transfer` and
+/// `send`.
+///
+/// The ergs forwarded to the recipient are computed by `transfer_gas`.
+///
+/// Returns the call success flag, so `transfer` must revert if it is zero.
+///
+pub fn transfer<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    address: inkwell::values::IntValue<'ctx>,
+    value: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let gas = transfer_gas(context);
+    crate::evm::contract::transfer(context, gas, address, value).map(Some)
+}
+
+///
+/// Returns the ergs forwarded by the Ether transfers.
+///
+/// The EVM stipend is requested and mapped by the gas forwarding policy like the gas requested
+/// by the ordinary calls, so it is forwarded as is by default. The existing contracts rely on
+/// the stipend being too small for the recipient to reenter them, so it is only overridden by
+/// `GasForwarding::Stipend`, which must be large enough to cover the ergs costs of the recipient
+/// fallback, and allows the reentrancy accordingly.
+///
+pub fn transfer_gas<'ctx, D>(context: &mut Context<'ctx, D>) -> inkwell::values::IntValue<'ctx>
+where
+    D: Dependency,
+{
+    let requested = context.field_const(TRANSFER_STIPEND);
+    crate::evm::contract::forwarded_gas(
+        context,
+        requested,
+        context.optimizer_settings().gas_forwarding,
+    )
 }
 
 ///
@@ -56,3 +138,57 @@ where
     )
     .map(Some)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::optimizer::settings::gas_forwarding::GasForwarding;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn transfer_gas(name: &str, gas_forwarding: GasForwarding) -> Option<u64> {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let mut settings = OptimizerSettings::none();
+        settings.gas_forwarding = gas_forwarding;
+        let optimizer = Optimizer::new(settings).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(&llvm, name, optimizer, None, vec![]);
+
+        context.add_function(name, context.function_type(0, vec![]), None);
+        let function = context.functions[name].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        super::transfer_gas(&mut context).get_zero_extended_constant()
+    }
+
+    #[test]
+    fn transfer_gas_stipend_default() {
+        assert_eq!(
+            transfer_gas("transfer_gas_stipend_default", GasForwarding::default()),
+            Some(super::TRANSFER_STIPEND)
+        );
+    }
+
+    #[test]
+    fn transfer_gas_stipend_overridden() {
+        assert_eq!(
+            transfer_gas(
+                "transfer_gas_stipend_overridden",
+                GasForwarding::Stipend(10000)
+            ),
+            Some(10000)
+        );
+    }
+
+    #[test]
+    fn transfer_gas_all_but_one_64th() {
+        assert_eq!(
+            transfer_gas(
+                "transfer_gas_all_but_one_64th",
+                GasForwarding::AllButOne64th
+            ),
+            None
+        );
+    }
+}
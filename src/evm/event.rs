@@ -54,6 +54,100 @@ where
     Ok(None)
 }
 
+///
+/// Translates a log or event call with `topics_count` topics laid out in memory at
+/// `topics_offset`.
+///
+/// The topics are loaded one by one, since their number is known at compile time, and then
+/// emitted the same way as in `log`.
+///
+pub fn log_from_memory<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    topics_offset: inkwell::values::IntValue<'ctx>,
+    topics_count: usize,
+    data_offset: inkwell::values::IntValue<'ctx>,
+    data_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let mut topics = Vec::with_capacity(topics_count);
+    for index in 0..topics_count {
+        let topic_offset = context.builder().build_int_add(
+            topics_offset,
+            context.field_const((index * compiler_common::SIZE_FIELD) as u64),
+            context.name_indexed("event_topic_offset", index).as_ref(),
+        );
+        let topic_pointer = context.access_memory(
+            topic_offset,
+            AddressSpace::Heap,
+            context.name_indexed("event_topic_pointer", index).as_ref(),
+        );
+        let topic = context.build_load(
+            topic_pointer,
+            context.name_indexed("event_topic", index).as_ref(),
+        );
+        topics.push(topic.into_int_value());
+    }
+
+    log(context, data_offset, data_length, topics)
+}
+
+///
+/// The maximum number of topics an event may have, including `topic0`.
+///
+pub const TOPICS_MAX: usize = 4;
+
+///
+/// Returns the `topic0` of the event with `signature`, e.g. `Transfer(address,address,uint256)`,
+/// which is computed at compile time.
+///
+pub fn topic0<'ctx, D>(
+    context: &Context<'ctx, D>,
+    signature: &str,
+) -> inkwell::values::IntValue<'ctx>
+where
+    D: Dependency,
+{
+    let hash = crate::hashes::keccak256(signature.as_bytes());
+    context.field_const_str(format!("0x{}", hash).as_str())
+}
+
+///
+/// Emits the event with `signature`, indexed `topics`, and the heap data at `data_offset`.
+///
+/// The `topic0` computed from `signature` is prepended to `topics`, unless the event is
+/// anonymous.
+///
+pub fn emit_event<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    signature: &str,
+    is_anonymous: bool,
+    mut topics: Vec<inkwell::values::IntValue<'ctx>>,
+    data_offset: inkwell::values::IntValue<'ctx>,
+    data_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    if !is_anonymous {
+        topics.insert(0, topic0(context, signature));
+    }
+    if topics.len() > TOPICS_MAX {
+        return Err(context.error(
+            "event",
+            format!(
+                "`{}` has {} topics, whereas at most {} are allowed",
+                signature,
+                topics.len(),
+                TOPICS_MAX
+            ),
+        ));
+    }
+
+    log(context, data_offset, data_length, topics)
+}
+
 ///
 /// Handles the even number of topics and empty data.
 ///
@@ -2,16 +2,136 @@
 //! Translates the external code operations.
 //!
 
+use inkwell::values::BasicValue;
+
+use crate::context::code_type::CodeType;
+use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 use crate::context::Context;
 use crate::Dependency;
 
 ///
 /// Translates the `extcodesize` instruction.
 ///
+/// In the deploy code, the contract is being constructed and its code size is zero, so the
+/// `extcodesize(this)` request to the system contract is skipped. In the runtime code, the code
+/// size is only known to the system contract, so it is always requested. See `has_code` for
+/// the runtime code specialization of the code existence checks.
+///
 pub fn size<'ctx, D>(
     context: &mut Context<'ctx, D>,
     address: inkwell::values::IntValue<'ctx>,
 ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let address = context.to_address(address);
+
+    let size = match context.try_code_type()? {
+        CodeType::Deploy => specialize_this(context, address, context.field_const(0), |context| {
+            request_size(context, address)
+        })?,
+        CodeType::Runtime => request_size(context, address)?,
+    };
+    Ok(Some(size.as_basic_value_enum()))
+}
+
+///
+/// Translates the `iszero(iszero(extcodesize(address)))` pattern, e.g. of the called contract
+/// existence checks, returning the `i1` flag.
+///
+/// The `extcodesize(this)` request is skipped in both the deploy code, where the code is empty,
+/// and the runtime code, where the code is being executed.
+///
+pub fn has_code<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    address: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
+where
+    D: Dependency,
+{
+    let address = context.to_address(address);
+
+    let this_result = match context.try_code_type()? {
+        CodeType::Deploy => context.field_const(0),
+        CodeType::Runtime => context.field_const(1),
+    };
+    let has_code = specialize_this(context, address, this_result, |context| {
+        let size = request_size(context, address)?;
+        let has_code = context.builder().build_int_compare(
+            inkwell::IntPredicate::NE,
+            size,
+            context.field_const(0),
+            "extcodesize_has_code",
+        );
+        Ok(context.builder().build_int_z_extend_or_bit_cast(
+            has_code,
+            context.field_type(),
+            "extcodesize_has_code_extended",
+        ))
+    })?;
+    Ok(context.builder().build_int_compare(
+        inkwell::IntPredicate::NE,
+        has_code,
+        context.field_const(0),
+        "extcodesize_has_code_result",
+    ))
+}
+
+///
+/// Returns `this_result` if `address` is the executed contract, and the result of `request`
+/// otherwise.
+///
+fn specialize_this<'ctx, D, F>(
+    context: &mut Context<'ctx, D>,
+    address: inkwell::values::IntValue<'ctx>,
+    this_result: inkwell::values::IntValue<'ctx>,
+    request: F,
+) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
+where
+    D: Dependency,
+    F: FnOnce(&mut Context<'ctx, D>) -> anyhow::Result<inkwell::values::IntValue<'ctx>>,
+{
+    let this_block = context.append_basic_block("extcodesize_this_block");
+    let request_block = context.append_basic_block("extcodesize_request_block");
+    let join_block = context.append_basic_block("extcodesize_join_block");
+
+    let result_pointer = context.build_alloca(context.field_type(), "extcodesize_result_pointer");
+    let this = context
+        .build_call(
+            context.get_intrinsic_function(IntrinsicFunction::Address),
+            &[],
+            "extcodesize_this_address",
+        )
+        .expect("Always exists");
+    let is_this = context.builder().build_int_compare(
+        inkwell::IntPredicate::EQ,
+        address,
+        this.into_int_value(),
+        "extcodesize_is_this",
+    );
+    context.build_conditional_branch(is_this, this_block, request_block);
+
+    context.set_basic_block(this_block);
+    context.build_store(result_pointer, this_result);
+    context.build_unconditional_branch(join_block);
+
+    context.set_basic_block(request_block);
+    let result = request(context)?;
+    context.build_store(result_pointer, result);
+    context.build_unconditional_branch(join_block);
+
+    context.set_basic_block(join_block);
+    let result = context.build_load(result_pointer, "extcodesize_result");
+    Ok(result.into_int_value())
+}
+
+///
+/// Requests the code size of `address` from the system contract.
+///
+fn request_size<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    address: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<inkwell::values::IntValue<'ctx>>
 where
     D: Dependency,
 {
@@ -21,7 +141,7 @@ where
         "getCodeSize(uint256)",
         vec![address],
     )
-    .map(Some)
+    .map(|size| size.into_int_value())
 }
 
 ///
@@ -34,6 +154,8 @@ pub fn hash<'ctx, D>(
 where
     D: Dependency,
 {
+    let address = context.to_address(address);
+
     crate::evm::contract::request::request(
         context,
         context.field_const(compiler_common::ADDRESS_ACCOUNT_CODE_STORAGE.into()),
@@ -42,3 +164,78 @@ where
     )
     .map(Some)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::code_type::CodeType;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn translate<F>(name: &str, code_type: CodeType, translate: F) -> String
+    where
+        F: FnOnce(&mut Context<DummyDependency>),
+    {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(&llvm, name, optimizer, None, vec![]);
+
+        context.add_function(
+            name,
+            context.function_type(0, vec![context.field_type().into()]),
+            None,
+        );
+        let function = context.functions[name].clone();
+        context.set_function(function);
+        context.set_code_type(code_type);
+        context.set_basic_block(context.function().entry_block);
+        translate(&mut context);
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+        assert!(context.verify().is_ok());
+
+        context.print_function(name).expect("Always exists")
+    }
+
+    fn address<'ctx>(context: &Context<'ctx, DummyDependency>) -> inkwell::values::IntValue<'ctx> {
+        context
+            .function()
+            .value
+            .get_first_param()
+            .expect("Always exists")
+            .into_int_value()
+    }
+
+    #[test]
+    fn size_runtime() {
+        let printed = translate("size_runtime", CodeType::Runtime, |context| {
+            let address = address(context);
+            super::size(context, address).expect("Always valid");
+        });
+        assert!(!printed.contains("extcodesize_is_this"));
+    }
+
+    #[test]
+    fn size_deploy() {
+        let printed = translate("size_deploy", CodeType::Deploy, |context| {
+            let address = address(context);
+            super::size(context, address).expect("Always valid");
+        });
+        assert!(printed.contains("extcodesize_is_this"));
+        assert!(printed.contains("store i256 0, i256* %extcodesize_result_pointer"));
+    }
+
+    #[test]
+    fn has_code_runtime() {
+        let printed = translate("has_code_runtime", CodeType::Runtime, |context| {
+            let address = address(context);
+            super::has_code(context, address).expect("Always valid");
+        });
+        assert!(printed.contains("extcodesize_is_this"));
+        assert!(printed.contains("store i256 1, i256* %extcodesize_result_pointer"));
+        assert!(printed.contains("%extcodesize_has_code = icmp ne i256"));
+    }
+}
@@ -5,13 +5,19 @@
 use inkwell::values::BasicValue;
 
 use crate::context::address_space::AddressSpace;
+use crate::context::fat_pointer_field::FatPointerField;
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::function::runtime::far_call::Kind as FarCallKind;
 use crate::context::Context;
 use crate::Dependency;
 
 ///
 /// Translates the `keccak256` instruction.
 ///
+/// If the hashed region has been filled with constants by the `mstore` instructions in the same
+/// basic block, e.g. `keccak256(abi.encodePacked(CONSTANT))`, and the folding is enabled,
+/// the hash is computed at compile time.
+///
 pub fn keccak256<'ctx, D>(
     context: &mut Context<'ctx, D>,
     input_offset: inkwell::values::IntValue<'ctx>,
@@ -20,9 +26,19 @@ pub fn keccak256<'ctx, D>(
 where
     D: Dependency,
 {
-    let success_block = context.append_basic_block("keccak256_success_block");
-    let failure_block = context.append_basic_block("keccak256_failure_block");
-    let join_block = context.append_basic_block("keccak256_failure_block");
+    if context
+        .optimizer_settings()
+        .is_constant_keccak_folding_enabled
+    {
+        if let Some(preimage) = context.heap_constant_contents(input_offset, input_length) {
+            let hash = crate::hashes::keccak256(preimage.as_slice());
+            return Ok(Some(
+                context
+                    .field_const_str_hex(hash.as_str())
+                    .as_basic_value_enum(),
+            ));
+        }
+    }
 
     let abi_data = crate::evm::contract::abi_data(
         context,
@@ -34,100 +50,271 @@ where
     )?;
     let address = context.field_const(compiler_common::ADDRESS_KECCAK256.into());
 
-    let result_pointer = context
-        .build_invoke_far_call(
-            context.runtime.static_call,
-            vec![
-                abi_data.as_basic_value_enum(),
-                address.as_basic_value_enum(),
-            ],
-            "keccak256_call_external",
-        )
-        .expect("Always returns a value");
-    let result_abi_data_pointer = context
-        .builder()
-        .build_struct_gep(
-            result_pointer.into_pointer_value(),
-            0,
-            "keccak256_call_external_result_abi_data_pointer",
-        )
-        .expect("Always valid");
-    let result_abi_data = context.build_load(
-        result_abi_data_pointer,
-        "keccak256_call_external_result_abi_data",
-    );
-    let result_abi_data_casted = context.builder().build_pointer_cast(
-        result_abi_data.into_pointer_value(),
-        context.field_type().ptr_type(AddressSpace::Generic.into()),
-        "keccak256_call_external_result_abi_data_casted",
+    let result = context.build_invoke_far_call(
+        context.runtime.modify(FarCallKind::Static, false, false),
+        vec![
+            abi_data.as_basic_value_enum(),
+            address.as_basic_value_enum(),
+        ],
+        "keccak256_call_external",
     );
+    result.bubble_revert(context);
 
-    let result_status_code_pointer = unsafe {
-        context.builder().build_gep(
-            result_pointer.into_pointer_value(),
-            &[
-                context.field_const(0),
-                context
-                    .integer_type(compiler_common::BITLENGTH_X32)
-                    .const_int(1, false),
-            ],
-            "keccak256_external_result_status_code_pointer",
-        )
-    };
-    let result_status_code_boolean = context.build_load(
-        result_status_code_pointer,
-        "keccak256_external_result_status_code_boolean",
-    );
-    let result_pointer = context.build_alloca(context.field_type(), "keccak256_result_pointer");
-    context.build_store(result_pointer, context.field_const(0));
-    context.build_conditional_branch(
-        result_status_code_boolean.into_int_value(),
-        success_block,
-        failure_block,
-    );
-
-    context.set_basic_block(success_block);
-    let child_data = context.build_load(result_abi_data_casted, "keccak256_child_data");
-    context.build_store(result_pointer, child_data);
-    context.build_unconditional_branch(join_block);
+    let child_data = context.build_load(result.data_pointer(context), "keccak256_child_data");
+    Ok(Some(child_data))
+}
 
-    context.set_basic_block(failure_block);
-    let result_abi_data_value = context.builder().build_ptr_to_int(
-        result_abi_data.into_pointer_value(),
-        context.field_type(),
-        "keccak256_child_data_pointer_value",
+///
+/// Translates the `keccak256` instruction over `input_length` bytes of the generic address space
+/// fat `pointer` starting at `input_offset`, e.g. the calldata or return data.
+///
+/// The pointer is narrowed to the hashed slice and passed to the hashing system contract
+/// by reference, so the data is not copied to the heap. Reverts if the slice is out of bounds,
+/// which is checked in the field type before the pointer is advanced.
+///
+pub fn keccak256_pointer<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    pointer: inkwell::values::PointerValue<'ctx>,
+    input_offset: inkwell::values::IntValue<'ctx>,
+    input_length: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let pointer = context.builder().build_pointer_cast(
+        pointer,
+        context
+            .integer_type(compiler_common::BITLENGTH_BYTE)
+            .ptr_type(AddressSpace::Generic.into()),
+        "keccak256_pointer_casted",
     );
-    let child_data_length_shifted = context.builder().build_right_shift(
-        result_abi_data_value,
-        context.field_const((compiler_common::BITLENGTH_X32 * 3) as u64),
-        false,
-        "keccak256_child_data_length_shifted",
+
+    let pointer_offset =
+        context.fat_ptr_field(pointer, FatPointerField::Offset, "keccak256_pointer_offset");
+    let pointer_length =
+        context.fat_ptr_field(pointer, FatPointerField::Length, "keccak256_pointer_length");
+    let pointer_left =
+        context
+            .builder()
+            .build_int_sub(pointer_length, pointer_offset, "keccak256_pointer_left");
+    let is_offset_in_bounds = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        input_offset,
+        pointer_left,
+        "keccak256_pointer_is_offset_in_bounds",
     );
-    let child_data_length = context.builder().build_and(
-        child_data_length_shifted,
-        context.field_const(u64::MAX as u64),
-        "keccak256_child_data_length",
+    let input_left =
+        context
+            .builder()
+            .build_int_sub(pointer_left, input_offset, "keccak256_pointer_input_left");
+    let is_length_in_bounds = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULE,
+        input_length,
+        input_left,
+        "keccak256_pointer_is_length_in_bounds",
     );
-    let source = result_abi_data_casted;
-    let destination = context.access_memory(
-        context.field_const(0),
-        AddressSpace::Heap,
-        "keccak256_destination",
+    let is_in_bounds = context.builder().build_and(
+        is_offset_in_bounds,
+        is_length_in_bounds,
+        "keccak256_pointer_is_in_bounds",
     );
-    context.build_memcpy(
-        IntrinsicFunction::MemoryCopyFromGeneric,
-        destination,
-        source,
-        child_data_length,
-        "keccak256_memcpy_from_child",
+    let in_bounds_block = context.append_basic_block("keccak256_pointer_in_bounds_block");
+    let out_of_bounds_block = context.append_basic_block("keccak256_pointer_out_of_bounds_block");
+    context.build_conditional_branch_weighted(
+        is_in_bounds,
+        in_bounds_block,
+        out_of_bounds_block,
+        Context::<D>::BRANCH_WEIGHT_LIKELY,
+        Context::<D>::BRANCH_WEIGHT_UNLIKELY,
     );
+
+    context.set_basic_block(out_of_bounds_block);
     context.build_exit(
         IntrinsicFunction::Revert,
         context.field_const(0),
-        child_data_length,
+        context.field_const(0),
+    );
+
+    context.set_basic_block(in_bounds_block);
+    let pointer = unsafe {
+        context
+            .builder()
+            .build_gep(pointer, &[input_offset], "keccak256_pointer_input")
+    };
+    let shrink =
+        context
+            .builder()
+            .build_int_sub(input_left, input_length, "keccak256_pointer_shrink");
+    let abi_data = context
+        .build_call(
+            context.get_intrinsic_function(IntrinsicFunction::PointerShrink),
+            &[pointer.as_basic_value_enum(), shrink.as_basic_value_enum()],
+            "keccak256_pointer_shrank",
+        )
+        .expect("Always returns a pointer");
+    let address = context.field_const(compiler_common::ADDRESS_KECCAK256.into());
+
+    let result = context.build_invoke_far_call(
+        context.runtime.modify(FarCallKind::Static, true, false),
+        vec![abi_data, address.as_basic_value_enum()],
+        "keccak256_pointer_call_external",
     );
+    result.bubble_revert(context);
+
+    let child_data =
+        context.build_load(result.data_pointer(context), "keccak256_pointer_child_data");
+    Ok(Some(child_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::address_space::AddressSpace;
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    fn keccak256_is_folded<F>(name: &str, between: F) -> bool
+    where
+        F: FnOnce(&mut Context<DummyDependency>),
+    {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let mut settings = OptimizerSettings::none();
+        settings.is_constant_keccak_folding_enabled = true;
+        let optimizer = Optimizer::new(settings).expect("Always valid");
+        let mut context = Context::<DummyDependency>::new(&llvm, name, optimizer, None, vec![]);
+
+        context.add_function(name, context.function_type(0, vec![]), None);
+        let function = context.functions[name].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        for offset in [0, compiler_common::SIZE_FIELD as u64] {
+            crate::evm::memory::store(
+                &mut context,
+                context.field_const(offset),
+                context.field_const(42),
+            )
+            .expect("Always valid");
+        }
+        between(&mut context);
+        let hash = super::keccak256(
+            &mut context,
+            context.field_const(0),
+            context.field_const((compiler_common::SIZE_FIELD * 2) as u64),
+        )
+        .expect("Always valid")
+        .expect("Always exists");
+        hash.into_int_value().is_constant_int()
+    }
+
+    #[test]
+    fn keccak256_folding() {
+        assert!(keccak256_is_folded("keccak256_folding", |_context| {}));
+    }
+
+    #[test]
+    fn keccak256_folding_after_store_byte() {
+        assert!(!keccak256_is_folded(
+            "keccak256_folding_after_store_byte",
+            |context| {
+                crate::evm::memory::store_byte(
+                    context,
+                    context.field_const(1),
+                    context.field_const(0xff),
+                )
+                .expect("Always valid");
+            }
+        ));
+    }
+
+    #[test]
+    fn keccak256_folding_after_call() {
+        assert!(!keccak256_is_folded(
+            "keccak256_folding_after_call",
+            |context| {
+                context.add_function("callee", context.function_type(0, vec![]), None);
+                let callee = context.functions["callee"].value;
+                context.build_call(callee, &[], "callee_call");
+            }
+        ));
+    }
+
+    #[test]
+    fn keccak256_pointer_bounds_before_gep() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "keccak256_pointer", optimizer, None, vec![]);
+
+        let pointer_type = context
+            .integer_type(compiler_common::BITLENGTH_BYTE)
+            .ptr_type(AddressSpace::Generic.into());
+        context.add_function(
+            "keccak256_pointer",
+            context.function_type(
+                0,
+                vec![
+                    pointer_type.into(),
+                    context.field_type().into(),
+                    context.field_type().into(),
+                ],
+            ),
+            None,
+        );
+        let function = context.functions["keccak256_pointer"].clone();
+        let parameters = function.value.get_params();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        let input_offset = parameters[1].into_int_value();
+        super::keccak256_pointer(
+            &mut context,
+            parameters[0].into_pointer_value(),
+            input_offset,
+            parameters[2].into_int_value(),
+        )
+        .expect("Always valid");
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
 
-    context.set_basic_block(join_block);
-    let result = context.build_load(result_pointer, "keccak256_result");
-    Ok(Some(result))
+        let find = |name: &str| {
+            context
+                .function()
+                .value
+                .get_basic_blocks()
+                .into_iter()
+                .find_map(|block| {
+                    let mut instruction = block.get_first_instruction();
+                    while let Some(current) = instruction {
+                        if current.get_name().map(|value| value.to_string_lossy())
+                            == Some(name.into())
+                        {
+                            return Some(current);
+                        }
+                        instruction = current.get_next_instruction();
+                    }
+                    None
+                })
+                .expect("Always exists")
+        };
+        let is_offset_in_bounds = find("keccak256_pointer_is_offset_in_bounds");
+        assert_eq!(
+            is_offset_in_bounds
+                .get_operand(0)
+                .and_then(|operand| operand.left()),
+            Some(input_offset.into())
+        );
+        let input = find("keccak256_pointer_input");
+        assert_eq!(
+            input
+                .get_parent()
+                .expect("Always exists")
+                .get_name()
+                .to_string_lossy(),
+            "keccak256_pointer_in_bounds_block"
+        );
+        assert!(context.verify().is_ok());
+    }
 }
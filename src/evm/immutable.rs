@@ -2,17 +2,21 @@
 //! Translates the contract immutable operations.
 //!
 
+use inkwell::values::BasicValue;
+
 use crate::context::address_space::AddressSpace;
 use crate::context::code_type::CodeType;
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
+use crate::context::function::runtime::far_call::Kind as FarCallKind;
+use crate::context::immutables_layout::ImmutablesLayout;
 use crate::context::Context;
 use crate::Dependency;
 
 ///
 /// Translates the contract immutable load.
 ///
-/// In the deploy code the values are read from the auxiliary heap.
-/// In the runtime code they are requested from the system contract.
+/// In the deploy code the values are read from the auxiliary heap, according to the immutables
+/// layout. In the runtime code they are requested from the system contract.
 ///
 pub fn load<'ctx, D>(
     context: &mut Context<'ctx, D>,
@@ -21,21 +25,30 @@ pub fn load<'ctx, D>(
 where
     D: Dependency,
 {
-    match context.code_type() {
+    match context.try_code_type()? {
         CodeType::Deploy => {
-            let index_double = context.builder().build_int_mul(
-                index,
-                context.field_const(2),
-                "immutable_load_index_double",
-            );
-            let offset_absolute = context.builder().build_int_add(
-                index_double,
-                context.field_const(
-                    crate::r#const::HEAP_AUX_OFFSET_CONSTRUCTOR_RETURN_DATA
-                        + (3 * compiler_common::SIZE_FIELD) as u64,
+            let offset_absolute = match context.immutables_layout() {
+                ImmutablesLayout::Pairs => {
+                    let index_double = context.builder().build_int_mul(
+                        index,
+                        context.field_const(2),
+                        "immutable_load_index_double",
+                    );
+                    context.builder().build_int_add(
+                        index_double,
+                        context.field_const(
+                            context.aux_heap_layout().immutables_offset()
+                                + compiler_common::SIZE_FIELD as u64,
+                        ),
+                        "immutable_offset_absolute",
+                    )
+                }
+                ImmutablesLayout::Compact => context.builder().build_int_add(
+                    index,
+                    context.field_const(context.aux_heap_layout().immutables_offset()),
+                    "immutable_offset_absolute",
                 ),
-                "immutable_offset_absolute",
-            );
+            };
             let immutable_pointer = context.access_memory(
                 offset_absolute,
                 AddressSpace::HeapAuxiliary,
@@ -68,7 +81,9 @@ where
 /// Translates the contract immutable store.
 ///
 /// In the deploy code the values are written to the auxiliary heap at the predefined offset,
-/// being prepared for returning to the system contract for saving.
+/// being prepared for returning to the system contract for saving. The written immutables are
+/// marked in a bit mask by their position to be validated before returning, so the repeated
+/// stores of the same immutable are not counted twice.
 ///
 /// Ignored in the runtime code.
 ///
@@ -80,33 +95,39 @@ pub fn store<'ctx, D>(
 where
     D: Dependency,
 {
-    match context.code_type() {
+    match context.try_code_type()? {
         CodeType::Deploy => {
-            let index_double = context.builder().build_int_mul(
-                index,
-                context.field_const(2),
-                "immutable_load_index_double",
-            );
-            let index_offset_absolute = context.builder().build_int_add(
-                index_double,
-                context.field_const(
-                    crate::r#const::HEAP_AUX_OFFSET_CONSTRUCTOR_RETURN_DATA
-                        + (2 * compiler_common::SIZE_FIELD) as u64,
-                ),
-                "index_offset_absolute",
-            );
-            let index_offset_pointer = context.access_memory(
-                index_offset_absolute,
-                AddressSpace::HeapAuxiliary,
-                "immutable_index_pointer",
-            );
-            context.build_store(index_offset_pointer, index);
+            let value_offset_absolute = match context.immutables_layout() {
+                ImmutablesLayout::Pairs => {
+                    let index_double = context.builder().build_int_mul(
+                        index,
+                        context.field_const(2),
+                        "immutable_load_index_double",
+                    );
+                    let index_offset_absolute = context.builder().build_int_add(
+                        index_double,
+                        context.field_const(context.aux_heap_layout().immutables_offset()),
+                        "index_offset_absolute",
+                    );
+                    let index_offset_pointer = context.access_memory(
+                        index_offset_absolute,
+                        AddressSpace::HeapAuxiliary,
+                        "immutable_index_pointer",
+                    );
+                    context.build_store(index_offset_pointer, index);
 
-            let value_offset_absolute = context.builder().build_int_add(
-                index_offset_absolute,
-                context.field_const(compiler_common::SIZE_FIELD as u64),
-                "value_offset_absolute",
-            );
+                    context.builder().build_int_add(
+                        index_offset_absolute,
+                        context.field_const(compiler_common::SIZE_FIELD as u64),
+                        "value_offset_absolute",
+                    )
+                }
+                ImmutablesLayout::Compact => context.builder().build_int_add(
+                    index,
+                    context.field_const(context.aux_heap_layout().immutables_offset()),
+                    "value_offset_absolute",
+                ),
+            };
             let value_offset_pointer = context.access_memory(
                 value_offset_absolute,
                 AddressSpace::HeapAuxiliary,
@@ -114,8 +135,251 @@ where
             );
             context.build_store(value_offset_pointer, value);
 
+            let immutables_written_pointer = context.declare_global(
+                crate::r#const::GLOBAL_IMMUTABLES_WRITTEN,
+                context.field_type(),
+            );
+            let immutables_written = context
+                .build_load(immutables_written_pointer, "immutables_written")
+                .into_int_value();
+            let immutable_position = context.builder().build_int_unsigned_div(
+                index,
+                context.field_const(compiler_common::SIZE_FIELD as u64),
+                "immutable_position",
+            );
+            let is_position_in_mask = context.builder().build_int_compare(
+                inkwell::IntPredicate::ULT,
+                immutable_position,
+                context.field_const(compiler_common::BITLENGTH_FIELD as u64),
+                "immutable_is_position_in_mask",
+            );
+            let immutable_bit = context.builder().build_left_shift(
+                context.field_const(1),
+                immutable_position,
+                "immutable_bit",
+            );
+            let immutable_bit = context
+                .builder()
+                .build_select(
+                    is_position_in_mask,
+                    immutable_bit,
+                    context.field_const(0),
+                    "immutable_bit_checked",
+                )
+                .into_int_value();
+            let immutables_written_updated = context.builder().build_or(
+                immutables_written,
+                immutable_bit,
+                "immutables_written_updated",
+            );
+            context.build_store(immutables_written_pointer, immutables_written_updated);
+
             Ok(None)
         }
         CodeType::Runtime => Ok(None),
     }
 }
+
+///
+/// Translates the batch load of `count` immutables of the contract at `address`, starting from
+/// `index`, into the heap at `destination_offset`.
+///
+/// The `getImmutable` request is prepared in the auxiliary heap once, so the loop only updates
+/// the index argument and repeats the far call. The immutable indexes are byte offsets, so
+/// the consecutive immutables are one field size apart.
+///
+pub fn load_batch<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    address: inkwell::values::IntValue<'ctx>,
+    index: inkwell::values::IntValue<'ctx>,
+    count: inkwell::values::IntValue<'ctx>,
+    destination_offset: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    let external_call_offset = context.aux_heap_layout().external_call_offset();
+    let input_offset = context.field_const(external_call_offset);
+    let input_length =
+        context.field_const((compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD * 2) as u64);
+    let abi_data = crate::evm::contract::abi_data(
+        context,
+        input_offset,
+        input_length,
+        context.field_const(0),
+        AddressSpace::HeapAuxiliary,
+        true,
+    )?;
+
+    let signature_hash = crate::hashes::keccak256("getImmutable(address,uint256)".as_bytes());
+    let signature_pointer = context.access_memory(
+        input_offset,
+        AddressSpace::HeapAuxiliary,
+        "immutable_batch_signature_pointer",
+    );
+    context.build_store(
+        signature_pointer,
+        context.field_const_str_hex(signature_hash.as_str()),
+    );
+    let address_pointer = context.access_memory(
+        context.field_const(external_call_offset + compiler_common::SIZE_X32 as u64),
+        AddressSpace::HeapAuxiliary,
+        "immutable_batch_address_pointer",
+    );
+    context.build_store(address_pointer, address);
+    let index_argument_pointer = context.access_memory(
+        context.field_const(
+            external_call_offset + (compiler_common::SIZE_X32 + compiler_common::SIZE_FIELD) as u64,
+        ),
+        AddressSpace::HeapAuxiliary,
+        "immutable_batch_index_argument_pointer",
+    );
+
+    let condition_block = context.append_basic_block("immutable_batch_loop_condition");
+    let body_block = context.append_basic_block("immutable_batch_loop_body");
+    let error_block = context.append_basic_block("immutable_batch_loop_error");
+    let increment_block = context.append_basic_block("immutable_batch_loop_increment");
+    let join_block = context.append_basic_block("immutable_batch_loop_join");
+
+    let counter_pointer =
+        context.build_alloca(context.field_type(), "immutable_batch_loop_counter_pointer");
+    context.build_store(counter_pointer, context.field_const(0));
+    context.build_unconditional_branch(condition_block);
+
+    context.set_basic_block(condition_block);
+    let counter = context
+        .build_load(counter_pointer, "immutable_batch_loop_counter")
+        .into_int_value();
+    let condition = context.builder().build_int_compare(
+        inkwell::IntPredicate::ULT,
+        counter,
+        count,
+        "immutable_batch_loop_condition",
+    );
+    context.build_conditional_branch(condition, body_block, join_block);
+
+    context.set_basic_block(increment_block);
+    let counter = context
+        .build_load(counter_pointer, "immutable_batch_loop_counter_increment")
+        .into_int_value();
+    let counter_incremented = context.builder().build_int_add(
+        counter,
+        context.field_const(1),
+        "immutable_batch_loop_counter_incremented",
+    );
+    context.build_store(counter_pointer, counter_incremented);
+    context.build_unconditional_branch(condition_block);
+
+    context.set_basic_block(body_block);
+    let counter = context
+        .build_load(counter_pointer, "immutable_batch_loop_body_counter")
+        .into_int_value();
+    let shift = context.builder().build_int_mul(
+        counter,
+        context.field_const(compiler_common::SIZE_FIELD as u64),
+        "immutable_batch_loop_shift",
+    );
+    let index_current =
+        context
+            .builder()
+            .build_int_add(index, shift, "immutable_batch_loop_index_current");
+    context.build_store(index_argument_pointer, index_current);
+    let result = context.build_invoke_far_call(
+        context.runtime.modify(FarCallKind::Static, false, false),
+        vec![
+            abi_data.as_basic_value_enum(),
+            context
+                .field_const(compiler_common::ADDRESS_IMMUTABLE_SIMULATOR.into())
+                .as_basic_value_enum(),
+        ],
+        "immutable_batch_loop_call",
+    );
+    let success_block = context.append_basic_block("immutable_batch_loop_success");
+    context.build_conditional_branch(result.status, success_block, error_block);
+
+    context.set_basic_block(success_block);
+    let value = context.build_load(result.data_pointer(context), "immutable_batch_loop_value");
+    let destination_offset_current = context.builder().build_int_add(
+        destination_offset,
+        shift,
+        "immutable_batch_loop_destination_offset",
+    );
+    let destination_pointer = context.access_memory(
+        destination_offset_current,
+        AddressSpace::Heap,
+        "immutable_batch_loop_destination_pointer",
+    );
+    context.build_store(destination_pointer, value);
+    context.build_unconditional_branch(increment_block);
+
+    context.set_basic_block(error_block);
+    context.build_exit(
+        IntrinsicFunction::Revert,
+        context.field_const(0),
+        context.field_const(0),
+    );
+
+    context.set_basic_block(join_block);
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::optimizer::settings::Settings as OptimizerSettings;
+    use crate::context::optimizer::Optimizer;
+    use crate::context::Context;
+    use crate::DummyDependency;
+
+    #[test]
+    fn load_batch_offsets() {
+        crate::initialize_target();
+        let llvm = inkwell::context::Context::create();
+        let optimizer = Optimizer::new(OptimizerSettings::none()).expect("Always valid");
+        let mut context =
+            Context::<DummyDependency>::new(&llvm, "load_batch", optimizer, None, vec![]);
+
+        context.add_function("load_batch", context.function_type(0, vec![]), None);
+        let function = context.functions["load_batch"].clone();
+        context.set_function(function);
+        context.set_basic_block(context.function().entry_block);
+        super::load_batch(
+            &mut context,
+            context.field_const(0),
+            context.field_const(64),
+            context.field_const(2),
+            context.field_const(128),
+        )
+        .expect("Always valid");
+        context.build_unconditional_branch(context.function().return_block);
+        context.set_basic_block(context.function().return_block);
+        context.build_return(None);
+
+        let mut index_current = None;
+        for block in context.function().value.get_basic_blocks().into_iter() {
+            let mut instruction = block.get_first_instruction();
+            while let Some(current) = instruction {
+                if current
+                    .get_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    == Some("immutable_batch_loop_index_current".to_owned())
+                {
+                    index_current = Some(current);
+                }
+                instruction = current.get_next_instruction();
+            }
+        }
+        let index_current = index_current.expect("Always exists");
+        let shift = index_current
+            .get_operand(1)
+            .and_then(|operand| operand.left())
+            .and_then(|operand| operand.into_int_value().as_instruction_value())
+            .expect("The index is shifted by the counter");
+        assert_eq!(shift.get_opcode(), inkwell::values::InstructionOpcode::Mul);
+        let stride = shift
+            .get_operand(1)
+            .and_then(|operand| operand.left())
+            .and_then(|operand| context.const_u64(operand.into_int_value()));
+        assert_eq!(stride, Some(compiler_common::SIZE_FIELD as u64));
+        assert!(context.verify().is_ok());
+    }
+}
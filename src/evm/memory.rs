@@ -18,6 +18,7 @@ pub fn load<'ctx, D>(
 where
     D: Dependency,
 {
+    context.build_heap_offset_check(offset);
     let pointer = context.access_memory(offset, AddressSpace::Heap, "memory_load_pointer");
     let result = context.build_load(pointer, "memory_load_result");
     Ok(Some(result))
@@ -36,8 +37,10 @@ pub fn store<'ctx, D>(
 where
     D: Dependency,
 {
+    context.build_heap_offset_check(offset);
     let pointer = context.access_memory(offset, AddressSpace::Heap, "memory_store_pointer");
     context.build_store(pointer, value);
+    context.log_heap_store(offset, value);
 
     Ok(None)
 }
@@ -55,6 +58,7 @@ pub fn store_byte<'ctx, D>(
 where
     D: Dependency,
 {
+    context.build_heap_offset_check(offset);
     let pointer = context.access_memory(
         offset,
         AddressSpace::Heap,
@@ -5,6 +5,7 @@
 pub mod arithmetic;
 pub mod bitwise;
 pub mod calldata;
+pub mod code;
 pub mod comparison;
 pub mod context;
 pub mod contract;
@@ -3,11 +3,23 @@
 //!
 
 use crate::context::address_space::AddressSpace;
+use crate::context::fat_pointer_field::FatPointerField;
 use crate::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 use crate::context::Context;
 use crate::Dependency;
 use inkwell::values::BasicValue;
 
+///
+/// The return data source.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The return data of the most recent call.
+    ReturnData,
+    /// The active pointer, which may have been manipulated by the verbatim instructions.
+    ActivePointer,
+}
+
 ///
 /// Translates the return data size.
 ///
@@ -17,9 +29,47 @@ pub fn size<'ctx, D>(
 where
     D: Dependency,
 {
-    match context.get_global(crate::r#const::GLOBAL_RETURN_DATA_SIZE) {
-        Ok(global) => Ok(Some(global)),
-        Err(_error) => Ok(Some(context.field_const(0).as_basic_value_enum())),
+    size_from(context, Source::ReturnData)
+}
+
+///
+/// Translates the return data size, taken from the specified `source`.
+///
+/// The active pointer data size is its length excluding the already passed offset.
+///
+pub fn size_from<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    source: Source,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    match source {
+        Source::ReturnData => match context.get_global(crate::r#const::GLOBAL_RETURN_DATA_SIZE) {
+            Ok(global) => Ok(Some(global)),
+            Err(_error) => Ok(Some(context.field_const(0).as_basic_value_enum())),
+        },
+        Source::ActivePointer => {
+            let active_pointer = context
+                .get_global(crate::r#const::GLOBAL_ACTIVE_POINTER)?
+                .into_pointer_value();
+            let active_pointer_offset = context.fat_ptr_field(
+                active_pointer,
+                FatPointerField::Offset,
+                "return_data_active_pointer_offset",
+            );
+            let active_pointer_length = context.fat_ptr_field(
+                active_pointer,
+                FatPointerField::Length,
+                "return_data_active_pointer_length",
+            );
+            let size = context.builder().build_int_sub(
+                active_pointer_length,
+                active_pointer_offset,
+                "return_data_active_pointer_size",
+            );
+            Ok(Some(size.as_basic_value_enum()))
+        }
     }
 }
 
@@ -32,6 +82,28 @@ pub fn copy<'ctx, D>(
     source_offset: inkwell::values::IntValue<'ctx>,
     size: inkwell::values::IntValue<'ctx>,
 ) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
+where
+    D: Dependency,
+{
+    copy_from(
+        context,
+        Source::ReturnData,
+        destination_offset,
+        source_offset,
+        size,
+    )
+}
+
+///
+/// Translates the return data copy, taken from the specified `source`.
+///
+pub fn copy_from<'ctx, D>(
+    context: &mut Context<'ctx, D>,
+    source: Source,
+    destination_offset: inkwell::values::IntValue<'ctx>,
+    source_offset: inkwell::values::IntValue<'ctx>,
+    size: inkwell::values::IntValue<'ctx>,
+) -> anyhow::Result<Option<inkwell::values::BasicValueEnum<'ctx>>>
 where
     D: Dependency,
 {
@@ -41,9 +113,11 @@ where
         "return_data_copy_destination_pointer",
     );
 
-    let return_data_pointer = context
-        .get_global(crate::r#const::GLOBAL_RETURN_DATA_POINTER)?
-        .into_pointer_value();
+    let source_global = match source {
+        Source::ReturnData => crate::r#const::GLOBAL_RETURN_DATA_POINTER,
+        Source::ActivePointer => crate::r#const::GLOBAL_ACTIVE_POINTER,
+    };
+    let return_data_pointer = context.get_global(source_global)?.into_pointer_value();
     let return_data_pointer = unsafe {
         context.builder().build_gep(
             return_data_pointer,
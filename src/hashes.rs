@@ -1,6 +1,9 @@
 //!
 //! The hashing tools.
 //!
+//! The hashes are returned as lowercase hexadecimal strings without the `0x` prefix, so they can
+//! be passed to `Context::field_const_str_hex` directly.
+//!
 
 ///
 /// Computes the `keccak256` hash for `preimage`.
@@ -8,12 +11,26 @@
 pub fn keccak256(preimage: &[u8]) -> String {
     use sha3::Digest;
 
-    let hash_bytes = sha3::Keccak256::digest(preimage);
-    hash_bytes
-        .into_iter()
-        .map(|byte| format!("{:02x}", byte))
-        .collect::<Vec<String>>()
-        .join("")
+    hex::encode(sha3::Keccak256::digest(preimage))
+}
+
+///
+/// Computes the `sha256` hash for `preimage`.
+///
+pub fn sha256(preimage: &[u8]) -> String {
+    use sha2::Digest;
+
+    hex::encode(sha2::Sha256::digest(preimage))
+}
+
+///
+/// Computes the Solidity function selector for `signature`, e.g. `transfer(address,uint256)`,
+/// which is the first 4 bytes of its `keccak256` hash.
+///
+pub fn selector(signature: &str) -> String {
+    let mut hash = keccak256(signature.as_bytes());
+    hash.truncate(compiler_common::SIZE_X32 * 2);
+    hash
 }
 
 #[cfg(test)]
@@ -25,4 +42,17 @@ mod tests {
             "0238fb1ab06c28c32885f9a4842207ac480c2467df26b6c58e201679628c5a5b"
         );
     }
+
+    #[test]
+    fn sha256() {
+        assert_eq!(
+            super::sha256("abc".as_bytes()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn selector() {
+        assert_eq!(super::selector("transfer(address,uint256)"), "a9059cbb");
+    }
 }
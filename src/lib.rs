@@ -4,16 +4,34 @@
 
 pub(crate) mod r#const;
 pub(crate) mod context;
+pub(crate) mod dependency_artifact;
+#[cfg(feature = "differential-testing")]
+pub mod differential;
 pub(crate) mod dump_flag;
+pub(crate) mod error;
 pub(crate) mod evm;
-pub(crate) mod hashes;
+pub mod hashes;
 
 pub use self::context::address_space::AddressSpace;
 pub use self::context::argument::Argument;
+pub use self::context::assembly_routine::AssemblyRoutine;
 pub use self::context::attribute::Attribute;
+pub use self::context::aux_heap_layout::AuxHeapLayout;
 pub use self::context::build::Build;
+pub use self::context::build_statistics::BuildStatistics;
+pub use self::context::build_timings::BuildTimings;
+pub use self::context::calldata_slice::CalldataSlice;
+pub use self::context::code_hash::CodeHash;
 pub use self::context::code_type::CodeType;
+pub use self::context::constructor_return_builder::ConstructorReturnBuilder;
+pub use self::context::contract_parts::ContractParts;
+pub use self::context::coverage::CoverageCounter;
+pub use self::context::coverage::CoverageMode;
+pub use self::context::dependency_policy::DependencyPolicy;
 pub use self::context::evm_data::EVMData as ContextEVMData;
+pub use self::context::far_call_result::FarCallResult;
+pub use self::context::fat_pointer_field::FatPointerField;
+pub use self::context::frozen_module::FrozenModule;
 pub use self::context::function::block::evm_data::EVMData as FunctionBlockEVMData;
 pub use self::context::function::block::key::Key as FunctionBlockKey;
 pub use self::context::function::block::Block as FunctionBlock;
@@ -22,15 +40,29 @@ pub use self::context::function::entry::Entry as EntryFunction;
 pub use self::context::function::evm_data::EVMData as FunctionEVMData;
 pub use self::context::function::intrinsic::Intrinsic as IntrinsicFunction;
 pub use self::context::function::r#return::Return as FunctionReturn;
+pub use self::context::function::runtime::far_call::FarCall as RuntimeFarCall;
+pub use self::context::function::runtime::far_call::Kind as RuntimeFarCallKind;
 pub use self::context::function::runtime::Runtime;
 pub use self::context::function::runtime_code::RuntimeCode as RuntimeCodeFunction;
 pub use self::context::function::Function;
+pub use self::context::hook_stage::HookStage;
+pub use self::context::immutables_layout::ImmutablesLayout;
+pub use self::context::optimizer::settings::compatibility_mode::CompatibilityMode as OptimizerSettingsCompatibilityMode;
+pub use self::context::optimizer::settings::gas_forwarding::GasForwarding as OptimizerSettingsGasForwarding;
+pub use self::context::optimizer::settings::register_pressure::RegisterPressure as OptimizerSettingsRegisterPressure;
 pub use self::context::optimizer::settings::size_level::SizeLevel as OptimizerSettingsSizeLevel;
 pub use self::context::optimizer::settings::Settings as OptimizerSettings;
 pub use self::context::optimizer::Optimizer;
+pub use self::context::output_format::OutputFormat;
+pub use self::context::path_sanitizer::PathSanitizer;
 pub use self::context::r#loop::Loop;
+pub use self::context::snapshot::Snapshot as ContextSnapshot;
 pub use self::context::Context;
+pub use self::context::GlobalInitializer;
+pub use self::context::Hook;
+pub use self::dependency_artifact::DependencyArtifact;
 pub use self::dump_flag::DumpFlag;
+pub use self::error::Error;
 pub use self::evm::arithmetic;
 pub use self::evm::bitwise;
 pub use self::evm::calldata;
@@ -50,6 +82,8 @@ pub use self::evm::return_data;
 pub use self::evm::storage;
 pub use self::evm::verbatim;
 pub use self::hashes::keccak256;
+pub use self::hashes::selector;
+pub use self::hashes::sha256;
 pub use self::r#const::*;
 
 use std::sync::Arc;
@@ -65,6 +99,10 @@ pub fn initialize_target() {
 ///
 /// Implemented by items which are translated into LLVM IR.
 ///
+/// The translation is performed in two phases. First, all the entities are declared, so they can
+/// reference each other regardless of the order. Then, each entity is translated. The phases
+/// are run in this order by `Context::translate`.
+///
 #[allow(clippy::upper_case_acronyms)]
 pub trait WriteLLVM<D>
 where
@@ -74,6 +112,8 @@ where
     /// Declares the entity in the LLVM IR.
     /// Is usually performed in order to use the item before defining it.
     ///
+    /// Does nothing by default, which is suitable for the entities not referenced by others.
+    ///
     fn declare(&mut self, _context: &mut Context<D>) -> anyhow::Result<()> {
         Ok(())
     }
@@ -81,6 +121,8 @@ where
     ///
     /// Translates the entity into LLVM IR.
     ///
+    /// Must be called after all the entities have been declared.
+    ///
     fn into_llvm(self, context: &mut Context<D>) -> anyhow::Result<()>;
 }
 
@@ -111,7 +153,7 @@ pub trait Dependency {
         path: &str,
         optimizer_settings: OptimizerSettings,
         dump_flags: Vec<DumpFlag>,
-    ) -> anyhow::Result<String>;
+    ) -> anyhow::Result<DependencyArtifact>;
 
     ///
     /// Resolves a full contract path.
@@ -123,3 +165,33 @@ pub trait Dependency {
     ///
     fn resolve_library(&self, path: &str) -> anyhow::Result<String>;
 }
+
+///
+/// The dummy dependency manager, which cannot resolve anything.
+///
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct DummyDependency {}
+
+#[cfg(test)]
+impl Dependency for DummyDependency {
+    fn compile(
+        _object: Arc<RwLock<Self>>,
+        path: &str,
+        _optimizer_settings: OptimizerSettings,
+        _dump_flags: Vec<DumpFlag>,
+    ) -> anyhow::Result<DependencyArtifact> {
+        anyhow::bail!("The dummy dependency manager cannot compile `{}`", path)
+    }
+
+    fn resolve_path(&self, identifier: &str) -> anyhow::Result<String> {
+        anyhow::bail!(
+            "The dummy dependency manager cannot resolve `{}`",
+            identifier
+        )
+    }
+
+    fn resolve_library(&self, path: &str) -> anyhow::Result<String> {
+        anyhow::bail!("The dummy dependency manager cannot resolve `{}`", path)
+    }
+}